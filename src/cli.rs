@@ -0,0 +1,258 @@
+use crate::core::export::{ExportFormat, export_results};
+use crate::core::http_api::{self, HttpApiConfig};
+use crate::core::pg_server::{self, PgServerConfig};
+use crate::core::param_lint::Severity;
+use crate::core::report::{
+    REPORTS_DIR, apply_parameters, check_declared_parameters, load_report, run_report,
+};
+use crate::core::report_format::{self, CURRENT_FORMAT_VERSION};
+use crate::core::scheduler::{self, AGENDA_FILE};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+#[cfg(windows)]
+unsafe extern "system" {
+    fn AttachConsole(process_id: u32) -> i32;
+}
+
+// O executável é compilado com windows_subsystem = "windows"; sem isso as
+// mensagens dos subcomandos não aparecem no prompt que os chamou.
+fn attach_parent_console() {
+    #[cfg(windows)]
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+// Subcomandos sem interface gráfica. Retorna None quando o app deve abrir a UI.
+pub fn dispatch(args: &[String]) -> Option<i32> {
+    let command = args.first()?;
+
+    let handler: fn(&[String]) -> Result<(), String> = match command.as_str() {
+        "run" => run_report_command,
+        "pg-server" => run_pg_server,
+        "http-server" => run_http_server,
+        "agenda" => run_scheduler,
+        "validar" => run_validation,
+        _ => return None,
+    };
+
+    attach_parent_console();
+
+    match handler(&args[1..]) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            Some(1)
+        }
+    }
+}
+
+// Serviços opcionais que sobem junto com a interface, controlados pelo .env
+pub fn start_background_services() {
+    if env::var("API_AUTOSTART").map_or(false, |v| v.eq_ignore_ascii_case("true")) {
+        std::thread::spawn(|| {
+            if let Err(e) = http_api::run(HttpApiConfig::from_env()) {
+                println!("Erro na API HTTP: {}", e);
+            }
+        });
+    }
+    if env::var("AGENDA_AUTOSTART").map_or(false, |v| v.eq_ignore_ascii_case("true")) {
+        std::thread::spawn(|| scheduler::run_forever(PathBuf::from(AGENDA_FILE)));
+    }
+}
+
+fn option_value(args: &[String], name: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|idx| args.get(idx + 1))
+        .cloned()
+}
+
+fn option_values(args: &[String], name: &str) -> Vec<String> {
+    args.windows(2)
+        .filter(|pair| pair[0] == name)
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+// newreport run relatorios/vendas.json --param data_ini=2026-01-01 --out vendas.xlsx
+fn run_report_command(args: &[String]) -> Result<(), String> {
+    let report_path = args
+        .first()
+        .filter(|a| !a.starts_with("--"))
+        .map(PathBuf::from)
+        .ok_or("Uso: newreport run <relatorio.json> [--param id=valor]... --out <arquivo>")?;
+    let out_path = option_value(args, "--out")
+        .map(PathBuf::from)
+        .ok_or("Informe o arquivo de saída com --out (.csv, .xlsx ou .parquet)")?;
+
+    let format = match option_value(args, "--formato") {
+        Some(f) => ExportFormat::parse(&f).ok_or(format!("Formato inválido: {}", f))?,
+        None => ExportFormat::from_path(&out_path).ok_or(format!(
+            "Extensão de saída não suportada: {} (use .csv, .xlsx ou .parquet)",
+            out_path.display()
+        ))?,
+    };
+
+    let mut values = HashMap::new();
+    for pair in option_values(args, "--param") {
+        let (id, value) = pair
+            .split_once('=')
+            .ok_or(format!("Parâmetro inválido '{}': use --param id=valor", pair))?;
+        values.insert(id.trim().to_string(), value.to_string());
+    }
+
+    let report = load_report(&report_path)?;
+    check_declared_parameters(&report.parametros, &values)?;
+    let final_sql = apply_parameters(&report.query_sql, &report.parametros, &values)?;
+
+    let start = std::time::Instant::now();
+    let report_name = report_path.to_string_lossy().to_string();
+    let run = run_report(&final_sql, &report_name, &values)?;
+
+    ensure_parent_dir(&out_path)?;
+    export_results(&run.engine, &run.columns, run.total_rows, &out_path, format)?;
+
+    println!(
+        "✅ {} linhas exportadas para {} em {} ms",
+        run.total_rows,
+        out_path.display(),
+        start.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+fn ensure_parent_dir(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Erro ao criar a pasta {}: {}", dir.display(), e)),
+        _ => Ok(()),
+    }
+}
+
+fn run_pg_server(args: &[String]) -> Result<(), String> {
+    let mut config = PgServerConfig::from_env();
+    if let Some(host) = option_value(args, "--host") {
+        config.host = host;
+    }
+    if let Some(port) = option_value(args, "--porta") {
+        config.port = port
+            .parse()
+            .map_err(|_| format!("Porta inválida: {}", port))?;
+    }
+    pg_server::run(config)
+}
+
+fn run_http_server(args: &[String]) -> Result<(), String> {
+    let mut config = HttpApiConfig::from_env();
+    if let Some(host) = option_value(args, "--host") {
+        config.host = host;
+    }
+    if let Some(port) = option_value(args, "--porta") {
+        config.port = port
+            .parse()
+            .map_err(|_| format!("Porta inválida: {}", port))?;
+    }
+    http_api::run(config)
+}
+
+// newreport validar [pasta] [--migrar]
+fn run_validation(args: &[String]) -> Result<(), String> {
+    let dir = args
+        .first()
+        .filter(|a| !a.starts_with("--"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(REPORTS_DIR));
+    if !dir.is_dir() {
+        return Err(format!("Pasta de relatórios não encontrada: {}", dir.display()));
+    }
+
+    let checks = report_format::validate_all(&dir);
+    let mut with_errors = 0;
+    let mut with_warnings = 0;
+    for check in &checks {
+        let icon = if check.has_errors() {
+            with_errors += 1;
+            "❌"
+        } else if !check.issues.is_empty() {
+            with_warnings += 1;
+            "⚠️"
+        } else {
+            "✅"
+        };
+        let version = check
+            .original_version
+            .map(|v| format!(" (formato {})", v))
+            .unwrap_or_default();
+        println!("{} {}{}", icon, check.caminho, version);
+        for (severity, message) in &check.issues {
+            let marker = if *severity == Severity::Error { "erro" } else { "aviso" };
+            println!("     {}: {}", marker, message.replace('\n', "\n       "));
+        }
+    }
+
+    if args.iter().any(|a| a == "--migrar") {
+        let migrated = report_format::migrate_all(&dir)?;
+        println!(
+            "🔄 {} relatório(s) regravado(s) no formato {}",
+            migrated, CURRENT_FORMAT_VERSION
+        );
+    }
+
+    println!(
+        "{} relatório(s): {} com erro, {} com aviso",
+        checks.len(),
+        with_errors,
+        with_warnings
+    );
+    if with_errors > 0 {
+        return Err(format!("{} relatório(s) com erro", with_errors));
+    }
+    Ok(())
+}
+
+// newreport agenda [--listar | --executar <nome>]
+fn run_scheduler(args: &[String]) -> Result<(), String> {
+    let agenda_path = option_value(args, "--arquivo")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(AGENDA_FILE));
+    let agenda = scheduler::load_agenda(&agenda_path)?;
+
+    if args.iter().any(|a| a == "--listar") {
+        let now = chrono::Local::now();
+        for task in &agenda.tarefas {
+            let next = scheduler::next_run(task, now)
+                .map(|d| d.format("%d/%m/%Y %H:%M").to_string())
+                .unwrap_or_else(|| "agenda inválida".to_string());
+            let status = if task.ativo { "" } else { " (inativa)" };
+            println!("{}{} -> próxima execução: {}", task.nome, status, next);
+        }
+        return Ok(());
+    }
+
+    if let Some(name) = option_value(args, "--executar") {
+        let task = agenda
+            .tarefas
+            .iter()
+            .find(|t| t.nome == name)
+            .ok_or(format!("Tarefa '{}' não encontrada em {}", name, agenda_path.display()))?;
+        let record = scheduler::run_task(task);
+        if !record.sucesso {
+            return Err(record.erro);
+        }
+        println!("✅ {} linhas exportadas para {}", record.linhas, record.arquivo);
+        return Ok(());
+    }
+
+    for task in &agenda.tarefas {
+        scheduler::parse_schedule(&task.agenda)?;
+    }
+    println!("🗓️ Agendador iniciado com {} tarefas ({})", agenda.tarefas.len(), agenda_path.display());
+    scheduler::run_forever(agenda_path);
+    Ok(())
+}
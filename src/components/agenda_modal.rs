@@ -0,0 +1,97 @@
+use crate::core::scheduler::{self, AGENDA_FILE, RunRecord, ScheduledTask};
+use dioxus::prelude::*;
+use std::path::Path;
+
+#[component]
+pub fn AgendaModal(show: Signal<bool>, on_close: EventHandler<()>) -> Element {
+    let mut history = use_signal(|| scheduler::read_history(50));
+    let mut running_task = use_signal(|| None::<String>);
+
+    if !show() {
+        return rsx! {};
+    }
+
+    let (tasks, load_error): (Vec<ScheduledTask>, String) =
+        match scheduler::load_agenda(Path::new(AGENDA_FILE)) {
+            Ok(agenda) => (agenda.tarefas, String::new()),
+            Err(e) => (Vec::new(), e),
+        };
+    let now = chrono::Local::now();
+    let history_list: Vec<RunRecord> = history.read().clone();
+
+    rsx! {
+        div { class: "modal-overlay",
+            div { class: "modal-window modal-w900",
+                div { class: "modal-header", "🗓️ Relatórios Agendados ({AGENDA_FILE})" }
+                div { class: "modal-body modal-body-scrollable",
+                    if !load_error.is_empty() {
+                        div { class: "error-message-box", "{load_error}" }
+                    }
+                    table { class: "pg-table table-wrapper",
+                        thead { tr { th { "Tarefa" } th { "Relatório" } th { "Agenda" } th { "Próxima execução" } th { "" } } }
+                        tbody {
+                            {tasks.iter().map(|task| {
+                                let next = if task.ativo {
+                                    scheduler::next_run(task, now)
+                                        .map(|d| d.format("%d/%m/%Y %H:%M").to_string())
+                                        .unwrap_or_else(|| "agenda inválida".to_string())
+                                } else {
+                                    "inativa".to_string()
+                                };
+                                let is_running = running_task.read().as_deref() == Some(task.nome.as_str());
+                                let task_to_run = task.clone();
+                                rsx! {
+                                    tr { key: "{task.nome}",
+                                        td { "{task.nome}" } td { "{task.relatorio}" } td { code { "{task.agenda}" } } td { "{next}" }
+                                        td {
+                                            button {
+                                                class: "btn-classic btn-icon-small",
+                                                disabled: running_task.read().is_some(),
+                                                onclick: move |_| {
+                                                    let task = task_to_run.clone();
+                                                    running_task.set(Some(task.nome.clone()));
+                                                    spawn(async move {
+                                                        let _ = tokio::task::spawn_blocking(move || scheduler::run_task(&task)).await;
+                                                        history.set(scheduler::read_history(50));
+                                                        running_task.set(None);
+                                                    });
+                                                },
+                                                if is_running { "⏳" } else { "▶ Executar agora" }
+                                            }
+                                        }
+                                    }
+                                }
+                            })}
+                        }
+                    }
+                    if tasks.is_empty() && load_error.is_empty() {
+                        p { class: "empty-msg", "Nenhuma tarefa cadastrada." }
+                    }
+
+                    h3 { class: "section-title", "Histórico de execuções" }
+                    table { class: "pg-table table-wrapper",
+                        thead { tr { th { "Início" } th { "Tarefa" } th { "Situação" } th { "Linhas" } th { "Duração" } th { "Arquivo / Erro" } } }
+                        tbody {
+                            {history_list.iter().enumerate().map(|(i, record)| rsx! {
+                                tr { key: "{i}",
+                                    td { "{record.inicio}" }
+                                    td { "{record.tarefa}" }
+                                    td { class: if record.sucesso { "status-ok" } else { "status-fail" }, if record.sucesso { "✅ Sucesso" } else { "❌ Falha" } }
+                                    td { "{record.linhas}" }
+                                    td { "{record.duracao_ms} ms" }
+                                    td { if record.sucesso { "{record.arquivo}" } else { "{record.erro}" } }
+                                }
+                            })}
+                        }
+                    }
+                    if history_list.is_empty() {
+                        p { class: "empty-msg", "Nenhuma execução registrada ainda." }
+                    }
+                }
+                div { class: "modal-footer",
+                    button { class: "btn-classic", onclick: move |_| on_close.call(()), "Fechar" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use dioxus::prelude::*;
+
+// Pergunta antes de ações que mexem em arquivos
+#[component]
+pub fn ConfirmModal(
+    show: Signal<bool>,
+    title: String,
+    message: String,
+    confirm_label: String,
+    on_confirm: EventHandler<()>,
+) -> Element {
+    if !show() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "modal-overlay overlay-confirm",
+            div { class: "modal-window modal-w400",
+                div { class: "modal-header", "{title}" }
+                div { class: "modal-body confirm-message", "{message}" }
+                div { class: "modal-footer",
+                    button { class: "btn-classic flex-1", onclick: move |_| show.set(false), "Cancelar" }
+                    button { class: "btn-classic btn-danger flex-1",
+                        onclick: move |_| {
+                            show.set(false);
+                            on_confirm.call(());
+                        },
+                        "{confirm_label}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::core::history::{ReportVersion, list_versions, parameters_text};
+use crate::core::report::ReportDefinition;
+use crate::core::text_diff::{DiffRow, has_changes, side_by_side};
+use dioxus::prelude::*;
+use std::path::Path;
+
+#[component]
+fn DiffTable(title: String, rows: Vec<DiffRow>) -> Element {
+    rsx! {
+        div { class: "history-diff-section",
+            div { class: "history-diff-title", "{title}" }
+            if !has_changes(&rows) {
+                div { class: "empty-msg", "Sem diferenças." }
+            } else {
+                table { class: "history-diff-table",
+                    thead { tr { th { colspan: 2, "Versão selecionada" } th { colspan: 2, "Editor atual" } } }
+                    tbody {
+                        {rows.iter().enumerate().map(|(i, row)| {
+                            let css = row.kind.css_class();
+                            let (left_no, left_text) = row.left.clone().map(|(n, t)| (n.to_string(), t)).unwrap_or_default();
+                            let (right_no, right_text) = row.right.clone().map(|(n, t)| (n.to_string(), t)).unwrap_or_default();
+                            rsx! {
+                                tr { key: "{i}", class: "{css}",
+                                    td { class: "diff-line-no", "{left_no}" }
+                                    td { class: "diff-text diff-left", "{left_text}" }
+                                    td { class: "diff-line-no", "{right_no}" }
+                                    td { class: "diff-text diff-right", "{right_text}" }
+                                }
+                            }
+                        })}
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Lista as versões gravadas e compara a escolhida com o que está no editor
+#[component]
+pub fn HistoryModal(
+    show: Signal<bool>,
+    report_path: String,
+    current: ReportDefinition,
+    on_restore: EventHandler<ReportVersion>,
+) -> Element {
+    let mut selected = use_signal(|| 0usize);
+
+    if !show() {
+        return rsx! {};
+    }
+
+    let versions = list_versions(Path::new(&report_path));
+    let index = selected().min(versions.len().saturating_sub(1));
+    let chosen = versions.get(index).cloned();
+
+    rsx! {
+        div { class: "modal-overlay",
+            div { class: "modal-window modal-w900 history-window",
+                div { class: "modal-header", "🕘 Histórico de Versões" }
+                div { class: "history-body",
+                    if versions.is_empty() {
+                        div { class: "empty-msg", "Nenhuma versão gravada ainda. Cada vez que o relatório é salvo, uma versão entra no histórico." }
+                    } else {
+                        div { class: "history-list",
+                            {versions.iter().enumerate().map(|(i, v)| {
+                                let comment = if v.comentario.is_empty() { "(sem comentário)".to_string() } else { v.comentario.clone() };
+                                rsx! {
+                                    div { key: "{i}",
+                                        class: if i == index { "history-item selected" } else { "history-item" },
+                                        onclick: move |_| selected.set(i),
+                                        div { class: "history-item-date", "{v.salvo_em}" }
+                                        div { class: "history-item-comment", "{comment}" }
+                                    }
+                                }
+                            })}
+                        }
+                        if let Some(version) = chosen.clone() {
+                            div { class: "history-diff",
+                                DiffTable { title: "SQL".to_string(), rows: side_by_side(&version.relatorio.query_sql, &current.query_sql) }
+                                DiffTable {
+                                    title: "Parâmetros".to_string(),
+                                    rows: side_by_side(&parameters_text(&version.relatorio.parametros), &parameters_text(&current.parametros))
+                                }
+                                DiffTable { title: "Descrição".to_string(), rows: side_by_side(&version.relatorio.descricao, &current.descricao) }
+                            }
+                        }
+                    }
+                }
+                div { class: "modal-footer",
+                    if let Some(version) = chosen {
+                        button { class: "btn-classic",
+                            onclick: move |_| {
+                                on_restore.call(version.clone());
+                                show.set(false);
+                            },
+                            "↩️ Restaurar esta versão"
+                        }
+                    }
+                    button { class: "btn-classic", onclick: move |_| show.set(false), "Fechar" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use crate::core::query_plan::format_bytes;
+use crate::core::telemetry::{self, ReportStats, TelemetryEvent};
+use dioxus::prelude::*;
+
+const HISTORY_LIMIT: usize = 30;
+
+#[component]
+pub fn PerformanceModal(show: Signal<bool>, on_close: EventHandler<()>) -> Element {
+    let mut selected_report = use_signal(|| None::<String>);
+
+    let events = use_resource(move || async move {
+        if !show() {
+            return Vec::new();
+        }
+        tokio::task::spawn_blocking(telemetry::read_events)
+            .await
+            .unwrap_or_default()
+    });
+
+    if !show() {
+        return rsx! {};
+    }
+
+    let all_events: Vec<TelemetryEvent> = events.read().clone().unwrap_or_default();
+    let reports = telemetry::summarize(&all_events);
+    let slowest = telemetry::slowest_stages(&reports, 10);
+
+    let current = selected_report
+        .read()
+        .clone()
+        .or_else(|| reports.first().map(|r| r.relatorio.clone()));
+    let current_stats: Option<ReportStats> = current
+        .as_ref()
+        .and_then(|name| reports.iter().find(|r| &r.relatorio == name).cloned());
+    let history: Vec<TelemetryEvent> = current
+        .as_ref()
+        .map(|name| {
+            all_events
+                .iter()
+                .rev()
+                .filter(|e| &e.relatorio == name)
+                .take(HISTORY_LIMIT)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    rsx! {
+        div { class: "modal-overlay overlay-dark",
+            div { class: "modal-window modal-w80-h80 perf-window",
+                h2 { class: "logs-title", "📈 Desempenho dos Relatórios" }
+
+                if events.read().is_none() {
+                    div { class: "empty-msg", "Lendo telemetria..." }
+                } else if all_events.is_empty() {
+                    div { class: "empty-msg", "Nenhum evento registrado ainda. Execute um relatório para começar." }
+                } else {
+                    div { class: "perf-layout",
+                        div { class: "perf-report-list",
+                            {reports.iter().map(|r| {
+                                let name = r.relatorio.clone();
+                                let is_selected = current.as_deref() == Some(r.relatorio.as_str());
+                                rsx! {
+                                    div {
+                                        key: "{r.relatorio}",
+                                        class: if is_selected { "perf-report-item selected" } else { "perf-report-item" },
+                                        onclick: move |_| selected_report.set(Some(name.clone())),
+                                        div { class: "perf-report-name", "{r.relatorio}" }
+                                        div { class: "perf-report-meta",
+                                            "{r.execucoes} execuções · última {r.ultima}"
+                                            if r.falhas > 0 { span { class: "status-fail", " · {r.falhas} falhas" } }
+                                        }
+                                    }
+                                }
+                            })}
+                        }
+
+                        div { class: "perf-detail",
+                            h3 { class: "section-title", "Etapas mais lentas (todos os relatórios, p95)" }
+                            table { class: "pg-table table-wrapper",
+                                thead { tr { th { "Relatório" } th { "Etapa" } th { "Execuções" } th { "p50" } th { "p95" } th { "Máx." } } }
+                                tbody {
+                                    {slowest.iter().enumerate().map(|(i, st)| rsx! {
+                                        tr { key: "{i}",
+                                            td { "{st.relatorio}" } td { "{st.etapa}" } td { "{st.execucoes}" }
+                                            td { "{st.p50_ms} ms" } td { "{st.p95_ms} ms" } td { "{st.max_ms} ms" }
+                                        }
+                                    })}
+                                }
+                            }
+
+                            if let Some(stats) = current_stats {
+                                h3 { class: "section-title", "Etapas de {stats.relatorio}" }
+                                table { class: "pg-table table-wrapper",
+                                    thead { tr { th { "Etapa" } th { "Execuções" } th { "p50" } th { "p95" } th { "Máx." } th { "Linhas (média)" } } }
+                                    tbody {
+                                        {stats.etapas.iter().enumerate().map(|(i, st)| rsx! {
+                                            tr { key: "{i}",
+                                                td { "{st.etapa}" } td { "{st.execucoes}" } td { "{st.p50_ms} ms" }
+                                                td { "{st.p95_ms} ms" } td { "{st.max_ms} ms" } td { "{st.linhas_media}" }
+                                            }
+                                        })}
+                                    }
+                                }
+
+                                if stats.tendencia.len() > 1 {
+                                    h3 { class: "section-title", "Extração por versão do schema (mudanças no ERP)" }
+                                    table { class: "pg-table table-wrapper",
+                                        thead { tr { th { "Schema" } th { "Desde" } th { "Execuções" } th { "p50" } th { "Variação" } } }
+                                        tbody {
+                                            {stats.tendencia.iter().enumerate().map(|(i, t)| {
+                                                let variation = if i == 0 {
+                                                    "-".to_string()
+                                                } else {
+                                                    let previous = stats.tendencia[i - 1].p50_ms.max(1) as f64;
+                                                    format!("{:+.0}%", (t.p50_ms as f64 - previous) / previous * 100.0)
+                                                };
+                                                let short_hash = t.schema_hash.chars().take(8).collect::<String>();
+                                                rsx! {
+                                                    tr { key: "{i}",
+                                                        td { code { "{short_hash}" } } td { "{t.desde}" } td { "{t.execucoes}" }
+                                                        td { "{t.p50_ms} ms" } td { "{variation}" }
+                                                    }
+                                                }
+                                            })}
+                                        }
+                                    }
+                                }
+                            }
+
+                            h3 { class: "section-title", "Histórico recente" }
+                            table { class: "pg-table table-wrapper",
+                                thead { tr { th { "Momento" } th { "Etapa" } th { "Duração" } th { "Linhas" } th { "Memória" } th { "Tabelas" } th { "Parâmetros" } } }
+                                tbody {
+                                    {history.iter().enumerate().map(|(i, e)| {
+                                        let tables = e.tabelas.join(", ");
+                                        let bytes = if e.bytes > 0 { format_bytes(e.bytes) } else { "-".to_string() };
+                                        let params_hash = e.parametros_hash.chars().take(8).collect::<String>();
+                                        rsx! {
+                                            tr { key: "{i}", class: if e.sucesso { "" } else { "perf-row-fail" }, title: "{e.erro}",
+                                                td { "{e.momento}" } td { "{e.etapa}" } td { "{e.duracao_ms} ms" } td { "{e.linhas}" }
+                                                td { "{bytes}" } td { "{tables}" } td { code { "{params_hash}" } }
+                                            }
+                                        }
+                                    })}
+                                }
+                            }
+                        }
+                    }
+                }
+
+                button { class: "btn-classic btn-close-logs", onclick: move |_| on_close.call(()), "✖ Fechar" }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::core::query_plan::{PlanNode, format_bytes, format_duration};
+use dioxus::prelude::*;
+
+#[component]
+pub fn PlanModal(
+    show: Signal<bool>,
+    nodes: Vec<PlanNode>,
+    loading: bool,
+    error: String,
+    on_close: EventHandler<()>,
+) -> Element {
+    if !show() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "modal-overlay",
+            div { class: "modal-window modal-w900",
+                div { class: "modal-header", "📊 Plano de Execução (EXPLAIN ANALYZE)" }
+                div { class: "modal-body modal-body-scrollable plan-body",
+                    if loading {
+                        div { class: "empty-msg", "Executando a consulta e coletando métricas..." }
+                    } else if !error.is_empty() {
+                        div { class: "error-message-box", "{error}" }
+                    } else if nodes.is_empty() {
+                        div { class: "empty-msg", "Nenhum operador encontrado no plano." }
+                    } else {
+                        table { class: "pg-table table-wrapper plan-table",
+                            thead { tr { th { "Operador" } th { "Linhas" } th { "Tempo" } th { "Memória" } th { "% do tempo" } } }
+                            tbody {
+                                {nodes.iter().enumerate().map(|(i, node)| {
+                                    let indent = format!("padding-left: {}px", 8 + node.depth * 18);
+                                    let row_class = match (node.is_hot(), node.is_join_or_sort()) {
+                                        (true, true) => "plan-hot plan-hot-join",
+                                        (true, false) => "plan-hot",
+                                        _ => "",
+                                    };
+                                    let rows = node.output_rows.map(|r| r.to_string()).unwrap_or_else(|| "-".to_string());
+                                    let time = node.elapsed_ns.map(format_duration).unwrap_or_else(|| "-".to_string());
+                                    let mem = node.mem_bytes.map(format_bytes).unwrap_or_else(|| "-".to_string());
+                                    let share = node.time_share * 100.0;
+                                    let bar = format!("width: {:.0}%", share);
+                                    let share_text = format!("{:.1}%", share);
+                                    let tooltip = node.metrics.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("\n");
+                                    rsx! {
+                                        tr { key: "{i}", class: "{row_class}", title: "{tooltip}",
+                                            td { style: "{indent}",
+                                                div { class: "plan-operator", if node.depth > 0 { "└ " } "{node.operator}" }
+                                                div { class: "plan-details", "{node.details}" }
+                                            }
+                                            td { "{rows}" }
+                                            td { "{time}" }
+                                            td { "{mem}" }
+                                            td {
+                                                div { class: "plan-share",
+                                                    div { class: "plan-share-bar", style: "{bar}" }
+                                                    span { "{share_text}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                })}
+                            }
+                        }
+                        p { class: "plan-legend", "Linhas em destaque consomem 25% ou mais do tempo; JOINs e ordenações dominantes aparecem em vermelho." }
+                    }
+                }
+                div { class: "modal-footer",
+                    button { class: "btn-classic", onclick: move |_| on_close.call(()), "Fechar" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::core::engine::{DataEngine, read_first_rows};
+use crate::core::preview::{ColumnStats, PREVIEW_SIZES, batch_headers, batch_rows, column_stats};
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq, Default)]
+struct PreviewData {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    stats: Vec<ColumnStats>,
+}
+
+#[component]
+pub fn PreviewModal(
+    mut table_name: Signal<Option<String>>,
+    engine: Signal<DataEngine>,
+) -> Element {
+    let mut limit = use_signal(|| PREVIEW_SIZES[0]);
+    let mut show_stats = use_signal(|| false);
+
+    let preview = use_resource(move || async move {
+        let Some(name) = table_name() else {
+            return Ok(PreviewData::default());
+        };
+        let rows_limit = limit();
+        let (base_path, config) = {
+            let engine_lock = engine.read();
+            let config = engine_lock
+                .schema
+                .iter()
+                .find(|(k, _)| k.to_lowercase() == name.to_lowercase())
+                .map(|(_, v)| v.clone());
+            (engine_lock.base_path.clone(), config)
+        };
+        let config = config.ok_or(format!("Tabela {} não mapeada no schema!", name))?;
+
+        tokio::task::spawn_blocking(move || -> Result<PreviewData, String> {
+            let batch = read_first_rows(&base_path, &name, &config, &config.columns, rows_limit)?;
+            Ok(PreviewData {
+                headers: batch_headers(&batch),
+                rows: batch_rows(&batch),
+                stats: column_stats(&batch),
+            })
+        })
+        .await
+        .unwrap_or(Err("Erro crítico ao ler a amostra".to_string()))
+    });
+
+    let Some(name) = table_name() else {
+        return rsx! {};
+    };
+
+    let state: Option<Result<PreviewData, String>> = preview.read().clone();
+
+    rsx! {
+        div { class: "modal-overlay",
+            div { class: "modal-window modal-w80-h80 preview-window",
+                div { class: "preview-toolbar",
+                    h3 { class: "preview-title", "👁 Prévia de {name}" }
+                    select { class: "input-classic input-h28", value: "{limit}",
+                        onchange: move |evt| { if let Ok(n) = evt.value().parse() { limit.set(n); } },
+                        {PREVIEW_SIZES.iter().map(|n| rsx! { option { key: "{n}", value: "{n}", "Primeiros {n} registros" } })}
+                    }
+                    button { class: "btn-classic", onclick: move |_| show_stats.toggle(), if show_stats() { "▦ Ver registros" } else { "📊 Estatísticas" } }
+                    button { class: "btn-classic", onclick: move |_| table_name.set(None), "✖ Fechar" }
+                }
+
+                div { class: "data-container preview-grid",
+                    match state {
+                        None => rsx! { div { class: "empty-msg", "Lendo o arquivo .dat..." } },
+                        Some(Err(e)) => rsx! { div { class: "error-message-box", "{e}" } },
+                        Some(Ok(data)) if data.rows.is_empty() => rsx! { div { class: "empty-msg", "Nenhum registro ativo encontrado." } },
+                        Some(Ok(data)) if show_stats() => rsx! {
+                            table { class: "pg-table table-wrapper",
+                                thead { tr { th { "Coluna" } th { "Tipo" } th { "Nulos" } th { "Vazios" } th { "Distintos" } th { "Mínimo" } th { "Máximo" } } }
+                                tbody {
+                                    {data.stats.iter().map(|st| rsx! {
+                                        tr { key: "{st.nome}",
+                                            td { "{st.nome}" } td { "{st.tipo}" } td { "{st.nulos}" } td { "{st.vazios}" }
+                                            td { "{st.distintos}" } td { "{st.minimo}" } td { "{st.maximo}" }
+                                        }
+                                    })}
+                                }
+                            }
+                        },
+                        Some(Ok(data)) => rsx! {
+                            table { class: "pg-table table-wrapper",
+                                thead { tr { {data.headers.iter().map(|h| rsx! { th { key: "{h}", class: "sticky-header", "{h}" } })} } }
+                                tbody {
+                                    {data.rows.iter().enumerate().map(|(i, row)| rsx! {
+                                        tr { key: "{i}", {row.iter().enumerate().map(|(j, cell)| rsx! { td { key: "{j}", "{cell}" } })} }
+                                    })}
+                                }
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
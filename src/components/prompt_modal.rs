@@ -0,0 +1,44 @@
+use dioxus::prelude::*;
+
+// Pede um nome (nova pasta, renomear). O valor começa em `initial` quando o modal é
+// montado; quem usa renderiza o modal só enquanto ele está aberto.
+#[component]
+pub fn PromptModal(
+    show: Signal<bool>,
+    title: String,
+    label: String,
+    initial: String,
+    on_submit: EventHandler<String>,
+) -> Element {
+    let mut value = use_signal(|| initial.clone());
+
+    if !show() {
+        return rsx! {};
+    }
+
+    let mut submit = move || {
+        show.set(false);
+        on_submit.call(value.peek().clone());
+    };
+
+    rsx! {
+        div { class: "modal-overlay overlay-confirm",
+            div { class: "modal-window modal-w400",
+                div { class: "modal-header", "{title}" }
+                div { class: "modal-body modal-body-scrollable",
+                    div { class: "form-group",
+                        label { "{label}" }
+                        input { class: "input-classic input-h30", autofocus: true, value: "{value}",
+                            oninput: move |evt| value.set(evt.value()),
+                            onkeydown: move |evt| { if evt.key() == Key::Enter { submit(); } }
+                        }
+                    }
+                }
+                div { class: "modal-footer",
+                    button { class: "btn-classic flex-1", onclick: move |_| show.set(false), "Cancelar" }
+                    button { class: "btn-classic btn-primary flex-1", onclick: move |_| submit(), "OK" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::components::preview_modal::PreviewModal;
+use crate::components::sql_editor::insert_into_sql_editor;
+use crate::core::engine::{DataEngine, arrow_field_for, dat_file_stats};
+use crate::core::query_plan::format_bytes;
+use dioxus::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, PartialEq)]
+struct TableInfo {
+    rows: Option<u32>,
+    size_bytes: Option<u64>,
+}
+
+#[component]
+pub fn SchemaBrowser(engine: Signal<DataEngine>) -> Element {
+    let mut search_text = use_signal(|| String::new());
+    let mut expanded = use_signal(|| HashSet::<String>::new());
+    let mut preview_table = use_signal(|| None::<String>);
+
+    let table_infos = use_resource(move || async move {
+        let (base_path, names): (String, Vec<String>) = {
+            let engine_lock = engine.read();
+            (
+                engine_lock.base_path.clone(),
+                engine_lock.schema.keys().cloned().collect(),
+            )
+        };
+        tokio::task::spawn_blocking(move || {
+            names
+                .into_iter()
+                .map(|name| {
+                    let stats = dat_file_stats(&base_path, &name);
+                    let info = TableInfo {
+                        rows: stats.as_ref().map(|s| s.rows),
+                        size_bytes: stats.as_ref().map(|s| s.size_bytes),
+                    };
+                    (name, info)
+                })
+                .collect::<HashMap<String, TableInfo>>()
+        })
+        .await
+        .unwrap_or_default()
+    });
+
+    let search = search_text.read().to_lowercase();
+    let infos = table_infos.read().clone().unwrap_or_default();
+    let schema = engine.read().schema.clone();
+
+    // Na busca, a tabela aparece se o nome dela ou de alguma coluna bate
+    let tables: Vec<(String, bool)> = schema
+        .iter()
+        .filter_map(|(name, config)| {
+            if search.is_empty() || name.to_lowercase().contains(&search) {
+                return Some((name.clone(), false));
+            }
+            config
+                .columns
+                .iter()
+                .any(|c| c.name.to_lowercase().contains(&search))
+                .then(|| (name.clone(), true))
+        })
+        .collect();
+
+    rsx! {
+        div { class: "schema-browser",
+            PreviewModal { table_name: preview_table, engine: engine }
+            div { class: "schema-browser-header",
+                input { class: "input-classic schema-search", placeholder: "Buscar tabela ou coluna...", value: "{search_text}", oninput: move |evt| search_text.set(evt.value()) }
+                span { class: "schema-count", "{tables.len()} tabelas" }
+            }
+            div { class: "schema-browser-list",
+                if schema.is_empty() {
+                    div { class: "empty-msg", "schema.toml não encontrado ou vazio." }
+                }
+                {tables.into_iter().map(|(name, matched_by_column)| {
+                    let config = schema.get(&name).cloned();
+                    let is_open = matched_by_column || expanded.read().contains(&name);
+                    let info = infos.get(&name).cloned();
+                    let rows = info.as_ref().and_then(|i| i.rows).map(|r| format!("{} reg.", r)).unwrap_or_else(|| "-".to_string());
+                    let size = info.as_ref().and_then(|i| i.size_bytes).map(format_bytes).unwrap_or_else(|| "sem .dat".to_string());
+                    let toggle_name = name.clone();
+                    let insert_name = name.to_lowercase();
+                    let preview_name = name.clone();
+                    rsx! {
+                        div { key: "{name}", class: "schema-table",
+                            div { class: "schema-table-row",
+                                span {
+                                    class: "schema-toggle",
+                                    onclick: move |_| {
+                                        let mut set = expanded.write();
+                                        if !set.remove(&toggle_name) {
+                                            set.insert(toggle_name.clone());
+                                        }
+                                    },
+                                    if is_open { "▾" } else { "▸" }
+                                }
+                                span { class: "schema-table-name", title: "Inserir no SQL", onclick: move |_| insert_into_sql_editor(&insert_name), "{name}" }
+                                span { class: "schema-table-meta", "{rows} · {size}" }
+                                span { class: "schema-preview", title: "Prévia dos primeiros registros", onclick: move |_| preview_table.set(Some(preview_name.clone())), "👁" }
+                            }
+                            if is_open {
+                                if let Some(config) = config {
+                                    div { class: "schema-columns",
+                                        {config.columns.into_iter().map(|col| {
+                                            let highlighted = !search.is_empty() && col.name.to_lowercase().contains(&search);
+                                            let arrow_type = arrow_field_for(&col).data_type().to_string();
+                                            let insert_col = col.name.to_lowercase();
+                                            rsx! {
+                                                div {
+                                                    key: "{col.name}",
+                                                    class: if highlighted { "schema-column highlighted" } else { "schema-column" },
+                                                    title: "Inserir no SQL",
+                                                    onclick: move |_| insert_into_sql_editor(&insert_col),
+                                                    span { class: "schema-column-name", "{col.name}" }
+                                                    span { class: "schema-column-type", "{col.field_type}({col.length}) · {arrow_type}" }
+                                                }
+                                            }
+                                        })}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                })}
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::core::autocomplete::{
+    Completion, CompletionSources, byte_to_utf16, complete, function_names, utf16_to_byte,
+};
+use crate::core::engine::DataEngine;
+use crate::core::report::ReportParameter;
+use crate::core::sql_diagnostics::SqlDiagnostic;
+use crate::core::sql_highlight::highlight;
+use dioxus::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// Liga o <textarea> ao Dioxus: manda texto, cursor e a posição do cursor na tela a
+// cada digitação, e repassa as teclas de navegação enquanto o popup está aberto.
+// Também mantém o realce e a numeração rolando junto com o texto.
+const EDITOR_HOOK_JS: &str = r#"
+const attach = () => {
+    const el = document.getElementById('sql-editor');
+    if (!el) { setTimeout(attach, 50); return; }
+    if (el.dataset.autocomplete) return;
+    el.dataset.autocomplete = '1';
+
+    const caret = () => {
+        const style = getComputedStyle(el);
+        const mirror = document.createElement('div');
+        for (const p of ['fontFamily', 'fontSize', 'lineHeight', 'letterSpacing', 'tabSize',
+                         'paddingTop', 'paddingLeft', 'borderTopWidth', 'borderLeftWidth']) {
+            mirror.style[p] = style[p];
+        }
+        mirror.style.position = 'absolute';
+        mirror.style.visibility = 'hidden';
+        mirror.style.whiteSpace = 'pre';
+        mirror.textContent = el.value.substring(0, el.selectionStart);
+        const marker = document.createElement('span');
+        marker.textContent = '\u200b';
+        mirror.appendChild(marker);
+        document.body.appendChild(mirror);
+        const x = el.offsetLeft + marker.offsetLeft - el.scrollLeft;
+        const y = el.offsetTop + marker.offsetTop - el.scrollTop + (parseFloat(style.lineHeight) || 18);
+        document.body.removeChild(mirror);
+        return { x, y };
+    };
+
+    const send = (tipo, tecla) => {
+        const pos = caret();
+        dioxus.send({ tipo, tecla: tecla || '', texto: el.value, pos: el.selectionStart, x: pos.x, y: pos.y });
+    };
+    const popupOpen = () => document.getElementById('sql-autocomplete') !== null;
+
+    el.addEventListener('input', () => {
+        if (el.dataset.skipAutocomplete) { delete el.dataset.skipAutocomplete; return; }
+        send('digitou');
+    });
+    el.addEventListener('keydown', (e) => {
+        if (e.ctrlKey && e.code === 'Space') { e.preventDefault(); send('forcar'); return; }
+        if (popupOpen() && ['ArrowUp', 'ArrowDown', 'Enter', 'Tab', 'Escape'].includes(e.key)) {
+            e.preventDefault();
+            send('tecla', e.key);
+        }
+    });
+    el.addEventListener('click', () => send('fechar'));
+    const syncScroll = () => {
+        const layer = document.getElementById('sql-highlight');
+        const gutter = document.getElementById('sql-gutter');
+        if (layer) { layer.scrollTop = el.scrollTop; layer.scrollLeft = el.scrollLeft; }
+        if (gutter) { gutter.scrollTop = el.scrollTop; }
+    };
+    el.addEventListener('scroll', () => { syncScroll(); if (popupOpen()) send('fechar'); });
+    el.addEventListener('input', () => requestAnimationFrame(syncScroll));
+    el.addEventListener('blur', () => setTimeout(() => send('fechar'), 150));
+};
+attach();
+"#;
+
+const SCROLL_TO_SELECTED_JS: &str =
+    "document.querySelector('.autocomplete-item.selected')?.scrollIntoView({ block: 'nearest' });";
+
+#[derive(Deserialize)]
+struct EditorEvent {
+    tipo: String,
+    tecla: String,
+    texto: String,
+    pos: usize,
+    x: f64,
+    y: f64,
+}
+
+#[derive(Clone, PartialEq)]
+struct Popup {
+    completion: Completion,
+    // Texto de quando a sugestão foi calculada; as posições se referem a ele
+    text: String,
+    selected: usize,
+    x: f64,
+    y: f64,
+}
+
+// Troca o trecho `range` (posições UTF-16 do textarea) por `text`, ou insere no cursor
+fn replace_in_sql_editor(text: &str, range: Option<(usize, usize)>) {
+    let literal = serde_json::to_string(text).unwrap_or_else(|_| "\"\"".to_string());
+    let (start, end) = match range {
+        Some((start, end)) => (start.to_string(), end.to_string()),
+        None => (
+            "el.selectionStart".to_string(),
+            "el.selectionEnd".to_string(),
+        ),
+    };
+    let js = format!(
+        r#"const el = document.getElementById('sql-editor');
+        if (el) {{
+            el.focus();
+            el.setRangeText({}, {}, {}, 'end');
+            el.dataset.skipAutocomplete = '1';
+            el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+        }}"#,
+        literal, start, end
+    );
+    let _ = eval(&js);
+}
+
+// Insere o texto na posição do cursor do <textarea id="sql-editor"> e avisa o Dioxus
+pub fn insert_into_sql_editor(text: &str) {
+    replace_in_sql_editor(text, None);
+}
+
+fn accept(mut popup: Signal<Option<Popup>>, index: usize) {
+    let Some(current) = popup.peek().clone() else {
+        return;
+    };
+    popup.set(None);
+    if let Some(item) = current.completion.items.get(index) {
+        let start = byte_to_utf16(&current.text, current.completion.start);
+        let end = byte_to_utf16(&current.text, current.completion.end);
+        replace_in_sql_editor(&item.insert, Some((start, end)));
+    }
+}
+
+#[component]
+pub fn SqlEditor(
+    query_text: Signal<String>,
+    engine: Signal<DataEngine>,
+    parameters: Signal<Vec<ReportParameter>>,
+    mut diagnostics: Signal<Vec<SqlDiagnostic>>,
+) -> Element {
+    let mut popup = use_signal(|| None::<Popup>);
+    let functions = use_hook(|| function_names(&engine.peek().ctx));
+
+    use_future(move || {
+        let functions = functions.clone();
+        async move {
+            let mut channel = eval(EDITOR_HOOK_JS);
+            while let Ok(value) = channel.recv().await {
+                let Ok(event) = serde_json::from_value::<EditorEvent>(value) else {
+                    continue;
+                };
+                match (event.tipo.as_str(), event.tecla.as_str()) {
+                    ("tecla", "ArrowDown") | ("tecla", "ArrowUp") => {
+                        if let Some(p) = popup.write().as_mut() {
+                            let len = p.completion.items.len();
+                            p.selected = if event.tecla == "ArrowDown" {
+                                (p.selected + 1) % len
+                            } else {
+                                (p.selected + len - 1) % len
+                            };
+                        }
+                        let _ = eval(SCROLL_TO_SELECTED_JS);
+                    }
+                    ("tecla", "Enter") | ("tecla", "Tab") => {
+                        let selected = popup.peek().as_ref().map_or(0, |p| p.selected);
+                        accept(popup, selected);
+                    }
+                    ("digitou", _) | ("forcar", _) => {
+                        let cursor = utf16_to_byte(&event.texto, event.pos);
+                        let engine_lock = engine.peek();
+                        let params = parameters.peek();
+                        let sources = CompletionSources {
+                            schema: &engine_lock.schema,
+                            parameters: &params,
+                            functions: &functions,
+                        };
+                        let forced = event.tipo == "forcar";
+                        let completion = complete(&event.texto, cursor, &sources, forced);
+                        popup.set(completion.map(|completion| Popup {
+                            completion,
+                            text: event.texto.clone(),
+                            selected: 0,
+                            x: event.x,
+                            y: event.y,
+                        }));
+                    }
+                    _ => popup.set(None),
+                }
+            }
+        }
+    });
+
+    let text = query_text();
+    let marks: Vec<_> = diagnostics.read().iter().map(|d| d.start..d.end).collect();
+    let spans = highlight(&text, &marks);
+    let line_count = text.split('\n').count();
+    let error_lines: HashMap<usize, String> = diagnostics
+        .read()
+        .iter()
+        .map(|d| (d.line, d.message.clone()))
+        .collect();
+    let first_error = diagnostics.read().first().cloned();
+
+    rsx! {
+        div { class: "sql-editor-body",
+            div { id: "sql-gutter", class: "sql-gutter",
+                {(1..=line_count).map(|n| {
+                    let message = error_lines.get(&n).cloned();
+                    rsx! {
+                        div {
+                            key: "{n}",
+                            class: if message.is_some() { "sql-gutter-line error" } else { "sql-gutter-line" },
+                            title: message.unwrap_or_default(),
+                            "{n}"
+                        }
+                    }
+                })}
+            }
+            div { class: "sql-code-area",
+                // Camada colorida atrás do textarea (texto transparente, só o cursor aparece)
+                pre { id: "sql-highlight", class: "sql-highlight",
+                    {spans.into_iter().enumerate().map(|(i, (range, class, marked))| {
+                        let piece = text[range].to_string();
+                        let css = if marked { format!("{} sql-error-mark", class.css_class()) } else { class.css_class().to_string() };
+                        rsx! { span { key: "{i}", class: "{css}", "{piece}" } }
+                    })}
+                    "\n"
+                }
+                textarea {
+                    id: "sql-editor", class: "sql-editor", spellcheck: false, value: "{query_text}",
+                    oninput: move |evt| {
+                        query_text.set(evt.value());
+                        if !diagnostics.peek().is_empty() {
+                            diagnostics.set(Vec::new());
+                        }
+                    }
+                }
+                if let Some(p) = popup() {
+                    div { id: "sql-autocomplete", class: "autocomplete-popup", style: "left: {p.x}px; top: {p.y}px;",
+                        {p.completion.items.iter().enumerate().map(|(i, item)| {
+                            let badge = item.kind.badge();
+                            rsx! {
+                                div {
+                                    key: "{i}",
+                                    class: if i == p.selected { "autocomplete-item selected" } else { "autocomplete-item" },
+                                    onmousedown: move |evt| { evt.prevent_default(); accept(popup, i); },
+                                    span { class: "autocomplete-kind", "{badge}" }
+                                    span { class: "autocomplete-label", "{item.label}" }
+                                    span { class: "autocomplete-detail", "{item.detail}" }
+                                }
+                            }
+                        })}
+                    }
+                }
+            }
+        }
+        if let Some(d) = first_error {
+            div { class: "sql-error-bar", "⚠️ Linha {d.line}, coluna {d.column}: {d.message}" }
+        }
+    }
+}
@@ -12,6 +12,8 @@ pub fn StatusModal(
     status: StatusType,
     message: String,
     sql_content: String,
+    // Linha exata do erro (a partir de 1), quando o editor conseguiu localizar
+    error_line: Option<usize>,
     on_close: EventHandler<()>,
 ) -> Element {
     if !show() {
@@ -69,7 +71,9 @@ pub fn StatusModal(
                     if !sql_content.is_empty() {
                         div { class: "sql-viewer",
                             {sql_lines.iter().enumerate().map(|(i, line)| {
-                                let is_suspect = if let Some(ref term) = highlight_term {
+                                let is_suspect = if let Some(error_line) = error_line {
+                                    i + 1 == error_line
+                                } else if let Some(ref term) = highlight_term {
                                     line.contains(term)
                                 } else {
                                     false
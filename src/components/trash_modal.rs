@@ -0,0 +1,112 @@
+use crate::components::confirm_modal::ConfirmModal;
+use crate::core::report::REPORTS_DIR;
+use crate::core::trash::{self, TrashEntry};
+use dioxus::prelude::*;
+use std::path::Path;
+
+// Relatórios excluídos: restaurar no caminho original ou apagar de vez
+#[component]
+pub fn TrashModal(show: Signal<bool>, on_restored: EventHandler<String>) -> Element {
+    let mut message = use_signal(|| String::new());
+    let mut pending_purge = use_signal(|| None::<TrashEntry>);
+    let mut confirm_purge = use_signal(|| false);
+    let mut confirm_empty = use_signal(|| false);
+    // Muda a cada operação para reler o manifesto
+    let mut changes = use_signal(|| 0u32);
+
+    if !show() {
+        return rsx! {};
+    }
+
+    let _ = changes();
+    let reports_dir = Path::new(REPORTS_DIR);
+    let entries = trash::list_trash(reports_dir);
+    let purge_name = pending_purge
+        .read()
+        .as_ref()
+        .map(|e| e.caminho_original.clone())
+        .unwrap_or_default();
+
+    rsx! {
+        div { class: "modal-overlay",
+            div { class: "modal-window modal-w900",
+                div { class: "modal-header", "🗑️ Lixeira" }
+                div { class: "modal-body modal-body-scrollable",
+                    if !message().is_empty() {
+                        div { class: "error-message-box", "{message}" }
+                    }
+                    if entries.is_empty() {
+                        div { class: "empty-msg", "A lixeira está vazia." }
+                    } else {
+                        table { class: "pg-table table-wrapper",
+                            thead { tr { th { "Relatório" } th { "Excluído em" } th { "" } } }
+                            tbody {
+                                {entries.iter().map(|entry| {
+                                    let restore_id = entry.id.clone();
+                                    let purge_entry = entry.clone();
+                                    rsx! {
+                                        tr { key: "{entry.id}",
+                                            td { "📊 {entry.caminho_original}" if entry.tem_historico { span { class: "trash-history-note", " (com histórico)" } } }
+                                            td { "{entry.excluido_em}" }
+                                            td { class: "trash-actions",
+                                                button { class: "btn-classic",
+                                                    onclick: move |_| match trash::restore(Path::new(REPORTS_DIR), &restore_id) {
+                                                        Ok(path) => {
+                                                            message.set(String::new());
+                                                            changes += 1;
+                                                            on_restored.call(path.to_string_lossy().to_string());
+                                                        }
+                                                        Err(e) => message.set(e),
+                                                    },
+                                                    "↩️ Restaurar"
+                                                }
+                                                button { class: "btn-classic btn-delete-text",
+                                                    onclick: move |_| {
+                                                        pending_purge.set(Some(purge_entry.clone()));
+                                                        confirm_purge.set(true);
+                                                    },
+                                                    "Excluir definitivamente"
+                                                }
+                                            }
+                                        }
+                                    }
+                                })}
+                            }
+                        }
+                    }
+                }
+                div { class: "modal-footer",
+                    button { class: "btn-classic btn-delete-text", disabled: entries.is_empty(), onclick: move |_| confirm_empty.set(true), "Esvaziar Lixeira" }
+                    button { class: "btn-classic", onclick: move |_| show.set(false), "Fechar" }
+                }
+            }
+            ConfirmModal {
+                show: confirm_purge,
+                title: "Excluir definitivamente".to_string(),
+                message: format!("'{}' será apagado de vez, junto com o histórico de versões. Essa ação não pode ser desfeita.", purge_name),
+                confirm_label: "Excluir".to_string(),
+                on_confirm: move |_| {
+                    let entry = pending_purge.write().take();
+                    if let Some(entry) = entry {
+                        if let Err(e) = trash::purge(Path::new(REPORTS_DIR), &entry.id) {
+                            message.set(e);
+                        }
+                    }
+                    changes += 1;
+                }
+            }
+            ConfirmModal {
+                show: confirm_empty,
+                title: "Esvaziar Lixeira".to_string(),
+                message: format!("Os {} relatório(s) da lixeira serão apagados de vez. Essa ação não pode ser desfeita.", entries.len()),
+                confirm_label: "Esvaziar".to_string(),
+                on_confirm: move |_| {
+                    if let Err(e) = trash::empty_trash(Path::new(REPORTS_DIR)) {
+                        message.set(e);
+                    }
+                    changes += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,530 @@
+use crate::core::engine::TableConfig;
+use crate::core::report::ReportParameter;
+use crate::core::sql_analysis::{analyze_saved, infer_extraction, referenced_tables};
+use crate::core::sql_text::{Context, context_at_end};
+use crate::core::udfs::BUILTIN_FUNCTIONS;
+use datafusion::prelude::SessionContext;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::LazyLock;
+
+const MAX_SUGGESTIONS: usize = 40;
+
+static RE_FROM_JOIN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:from|join)\s+([a-z_][a-z0-9_]*)(?:\s+(?:as\s+)?([a-z_][a-z0-9_]*))?")
+        .unwrap()
+});
+static RE_SYNC_OPEN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\[SYNC:").unwrap());
+static RE_SYNC_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").unwrap());
+static RE_SYNC_TABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([a-zA-Z0-9_]+)\s*\(").unwrap());
+
+pub const KEYWORDS: &[&str] = &[
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "GROUP BY",
+    "ORDER BY",
+    "HAVING",
+    "LIMIT",
+    "OFFSET",
+    "JOIN",
+    "INNER JOIN",
+    "LEFT JOIN",
+    "RIGHT JOIN",
+    "FULL JOIN",
+    "CROSS JOIN",
+    "ON",
+    "USING",
+    "AS",
+    "AND",
+    "OR",
+    "NOT",
+    "IN",
+    "EXISTS",
+    "BETWEEN",
+    "LIKE",
+    "ILIKE",
+    "IS NULL",
+    "IS NOT NULL",
+    "CASE",
+    "WHEN",
+    "THEN",
+    "ELSE",
+    "END",
+    "DISTINCT",
+    "UNION",
+    "UNION ALL",
+    "EXCEPT",
+    "INTERSECT",
+    "WITH",
+    "ASC",
+    "DESC",
+    "NULLS FIRST",
+    "NULLS LAST",
+    "OVER",
+    "PARTITION BY",
+    "CREATE TABLE",
+    "CREATE VIEW",
+    "DROP TABLE",
+    "INSERT INTO",
+    "VALUES",
+    "TRUE",
+    "FALSE",
+    "NULL",
+];
+
+// Agregações, janelas e expressões que não aparecem no registro de funções do DataFusion
+const BUILTIN_EXPRESSIONS: &[&str] = &[
+    "count",
+    "sum",
+    "avg",
+    "min",
+    "max",
+    "median",
+    "array_agg",
+    "string_agg",
+    "stddev",
+    "variance",
+    "bool_and",
+    "bool_or",
+    "row_number",
+    "rank",
+    "dense_rank",
+    "ntile",
+    "lag",
+    "lead",
+    "first_value",
+    "last_value",
+    "coalesce",
+    "nullif",
+    "cast",
+    "try_cast",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SuggestionKind {
+    Keyword,
+    Function,
+    Table,
+    Column,
+    ColumnList,
+    Parameter,
+}
+
+impl SuggestionKind {
+    pub fn badge(&self) -> &'static str {
+        match self {
+            SuggestionKind::Keyword => "sql",
+            SuggestionKind::Function => "fx",
+            SuggestionKind::Table => "tab",
+            SuggestionKind::Column => "col",
+            SuggestionKind::ColumnList => "lista",
+            SuggestionKind::Parameter => "par",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub label: String,
+    pub insert: String,
+    pub kind: SuggestionKind,
+    pub detail: String,
+}
+
+// `start..end` (em bytes) é o trecho do texto trocado pela sugestão escolhida
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    pub start: usize,
+    pub end: usize,
+    pub items: Vec<Suggestion>,
+}
+
+pub struct CompletionSources<'a> {
+    pub schema: &'a BTreeMap<String, TableConfig>,
+    pub parameters: &'a [ReportParameter],
+    pub functions: &'a [String],
+}
+
+// Funções registradas no contexto (DataFusion + UDFs do projeto), em minúsculas
+pub fn function_names(ctx: &SessionContext) -> Vec<String> {
+    let state = ctx.state();
+    let mut names: Vec<String> = state
+        .scalar_functions()
+        .keys()
+        .chain(state.aggregate_functions().keys())
+        .map(|n| n.to_lowercase())
+        .chain(BUILTIN_EXPRESSIONS.iter().map(|n| n.to_string()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+// O <textarea> conta posições em UTF-16; o Rust em bytes
+pub fn utf16_to_byte(text: &str, pos: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in text.char_indices() {
+        if units >= pos {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+pub fn byte_to_utf16(text: &str, pos: usize) -> usize {
+    text[..pos.min(text.len())].encode_utf16().count()
+}
+
+// Sugestões para o cursor em `cursor` (bytes). Sem `forced` (Ctrl+Espaço) só
+// sugere quando já há algo digitado ou logo após '.', '[' e dentro do SYNC.
+pub fn complete(
+    text: &str,
+    cursor: usize,
+    sources: &CompletionSources,
+    forced: bool,
+) -> Option<Completion> {
+    let mut cursor = cursor.min(text.len());
+    while !text.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+
+    let before = &text[..cursor];
+    let start = word_start(before);
+    let end = cursor
+        + text[cursor..]
+            .find(|c: char| !is_word_char(c))
+            .unwrap_or(text.len() - cursor);
+    let prefix = before[start..].to_lowercase();
+    let preceding = &before[..start];
+
+    // A tag fica num comentário, por isso vem antes da checagem de contexto
+    if let Some(sync_body) = sync_context(before) {
+        // Uma única análise da query por pedido, compartilhada por todas as tabelas
+        let inferred = inferred_columns(text, sources.schema);
+        let items = sync_suggestions(sync_body, &prefix, sources, &inferred);
+        return finish(start, end, &prefix, items);
+    }
+
+    if preceding.ends_with('[') {
+        let end = if text[end..].starts_with(']') {
+            end + 1
+        } else {
+            end
+        };
+        return finish(start - 1, end, &prefix, parameter_suggestions(sources));
+    }
+
+    if context_at_end(before) != Some(Context::Code) {
+        return None;
+    }
+
+    let aliases = table_aliases(text, sources.schema);
+
+    if let Some(qualifier) = preceding.strip_suffix('.') {
+        let alias = qualifier[word_start(qualifier)..].to_lowercase();
+        let table = aliases.get(&alias)?;
+        return finish(
+            start,
+            end,
+            &prefix,
+            column_suggestions(table, sources.schema),
+        );
+    }
+
+    if prefix.is_empty() && !forced {
+        return None;
+    }
+
+    let items = match previous_word(preceding).as_deref() {
+        Some("from") | Some("join") => table_suggestions(sources.schema, None),
+        _ => {
+            let mut used: Vec<String> = referenced_tables(text);
+            used.extend(aliases.values().cloned());
+            used.extend(sync_tables(text));
+            let mut seen = HashSet::new();
+            used.retain(|t| seen.insert(t.clone()));
+
+            let mut items: Vec<Suggestion> = used
+                .iter()
+                .flat_map(|t| column_suggestions(t, sources.schema))
+                .collect();
+            items.extend(function_suggestions(sources.functions));
+            items.extend(KEYWORDS.iter().map(|k| Suggestion {
+                label: k.to_string(),
+                insert: k.to_string(),
+                kind: SuggestionKind::Keyword,
+                detail: String::new(),
+            }));
+            items.extend(table_suggestions(sources.schema, None));
+            items
+        }
+    };
+    finish(start, end, &prefix, items)
+}
+
+// Filtra pelo que já foi digitado: primeiro quem começa com o prefixo, depois quem contém
+fn finish(start: usize, end: usize, prefix: &str, items: Vec<Suggestion>) -> Option<Completion> {
+    let mut seen = HashSet::new();
+    let mut ranked: Vec<(u8, Suggestion)> = items
+        .into_iter()
+        .filter_map(|item| {
+            let key = item.label.trim_start_matches('[').to_lowercase();
+            let rank = if key.starts_with(prefix) {
+                0
+            } else if key.contains(prefix) {
+                1
+            } else {
+                return None;
+            };
+            seen.insert(key).then_some((rank, item))
+        })
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+
+    let items: Vec<Suggestion> = ranked
+        .into_iter()
+        .map(|(_, item)| item)
+        .take(MAX_SUGGESTIONS)
+        .collect();
+
+    // Nada a sugerir se a única opção é exatamente o que já está escrito
+    let already_typed = items.len() == 1 && items[0].insert.to_lowercase() == prefix;
+    (!items.is_empty() && !already_typed).then_some(Completion { start, end, items })
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn word_start(before: &str) -> usize {
+    before
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_word_char(*c))
+        .map_or(0, |(i, c)| i + c.len_utf8())
+}
+
+fn previous_word(preceding: &str) -> Option<String> {
+    let trimmed = preceding.trim_end();
+    let word = &trimmed[word_start(trimmed)..];
+    (!word.is_empty()).then(|| word.to_lowercase())
+}
+
+fn schema_entry<'a>(
+    schema: &'a BTreeMap<String, TableConfig>,
+    table: &str,
+) -> Option<(&'a String, &'a TableConfig)> {
+    schema
+        .iter()
+        .find(|(k, _)| k.to_lowercase() == table.to_lowercase())
+}
+
+// alias -> tabela (a própria tabela também vale como alias)
+fn table_aliases(text: &str, schema: &BTreeMap<String, TableConfig>) -> HashMap<String, String> {
+    let keywords: HashSet<String> = KEYWORDS
+        .iter()
+        .flat_map(|k| k.split(' '))
+        .map(|k| k.to_lowercase())
+        .collect();
+
+    let mut aliases = HashMap::new();
+    for cap in RE_FROM_JOIN.captures_iter(text) {
+        let table = cap[1].to_lowercase();
+        if schema_entry(schema, &table).is_none() {
+            continue;
+        }
+        if let Some(alias) = cap.get(2).map(|a| a.as_str().to_lowercase()) {
+            if !keywords.contains(&alias) {
+                aliases.insert(alias, table.clone());
+            }
+        }
+        aliases.insert(table.clone(), table);
+    }
+    aliases
+}
+
+// Conteúdo da tag [SYNC: ...] ainda aberta antes do cursor
+fn sync_context(before: &str) -> Option<&str> {
+    let tag = RE_SYNC_OPEN.find_iter(before).last()?;
+    let body = &before[tag.end()..];
+    (!body.contains(']')).then_some(body)
+}
+
+fn sync_tables(text: &str) -> Vec<String> {
+    RE_SYNC_TAG
+        .captures_iter(text)
+        .flat_map(|tag| {
+            RE_SYNC_TABLE
+                .captures_iter(&tag[1])
+                .map(|t| t[1].to_lowercase())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// tabela -> colunas que a query usa dela, do mesmo jeito que a extração infere
+fn inferred_columns(
+    text: &str,
+    schema: &BTreeMap<String, TableConfig>,
+) -> HashMap<String, Vec<String>> {
+    let Ok(refs) = analyze_saved(text) else {
+        return HashMap::new();
+    };
+    infer_extraction(&refs, schema, &HashSet::new())
+        .into_iter()
+        .map(|(name, cols)| {
+            let cols = cols.iter().map(|c| c.to_lowercase()).collect();
+            (name.to_lowercase(), cols)
+        })
+        .collect()
+}
+
+fn sync_suggestions(
+    body: &str,
+    prefix: &str,
+    sources: &CompletionSources,
+    inferred: &HashMap<String, Vec<String>>,
+) -> Vec<Suggestion> {
+    let open_paren = body.rfind('(').filter(|open| !body[*open..].contains(')'));
+
+    let Some(open) = open_paren else {
+        return table_suggestions(sources.schema, Some(inferred));
+    };
+
+    // Caminho de arquivo externo: metas('planilhas/metas.csv')
+    let listed = &body[open + 1..];
+    if listed.trim_start().starts_with('\'') {
+        return Vec::new();
+    }
+
+    let table_part = body[..open].trim_end();
+    let table = table_part[word_start(table_part)..].to_lowercase();
+    let Some((_, config)) = schema_entry(sources.schema, &table) else {
+        return Vec::new();
+    };
+
+    let already: HashSet<String> = listed
+        .split(',')
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty() && *c != prefix)
+        .collect();
+
+    let mut items = Vec::new();
+    if already.is_empty() {
+        let used = inferred.get(&table).cloned().unwrap_or_default();
+        if !used.is_empty() && used != ["*"] {
+            items.push(Suggestion {
+                label: used.join(", "),
+                insert: used.join(", "),
+                kind: SuggestionKind::ColumnList,
+                detail: "colunas usadas na query".to_string(),
+            });
+        }
+        items.push(Suggestion {
+            label: "*".to_string(),
+            insert: "*".to_string(),
+            kind: SuggestionKind::ColumnList,
+            detail: format!("todas as {} colunas", config.columns.len()),
+        });
+    }
+    items.extend(
+        column_suggestions(&table, sources.schema)
+            .into_iter()
+            .filter(|s| !already.contains(&s.insert)),
+    );
+    items
+}
+
+// `inferred` só vem dentro do SYNC
+fn table_suggestions(
+    schema: &BTreeMap<String, TableConfig>,
+    inferred: Option<&HashMap<String, Vec<String>>>,
+) -> Vec<Suggestion> {
+    schema
+        .iter()
+        .map(|(name, config)| {
+            let table = name.to_lowercase();
+            Suggestion {
+                label: table.clone(),
+                insert: table,
+                kind: SuggestionKind::Table,
+                detail: format!("{} colunas", config.columns.len()),
+            }
+        })
+        .map(|mut s| {
+            // Dentro do SYNC a tabela já entra com a lista de colunas inferida
+            if let Some(inferred) = inferred {
+                let list = match inferred.get(&s.insert) {
+                    Some(cols) if !cols.is_empty() => cols.join(", "),
+                    _ => "*".to_string(),
+                };
+                s.insert = format!("{}({})", s.insert, list);
+            }
+            s
+        })
+        .collect()
+}
+
+fn column_suggestions(table: &str, schema: &BTreeMap<String, TableConfig>) -> Vec<Suggestion> {
+    let Some((name, config)) = schema_entry(schema, table) else {
+        return Vec::new();
+    };
+    config
+        .columns
+        .iter()
+        .map(|col| Suggestion {
+            label: col.name.to_lowercase(),
+            insert: col.name.to_lowercase(),
+            kind: SuggestionKind::Column,
+            detail: format!(
+                "{} · {}({})",
+                name.to_lowercase(),
+                col.field_type,
+                col.length
+            ),
+        })
+        .collect()
+}
+
+fn function_suggestions(functions: &[String]) -> Vec<Suggestion> {
+    functions
+        .iter()
+        .map(|name| {
+            let detail = BUILTIN_FUNCTIONS
+                .iter()
+                .find(|(signature, _)| signature.split('(').next() == Some(name.as_str()))
+                .map(|(signature, description)| format!("{} — {}", signature, description))
+                .unwrap_or_else(|| "função".to_string());
+            Suggestion {
+                label: name.clone(),
+                insert: format!("{}(", name),
+                kind: SuggestionKind::Function,
+                detail,
+            }
+        })
+        .collect()
+}
+
+fn parameter_suggestions(sources: &CompletionSources) -> Vec<Suggestion> {
+    let mut items: Vec<Suggestion> = sources
+        .parameters
+        .iter()
+        .map(|p| Suggestion {
+            label: format!("[{}]", p.id),
+            insert: format!("[{}]", p.id),
+            kind: SuggestionKind::Parameter,
+            detail: format!("{} ({})", p.nome, p.tipo),
+        })
+        .collect();
+    items.push(Suggestion {
+        label: "[SYNC: ]".to_string(),
+        insert: "[SYNC: ".to_string(),
+        kind: SuggestionKind::Keyword,
+        detail: "sobrescreve as tabelas/colunas extraídas".to_string(),
+    });
+    items
+}
@@ -1,3 +1,7 @@
+use crate::core::sql_analysis;
+use crate::core::sql_text::split_statements;
+use crate::core::telemetry::{self, STAGE_EXTRACTION, STAGE_REGISTRATION, STAGE_SQL, TelemetryEvent};
+use crate::core::udfs;
 use datafusion::arrow::array::{
     ArrayRef, BooleanBuilder, Date32Builder, Float64Builder, Int64Builder, StringBuilder,
 };
@@ -14,7 +18,7 @@ use serde::Deserialize;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use std::io::Read;
 use std::os::windows::fs::OpenOptionsExt;
 use std::sync::{
     Arc, Mutex,
@@ -51,6 +55,9 @@ pub struct DataEngine {
     pub base_path: String,
     pub cached_results: Arc<Mutex<Vec<RecordBatch>>>,
     pub active_tables: Arc<Mutex<HashSet<String>>>,
+    // Relatório da última carga e hash dos valores dos parâmetros, para a telemetria
+    pub report_name: String,
+    pub parameters_hash: String,
 }
 
 enum WorkerMsg {
@@ -74,11 +81,13 @@ enum ColBuilder {
 impl DataEngine {
     pub fn new_empty() -> Self {
         Self {
-            ctx: SessionContext::new(),
+            ctx: new_session_context(),
             schema: BTreeMap::new(),
             base_path: String::new(),
             cached_results: Arc::new(Mutex::new(Vec::new())),
             active_tables: Arc::new(Mutex::new(HashSet::new())),
+            report_name: String::new(),
+            parameters_hash: String::new(),
         }
     }
 
@@ -111,11 +120,13 @@ impl DataEngine {
         }
 
         Self {
-            ctx: SessionContext::new(),
+            ctx: new_session_context(),
             schema,
             base_path,
             cached_results: Arc::new(Mutex::new(Vec::new())),
             active_tables: Arc::new(Mutex::new(HashSet::new())),
+            report_name: String::new(),
+            parameters_hash: String::new(),
         }
     }
 
@@ -134,6 +145,9 @@ impl DataEngine {
                     for cap in re_table.captures_iter(content.as_str()) {
                         let table_name = cap[1].to_string();
                         let col_str = cap[2].trim();
+                        if is_quoted_path(col_str) {
+                            continue;
+                        }
                         let cols = if col_str == "*" {
                             vec!["*".to_string()]
                         } else {
@@ -147,58 +161,80 @@ impl DataEngine {
         tasks
     }
 
-    pub fn process_report_with_progress<F>(
-        &mut self,
-        user_sql: &str,
-        cancel_flag: Arc<AtomicBool>,
-        report_name: &str,
-        mut on_progress: F,
-    ) -> Result<(), String>
-    where
-        F: FnMut(f32) + Send + 'static,
-    {
-        self.ctx = SessionContext::new();
-
-        let start_carga = std::time::Instant::now();
-        let mut tempo_registro = 0;
+    // Entradas do SYNC com caminho entre aspas viram tabelas externas:
+    // [SYNC: nfmestre(*), metas('planilhas/metas.csv')]
+    pub fn parse_external_sources(sql: &str) -> Vec<(String, String)> {
+        let mut sources = Vec::new();
+        if let Ok(re_header) = Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]") {
+            if let Some(content) = re_header.captures(sql).and_then(|caps| caps.get(1)) {
+                if let Ok(re_source) =
+                    Regex::new(r#"([a-zA-Z0-9_]+)\s*\(\s*(?:'([^']*)'|"([^"]*)")\s*\)"#)
+                {
+                    for cap in re_source.captures_iter(content.as_str()) {
+                        let path = cap.get(2).or_else(|| cap.get(3)).map(|m| m.as_str());
+                        if let Some(path) = path {
+                            sources.push((cap[1].to_lowercase(), path.trim().to_string()));
+                        }
+                    }
+                }
+            }
+        }
+        sources
+    }
 
-        let sync_tasks = self.parse_sync_header(user_sql);
-        if sync_tasks.is_empty() {
-            return Err("Tag [SYNC: ...] não encontrada ou formato inválido!".to_string());
+    fn register_external_sources(&self, sources: &[(String, String)]) -> Result<(), String> {
+        if sources.is_empty() {
+            return Ok(());
         }
-        if self.schema.is_empty() {
+
+        let ctx = self.ctx.clone();
+        let sources = sources.to_vec();
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| e.to_string())?;
+
+            rt.block_on(async move {
+                for (name, path) in &sources {
+                    register_external_source(&ctx, name, path).await?;
+                }
+                Ok(())
+            })
+        })
+        .join()
+        .unwrap_or(Err("Erro crítico na thread de fontes externas".into()))
+    }
+
+    // Tabelas do DBISAM a extrair: o SYNC vale como sobrescrita e as demais tabelas
+    // e colunas saem da própria query
+    pub fn plan_extraction(
+        &self,
+        user_sql: &str,
+        external_sources: &[(String, String)],
+    ) -> Result<Vec<(String, TableConfig, Vec<Column>)>, String> {
+        let mut sync_tasks = self.parse_sync_header(user_sql);
+        if !sync_tasks.is_empty() && self.schema.is_empty() {
             return Err("schema.toml não encontrado ou vazio!".to_string());
         }
 
-        {
-            let mut active = self.active_tables.lock().unwrap();
-            for (table_name, _) in &sync_tasks {
-                active.insert(table_name.to_lowercase());
+        let skip: HashSet<String> = sync_tasks
+            .iter()
+            .map(|(t, _)| t.to_lowercase())
+            .chain(external_sources.iter().map(|(t, _)| t.clone()))
+            .collect();
+        match sql_analysis::analyze(user_sql) {
+            Ok(refs) => {
+                sync_tasks.extend(sql_analysis::infer_extraction(&refs, &self.schema, &skip))
             }
-        }
-
-        let mut total_rows_overall = 0;
-        for (table_name, _) in &sync_tasks {
-            let dat_path = format!(r"{}/{}.dat", self.base_path, table_name);
-            if let Ok(file) = File::open(&dat_path) {
-                if let Ok(mmap) = unsafe { Mmap::map(&file) } {
-                    if mmap.len() >= 49 {
-                        total_rows_overall += mmap
-                            .get(DBISAM_OFFSET_TOTAL_ROWS)
-                            .and_then(|s| s.try_into().ok())
-                            .map(u32::from_le_bytes)
-                            .unwrap_or(0) as usize;
-                    }
-                }
+            Err(e) if sync_tasks.is_empty() && external_sources.is_empty() => {
+                return Err(format!("Erro de sintaxe SQL: {}", e));
             }
+            Err(_) => {}
         }
-        if total_rows_overall == 0 {
-            total_rows_overall = 1;
-        }
-        let total_rows_f32 = total_rows_overall as f32;
-
-        let mut extract_jobs = HashMap::new();
 
+        let mut jobs = Vec::new();
         for (table_name, requested_cols) in &sync_tasks {
             let config = self
                 .schema
@@ -223,9 +259,73 @@ impl DataEngine {
                         .collect()
                 };
 
-            extract_jobs.insert(table_name.clone(), (config, target_columns));
+            jobs.push((table_name.clone(), config, target_columns));
+        }
+        Ok(jobs)
+    }
+
+    pub fn process_report_with_progress<F>(
+        &mut self,
+        user_sql: &str,
+        cancel_flag: Arc<AtomicBool>,
+        report_name: &str,
+        mut on_progress: F,
+    ) -> Result<(), String>
+    where
+        F: FnMut(f32) + Send + 'static,
+    {
+        self.ctx = new_session_context();
+        self.report_name = report_name.to_string();
+
+        let start_carga = std::time::Instant::now();
+        let mut tempo_registro = 0;
+
+        let external_sources = Self::parse_external_sources(user_sql);
+        let jobs = self.plan_extraction(user_sql, &external_sources)?;
+        if jobs.is_empty() && external_sources.is_empty() {
+            if self.schema.is_empty() {
+                return Err("schema.toml não encontrado ou vazio!".to_string());
+            }
+            return Err("Nenhuma tabela do schema.toml encontrada na query.".to_string());
         }
 
+        let event_base = TelemetryEvent {
+            parametros_hash: self.parameters_hash.clone(),
+            tabelas: jobs.iter().map(|(t, _, _)| t.to_lowercase()).collect(),
+            colunas: jobs
+                .iter()
+                .flat_map(|(t, _, cols)| {
+                    cols.iter()
+                        .map(move |c| format!("{}.{}", t.to_lowercase(), c.name.to_lowercase()))
+                })
+                .collect(),
+            schema_hash: schema_fingerprint(&jobs),
+            ..TelemetryEvent::new(report_name, STAGE_EXTRACTION, 0)
+        };
+
+        {
+            let mut active = self.active_tables.lock().unwrap();
+            for (table_name, _, _) in &jobs {
+                active.insert(table_name.to_lowercase());
+            }
+        }
+
+        let mut total_rows_overall = 0;
+        for (table_name, _, _) in &jobs {
+            if let Some(stats) = dat_file_stats(&self.base_path, table_name) {
+                total_rows_overall += stats.rows as usize;
+            }
+        }
+        if total_rows_overall == 0 {
+            total_rows_overall = 1;
+        }
+        let total_rows_f32 = total_rows_overall as f32;
+
+        let extract_jobs: HashMap<String, (TableConfig, Vec<Column>)> = jobs
+            .into_iter()
+            .map(|(table_name, config, target_columns)| (table_name, (config, target_columns)))
+            .collect();
+
         let (tx, rx) = mpsc::channel();
         let mut handles = Vec::new();
 
@@ -250,6 +350,7 @@ impl DataEngine {
         drop(tx);
 
         let mut total_processed = 0;
+        let mut bytes_loaded = 0;
         let mut final_error = None;
         let mut table_batches: HashMap<String, Vec<RecordBatch>> = HashMap::new();
 
@@ -264,6 +365,7 @@ impl DataEngine {
                     batch,
                     processed_count,
                 } => {
+                    bytes_loaded += batch.get_array_memory_size();
                     table_batches
                         .entry(table_name)
                         .or_insert_with(Vec::new)
@@ -319,6 +421,12 @@ impl DataEngine {
         }
 
         if let Some(err) = final_error {
+            telemetry::record(&TelemetryEvent {
+                duracao_ms: start_carga.elapsed().as_millis() as u64,
+                sucesso: false,
+                erro: err.clone(),
+                ..event_base
+            });
             return Err(err);
         }
 
@@ -326,6 +434,10 @@ impl DataEngine {
             let _ = h.join();
         }
 
+        let start_fontes = std::time::Instant::now();
+        self.register_external_sources(&external_sources)?;
+        tempo_registro += start_fontes.elapsed().as_millis();
+
         on_progress(100.0);
 
         let tempo_total = start_carga.elapsed().as_millis();
@@ -334,16 +446,19 @@ impl DataEngine {
         println!("⏱️ [EXTRAÇÃO DBISAM -> ARROW] {} ms", tempo_extracao);
         println!("⏱️ [REGISTRO DATAFUSION] {} ms", tempo_registro);
 
-        append_log(
-            report_name,
-            "1. Extração DBISAM (.dat -> Arrow)",
-            tempo_extracao,
-        );
-        append_log(
-            report_name,
-            "2. Registro DataFusion (Memória Colunar)",
-            tempo_registro,
-        );
+        telemetry::record(&TelemetryEvent {
+            duracao_ms: tempo_extracao as u64,
+            linhas: total_processed as u64,
+            bytes: bytes_loaded as u64,
+            ..event_base.clone()
+        });
+        telemetry::record(&TelemetryEvent {
+            etapa: STAGE_REGISTRATION.to_string(),
+            duracao_ms: tempo_registro as u64,
+            linhas: total_processed as u64,
+            bytes: bytes_loaded as u64,
+            ..event_base
+        });
 
         Ok(())
     }
@@ -357,11 +472,7 @@ impl DataEngine {
         let re_sync = Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").map_err(|e| e.to_string())?;
         let clean_sql = re_sync.replace_all(sql, "").to_string();
 
-        let commands: Vec<String> = clean_sql
-            .split(';')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
+        let commands = split_statements(&clean_sql);
 
         if commands.is_empty() {
             return Err("SQL vazio".to_string());
@@ -383,31 +494,101 @@ impl DataEngine {
 
                 let last_query = commands.last().unwrap();
                 let df = ctx.sql(last_query).await.map_err(|e| e.to_string())?;
+                // Esquema do plano: vale mesmo quando a consulta não devolve linhas
+                let schema = Arc::new(ArrowSchema::from(df.schema()));
 
-                let batches = df.collect().await.map_err(|e| e.to_string())?;
-
-                let mut total_rows = 0;
-                let mut cols = Vec::new();
+                let mut batches = df.collect().await.map_err(|e| e.to_string())?;
 
-                if !batches.is_empty() {
-                    let schema = batches[0].schema();
-                    cols = schema.fields().iter().map(|f| f.name().clone()).collect();
-                    total_rows = batches.iter().map(|b| b.num_rows()).sum();
+                let cols: Vec<String> = schema.fields().iter().map(|f| f.name().clone()).collect();
+                let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+                if batches.is_empty() {
+                    // Lote vazio só para guardar as colunas (API, exportações)
+                    batches.push(RecordBatch::new_empty(schema));
                 }
 
+                let bytes: usize = batches.iter().map(|b| b.get_array_memory_size()).sum();
+
                 let mut cache = cache_ptr.lock().unwrap();
                 *cache = batches;
 
-                Ok((cols, total_rows))
+                Ok((cols, total_rows, bytes))
             })
         })
         .join()
         .unwrap_or(Err("Erro crítico na thread do SQL".into()));
 
         let tempo_sql = start_sql.elapsed().as_millis();
-        append_log(report_name, "3. Execução SQL (DataFusion)", tempo_sql);
+        let event = TelemetryEvent {
+            parametros_hash: self.parameters_hash.clone(),
+            ..TelemetryEvent::new(report_name, STAGE_SQL, tempo_sql)
+        };
+        match &result {
+            Ok((_, total_rows, bytes)) => telemetry::record(&TelemetryEvent {
+                linhas: *total_rows as u64,
+                bytes: *bytes as u64,
+                ..event
+            }),
+            Err(e) => telemetry::record(&TelemetryEvent {
+                sucesso: false,
+                erro: e.clone(),
+                ..event
+            }),
+        }
 
-        result
+        result.map(|(cols, total_rows, _)| (cols, total_rows))
+    }
+
+    // EXPLAIN ANALYZE do último comando. Os anteriores (CREATE VIEW, tabelas
+    // temporárias...) só rodam antes quando o contexto ainda não os conhece.
+    pub fn explain_analyze(&self, sql: &str, run_preceding: bool) -> Result<String, String> {
+        let re_sync = Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").map_err(|e| e.to_string())?;
+        let clean_sql = re_sync.replace_all(sql, "").to_string();
+
+        let mut commands = split_statements(&clean_sql);
+        let last_query = commands.pop().ok_or("SQL vazio".to_string())?;
+        let ctx = self.ctx.clone();
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| e.to_string())?;
+
+            rt.block_on(async move {
+                if run_preceding {
+                    for cmd in &commands {
+                        ctx.sql(cmd).await.map_err(|e| e.to_string())?;
+                    }
+                }
+
+                let df = ctx
+                    .sql(&format!("EXPLAIN ANALYZE {}", last_query))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let batches = df.collect().await.map_err(|e| e.to_string())?;
+
+                // Colunas: plan_type, plan
+                for batch in &batches {
+                    for row in 0..batch.num_rows() {
+                        let plan_type = datafusion::arrow::util::display::array_value_to_string(
+                            batch.column(0),
+                            row,
+                        )
+                        .unwrap_or_default();
+                        if plan_type.contains("Metrics") {
+                            return datafusion::arrow::util::display::array_value_to_string(
+                                batch.column(1),
+                                row,
+                            )
+                            .map_err(|e| e.to_string());
+                        }
+                    }
+                }
+                Err("O DataFusion não devolveu o plano com métricas.".to_string())
+            })
+        })
+        .join()
+        .unwrap_or(Err("Erro crítico na thread do EXPLAIN".into()))
     }
 
     pub fn get_rows_slice(&self, offset: usize, limit: usize) -> Vec<Vec<String>> {
@@ -448,8 +629,31 @@ impl DataEngine {
         rows_vec
     }
 
+    pub fn get_batches_slice(&self, offset: usize, limit: usize) -> Vec<RecordBatch> {
+        let cache = self.cached_results.lock().unwrap();
+        let mut slices = Vec::new();
+        let mut skip = offset;
+        let mut remaining = limit;
+
+        for batch in cache.iter() {
+            if remaining == 0 {
+                break;
+            }
+            let num_rows = batch.num_rows();
+            if skip >= num_rows {
+                skip -= num_rows;
+                continue;
+            }
+            let take = (num_rows - skip).min(remaining);
+            slices.push(batch.slice(skip, take));
+            remaining -= take;
+            skip = 0;
+        }
+        slices
+    }
+
     pub fn clear_memory(&mut self) {
-        self.ctx = SessionContext::new();
+        self.ctx = new_session_context();
 
         if let Ok(mut cache) = self.cached_results.lock() {
             cache.clear();
@@ -478,6 +682,12 @@ impl DataEngine {
                                     unique_tables.insert(cap_table[1].to_string().to_lowercase());
                                 }
                             }
+                            if let Some(sql) = serde_json::from_str::<serde_json::Value>(&content)
+                                .ok()
+                                .and_then(|v| v["query_sql"].as_str().map(str::to_string))
+                            {
+                                unique_tables.extend(sql_analysis::referenced_tables(&sql));
+                            }
                         }
                     }
                 }
@@ -510,6 +720,53 @@ impl DataEngine {
 }
 
 // FUNÇÕES AUXILIARES E WORKERS
+pub fn new_session_context() -> SessionContext {
+    // information_schema para clientes SQL externos listarem tabelas e colunas
+    let config = SessionConfig::new().with_information_schema(true);
+    let ctx = SessionContext::new_with_config(config);
+    udfs::register_all(&ctx);
+    ctx
+}
+
+pub struct DatFileStats {
+    pub rows: u32,
+    pub size_bytes: u64,
+}
+
+// Lê só o cabeçalho do .dat: nº de registros e tamanho do arquivo
+pub fn dat_file_stats(base_path: &str, table_name: &str) -> Option<DatFileStats> {
+    let dat_path = format!(r"{}/{}.dat", base_path, table_name);
+    let mut file = File::open(&dat_path).ok()?;
+    let size_bytes = file.metadata().ok()?.len();
+
+    let mut header = [0u8; DBISAM_OFFSET_TOTAL_ROWS.end];
+    file.read_exact(&mut header).ok()?;
+    let rows = header
+        .get(DBISAM_OFFSET_TOTAL_ROWS)
+        .and_then(|s| s.try_into().ok())
+        .map(u32::from_le_bytes)
+        .unwrap_or(0);
+
+    Some(DatFileStats { rows, size_bytes })
+}
+
+// Layout das tabelas usadas: muda quando o ERP altera o schema.toml
+fn schema_fingerprint(jobs: &[(String, TableConfig, Vec<Column>)]) -> String {
+    let mut parts: Vec<String> = jobs
+        .iter()
+        .map(|(name, config, _)| {
+            let cols: Vec<String> = config
+                .columns
+                .iter()
+                .map(|c| format!("{}:{}:{}:{}", c.name, c.field_type, c.offset, c.length))
+                .collect();
+            format!("{}|{}|{}", name.to_lowercase(), config.record_size, cols.join(","))
+        })
+        .collect();
+    parts.sort();
+    telemetry::stable_hash(&parts.join(";"))
+}
+
 fn parse_dbisam_table(
     base_path: String,
     table_name: String,
@@ -534,35 +791,9 @@ fn parse_dbisam_table(
         return Ok(());
     }
 
-    let total_fields = mmap
-        .get(DBISAM_OFFSET_TOTAL_FIELDS)
-        .and_then(|s| s.try_into().ok())
-        .map(u16::from_le_bytes)
-        .unwrap_or(0) as usize;
-    let data_offset = DBISAM_BASE_HEADER_SIZE + (total_fields * DBISAM_FIELD_DEF_SIZE);
-    let total_rows_expected = mmap
-        .get(DBISAM_OFFSET_TOTAL_ROWS)
-        .and_then(|s| s.try_into().ok())
-        .map(u32::from_le_bytes)
-        .unwrap_or(0);
-
-    let mut arrow_fields = Vec::new();
+    let (data_offset, total_rows_expected) = dat_layout(&mmap);
 
-    for col in &target_columns {
-        let normalized_name = col.name.to_lowercase();
-        match col.field_type.as_str() {
-            "I" => {
-                if col.length == 1 {
-                    arrow_fields.push(Field::new(&normalized_name, DataType::Boolean, true));
-                } else {
-                    arrow_fields.push(Field::new(&normalized_name, DataType::Int64, true));
-                }
-            }
-            "F" => arrow_fields.push(Field::new(&normalized_name, DataType::Float64, true)),
-            "D" => arrow_fields.push(Field::new(&normalized_name, DataType::Date32, true)),
-            _ => arrow_fields.push(Field::new(&normalized_name, DataType::Utf8, true)),
-        }
-    }
+    let arrow_fields: Vec<Field> = target_columns.iter().map(arrow_field_for).collect();
 
     let arrow_schema = Arc::new(ArrowSchema::new(arrow_fields));
     let row_indexes: Vec<u32> = (0..total_rows_expected).collect();
@@ -582,51 +813,7 @@ fn parse_dbisam_table(
                 mmap.get(offset_da_linha..offset_da_linha + config.record_size as usize)
             {
                 if row_data[0] == 0 {
-                    for (col_idx, col) in target_columns.iter().enumerate() {
-                        let start = col.offset as usize + 1;
-                        let end = start + col.length as usize;
-
-                        match &mut local_builders[col_idx] {
-                            ColBuilder::Int(b) => {
-                                let val = match col.length {
-                                    1 => row_data[start] as i64,
-                                    2 => i16::from_le_bytes(
-                                        row_data[start..start + 2].try_into().unwrap_or([0; 2]),
-                                    ) as i64,
-                                    _ => i32::from_le_bytes(
-                                        row_data[start..start + 4].try_into().unwrap_or([0; 4]),
-                                    ) as i64,
-                                };
-                                b.append_value(val);
-                            }
-                            ColBuilder::Float(b) => {
-                                let v = f64::from_le_bytes(
-                                    row_data[start..start + 8].try_into().unwrap_or([0; 8]),
-                                );
-                                b.append_value(v);
-                            }
-                            ColBuilder::Date(b) => {
-                                let days = i32::from_le_bytes(
-                                    row_data[start..start + 4].try_into().unwrap_or([0; 4]),
-                                );
-                                if days > 0 {
-                                    b.append_value(days - 719163);
-                                } else {
-                                    b.append_null();
-                                }
-                            }
-                            ColBuilder::Text(b) => {
-                                if let Some(slice) = row_data.get(start..end) {
-                                    b.append_value(DataEngine::decode_db_string(slice));
-                                } else {
-                                    b.append_null();
-                                }
-                            }
-                            ColBuilder::Bool(b) => {
-                                b.append_value(row_data[start] != 0);
-                            }
-                        }
-                    }
+                    append_row(&mut local_builders, &target_columns, row_data);
                     local_count += 1;
                 }
             }
@@ -652,6 +839,253 @@ fn parse_dbisam_table(
     Ok(())
 }
 
+// Início dos registros e nº de registros (vivos e apagados) gravados no cabeçalho
+fn dat_layout(mmap: &[u8]) -> (usize, u32) {
+    let total_fields = mmap
+        .get(DBISAM_OFFSET_TOTAL_FIELDS)
+        .and_then(|s| s.try_into().ok())
+        .map(u16::from_le_bytes)
+        .unwrap_or(0) as usize;
+    let data_offset = DBISAM_BASE_HEADER_SIZE + (total_fields * DBISAM_FIELD_DEF_SIZE);
+    let total_rows = mmap
+        .get(DBISAM_OFFSET_TOTAL_ROWS)
+        .and_then(|s| s.try_into().ok())
+        .map(u32::from_le_bytes)
+        .unwrap_or(0);
+    (data_offset, total_rows)
+}
+
+// Decodifica um registro vivo (row_data[0] == 0) nos builders das colunas alvo
+fn append_row(builders: &mut [ColBuilder], target_columns: &[Column], row_data: &[u8]) {
+    for (col_idx, col) in target_columns.iter().enumerate() {
+        let start = col.offset as usize + 1;
+        let end = start + col.length as usize;
+
+        match &mut builders[col_idx] {
+            ColBuilder::Int(b) => {
+                let val = match col.length {
+                    1 => row_data[start] as i64,
+                    2 => i16::from_le_bytes(
+                        row_data[start..start + 2].try_into().unwrap_or([0; 2]),
+                    ) as i64,
+                    _ => i32::from_le_bytes(
+                        row_data[start..start + 4].try_into().unwrap_or([0; 4]),
+                    ) as i64,
+                };
+                b.append_value(val);
+            }
+            ColBuilder::Float(b) => {
+                let v = f64::from_le_bytes(row_data[start..start + 8].try_into().unwrap_or([0; 8]));
+                b.append_value(v);
+            }
+            ColBuilder::Date(b) => {
+                let days =
+                    i32::from_le_bytes(row_data[start..start + 4].try_into().unwrap_or([0; 4]));
+                if days > 0 {
+                    b.append_value(days - 719163);
+                } else {
+                    b.append_null();
+                }
+            }
+            ColBuilder::Text(b) => {
+                if let Some(slice) = row_data.get(start..end) {
+                    b.append_value(DataEngine::decode_db_string(slice));
+                } else {
+                    b.append_null();
+                }
+            }
+            ColBuilder::Bool(b) => {
+                b.append_value(row_data[start] != 0);
+            }
+        }
+    }
+}
+
+fn finish_batch(
+    schema: &Arc<ArrowSchema>,
+    builders: &mut [ColBuilder],
+) -> Result<RecordBatch, String> {
+    let arrays: Vec<ArrayRef> = builders
+        .iter_mut()
+        .map(|b| match b {
+            ColBuilder::Int(b) => Arc::new(b.finish()) as ArrayRef,
+            ColBuilder::Float(b) => Arc::new(b.finish()) as ArrayRef,
+            ColBuilder::Date(b) => Arc::new(b.finish()) as ArrayRef,
+            ColBuilder::Text(b) => Arc::new(b.finish()) as ArrayRef,
+            ColBuilder::Bool(b) => Arc::new(b.finish()) as ArrayRef,
+        })
+        .collect();
+
+    RecordBatch::try_new(schema.clone(), arrays).map_err(|e| e.to_string())
+}
+
+// Primeiros `limit` registros vivos do .dat, sem varrer o arquivo inteiro
+pub fn read_first_rows(
+    base_path: &str,
+    table_name: &str,
+    config: &TableConfig,
+    target_columns: &[Column],
+    limit: usize,
+) -> Result<RecordBatch, String> {
+    let dat_path = format!(r"{}/{}.dat", base_path, table_name);
+    let file = File::open(&dat_path).map_err(|e| format!("Erro ao abrir {}: {}", dat_path, e))?;
+    let mmap = unsafe { Mmap::map(&file).map_err(|e| e.to_string())? };
+
+    let arrow_fields: Vec<Field> = target_columns.iter().map(arrow_field_for).collect();
+    let arrow_schema = Arc::new(ArrowSchema::new(arrow_fields));
+    let mut builders = create_builders_from_cols(target_columns, limit);
+
+    if mmap.len() >= DBISAM_HEADER_MIN_LEN {
+        let (data_offset, total_rows) = dat_layout(&mmap);
+        let record_size = config.record_size as usize;
+        let mut found = 0;
+
+        for row_idx in 0..total_rows as usize {
+            if found >= limit {
+                break;
+            }
+            let offset_da_linha = data_offset + row_idx * record_size;
+            if let Some(row_data) = mmap.get(offset_da_linha..offset_da_linha + record_size) {
+                if row_data[0] == 0 {
+                    append_row(&mut builders, target_columns, row_data);
+                    found += 1;
+                }
+            }
+        }
+    }
+
+    finish_batch(&arrow_schema, &mut builders)
+}
+
+pub fn arrow_field_for(col: &Column) -> Field {
+    let normalized_name = col.name.to_lowercase();
+    match col.field_type.as_str() {
+        "I" => {
+            if col.length == 1 {
+                Field::new(&normalized_name, DataType::Boolean, true)
+            } else {
+                Field::new(&normalized_name, DataType::Int64, true)
+            }
+        }
+        "F" => Field::new(&normalized_name, DataType::Float64, true),
+        "D" => Field::new(&normalized_name, DataType::Date32, true),
+        _ => Field::new(&normalized_name, DataType::Utf8, true),
+    }
+}
+
+// Extração síncrona de uma única tabela, para quem não precisa de progresso
+pub fn extract_table(
+    base_path: &str,
+    table_name: &str,
+    config: &TableConfig,
+    target_columns: &[Column],
+    cancel: Arc<AtomicBool>,
+) -> Result<Vec<RecordBatch>, String> {
+    let (tx, rx) = mpsc::channel();
+    parse_dbisam_table(
+        base_path.to_string(),
+        table_name.to_string(),
+        config.clone(),
+        target_columns.to_vec(),
+        tx,
+        cancel,
+    )?;
+
+    let mut batches = Vec::new();
+    for msg in rx {
+        match msg {
+            WorkerMsg::Batch { batch, .. } => batches.push(batch),
+            WorkerMsg::TableDone(_) => {}
+            WorkerMsg::Error(e) => return Err(e),
+        }
+    }
+    Ok(batches)
+}
+
+fn is_quoted_path(value: &str) -> bool {
+    value.starts_with('\'') || value.starts_with('"')
+}
+
+fn resolve_source_path(path: &str) -> Result<String, String> {
+    let candidate = std::path::Path::new(path);
+    if candidate.exists() {
+        return Ok(path.to_string());
+    }
+    if candidate.is_relative() {
+        if let Ok(exe_path) = env::current_exe() {
+            if let Some(dir) = exe_path.parent() {
+                let beside_exe = dir.join(candidate);
+                if beside_exe.exists() {
+                    return Ok(beside_exe.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+    Err(format!("Arquivo externo não encontrado: {}", path))
+}
+
+// Planilhas exportadas do Excel em pt-BR costumam vir separadas por ';'
+fn sniff_csv_delimiter(path: &str) -> u8 {
+    let mut first_line = String::new();
+    if let Ok(mut file) = File::open(path) {
+        let mut buffer = [0u8; 4096];
+        if let Ok(n) = file.read(&mut buffer) {
+            first_line = String::from_utf8_lossy(&buffer[..n])
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+        }
+    }
+    if first_line.matches(';').count() > first_line.matches(',').count() {
+        b';'
+    } else {
+        b','
+    }
+}
+
+pub async fn register_external_source(
+    ctx: &SessionContext,
+    name: &str,
+    path: &str,
+) -> Result<(), String> {
+    let resolved = resolve_source_path(path)?;
+    let extension = std::path::Path::new(&resolved)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let file_extension = format!(".{}", extension);
+
+    let result = match extension.as_str() {
+        "csv" | "txt" => {
+            let options = CsvReadOptions::new()
+                .has_header(true)
+                .delimiter(sniff_csv_delimiter(&resolved))
+                .file_extension(&file_extension);
+            ctx.register_csv(name, &resolved, options).await
+        }
+        "parquet" => {
+            let options = ParquetReadOptions {
+                file_extension: &file_extension,
+                ..Default::default()
+            };
+            ctx.register_parquet(name, &resolved, options).await
+        }
+        "json" | "ndjson" | "jsonl" => {
+            let options = NdJsonReadOptions::default().file_extension(&file_extension);
+            ctx.register_json(name, &resolved, options).await
+        }
+        _ => {
+            return Err(format!(
+                "Formato não suportado para a fonte '{}': use .csv, .parquet ou .json (NDJSON)",
+                name
+            ));
+        }
+    };
+
+    result.map_err(|e| format!("Erro ao registrar fonte externa {}: {}", name, e))
+}
+
 fn create_builders_from_cols(target_columns: &[Column], capacity: usize) -> Vec<ColBuilder> {
     target_columns
         .iter()
@@ -680,18 +1114,7 @@ fn send_batch(
     count: usize,
     tx: &mpsc::Sender<WorkerMsg>,
 ) -> Result<(), String> {
-    let arrays: Vec<ArrayRef> = builders
-        .iter_mut()
-        .map(|b| match b {
-            ColBuilder::Int(b) => Arc::new(b.finish()) as ArrayRef,
-            ColBuilder::Float(b) => Arc::new(b.finish()) as ArrayRef,
-            ColBuilder::Date(b) => Arc::new(b.finish()) as ArrayRef,
-            ColBuilder::Text(b) => Arc::new(b.finish()) as ArrayRef,
-            ColBuilder::Bool(b) => Arc::new(b.finish()) as ArrayRef,
-        })
-        .collect();
-
-    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(|e| e.to_string())?;
+    let batch = finish_batch(schema, builders)?;
 
     tx.send(WorkerMsg::Batch {
         table_name: table_name.to_string(),
@@ -702,20 +1125,3 @@ fn send_batch(
 
     Ok(())
 }
-
-pub fn append_log(report_name: &str, stage: &str, duration_ms: u128) {
-    let now = chrono::Local::now().format("%d/%m/%Y %H:%M:%S");
-    let log_line = format!(
-        "(RELATÓRIO: {} | {}) -> {} levou {} ms\n",
-        report_name, now, stage, duration_ms
-    );
-
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("logs_desempenho.txt")
-    {
-        let _ = file.write_all(log_line.as_bytes());
-        let _ = file.sync_all();
-    }
-}
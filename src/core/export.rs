@@ -0,0 +1,135 @@
+use crate::core::engine::DataEngine;
+use datafusion::arrow::datatypes::{DataType, Field, Schema as ArrowSchema};
+use datafusion::parquet::arrow::ArrowWriter;
+use rust_xlsxwriter::*;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim_start_matches('.').to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "xlsx" => Some(Self::Xlsx),
+            "parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| Self::parse(&ext.to_string_lossy()))
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Xlsx => "xlsx",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+pub fn export_results(
+    engine: &DataEngine,
+    cols: &[String],
+    total: usize,
+    path: &Path,
+    format: ExportFormat,
+) -> Result<(), String> {
+    match format {
+        ExportFormat::Csv => write_csv(engine, cols, total, path),
+        ExportFormat::Xlsx => write_xlsx(engine, cols, total, path),
+        ExportFormat::Parquet => write_parquet(engine, cols, path),
+    }
+}
+
+pub fn write_csv(
+    engine: &DataEngine,
+    cols: &[String],
+    total: usize,
+    path: &Path,
+) -> Result<(), String> {
+    let all_data = engine.get_rows_slice(0, total);
+    let mut file_content = String::with_capacity(total * 100);
+
+    push_csv_line(&mut file_content, cols);
+    for row in all_data {
+        push_csv_line(&mut file_content, &row);
+    }
+
+    std::fs::write(path, file_content).map_err(|e| e.to_string())
+}
+
+// RFC 4180 com ';': campo com separador, aspas ou quebra de linha vai entre aspas,
+// com as aspas internas dobradas
+fn push_csv_line(out: &mut String, cells: &[String]) {
+    for (i, cell) in cells.iter().enumerate() {
+        if i > 0 {
+            out.push(';');
+        }
+        if cell.contains([';', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&cell.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(cell);
+        }
+    }
+    out.push('\n');
+}
+
+pub fn write_xlsx(
+    engine: &DataEngine,
+    cols: &[String],
+    total: usize,
+    path: &Path,
+) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header_format = Format::new().set_bold();
+
+    for (col_idx, header_text) in cols.iter().enumerate() {
+        let _ = worksheet.write_with_format(0, col_idx as u16, header_text, &header_format);
+    }
+
+    let all_data = engine.get_rows_slice(0, total);
+
+    for (row_idx, row_data) in all_data.iter().enumerate() {
+        for (col_idx, cell_value) in row_data.iter().enumerate() {
+            if let Ok(num) = cell_value.parse::<f64>() {
+                let _ = worksheet.write_number((row_idx + 1) as u32, col_idx as u16, num);
+            } else {
+                let _ = worksheet.write((row_idx + 1) as u32, col_idx as u16, cell_value);
+            }
+        }
+    }
+
+    workbook.save(path).map_err(|e| e.to_string())
+}
+
+pub fn write_parquet(engine: &DataEngine, cols: &[String], path: &Path) -> Result<(), String> {
+    let batches = engine.cached_results.lock().unwrap().clone();
+    let schema = batches.first().map(|b| b.schema()).unwrap_or_else(|| {
+        let fields: Vec<Field> = cols
+            .iter()
+            .map(|c| Field::new(c, DataType::Utf8, true))
+            .collect();
+        Arc::new(ArrowSchema::new(fields))
+    });
+
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = ArrowWriter::try_new(file, schema, None).map_err(|e| e.to_string())?;
+    for batch in &batches {
+        writer.write(batch).map_err(|e| e.to_string())?;
+    }
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(())
+}
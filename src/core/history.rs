@@ -0,0 +1,157 @@
+use crate::core::report::{ReportDefinition, ReportParameter};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Pasta oculta ao lado dos relatórios: .history/<nome do relatório>/<data>.json
+pub const HISTORY_DIR: &str = ".history";
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ReportVersion {
+    pub salvo_em: String,
+    #[serde(default)]
+    pub comentario: String,
+    #[serde(flatten)]
+    pub relatorio: ReportDefinition,
+}
+
+pub fn history_dir(report_path: &Path) -> PathBuf {
+    let stem = report_path.file_stem().unwrap_or_default();
+    report_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(HISTORY_DIR)
+        .join(stem)
+}
+
+// Mais recente primeiro
+pub fn list_versions(report_path: &Path) -> Vec<ReportVersion> {
+    let mut files: Vec<PathBuf> = fs::read_dir(history_dir(report_path))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files.reverse();
+
+    files
+        .iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect()
+}
+
+// Grava a versão que acabou de ser salva. Na primeira vez o conteúdo anterior
+// (`previous`, com a data do arquivo) entra antes, para não se perder.
+pub fn record_version(
+    report_path: &Path,
+    previous: Option<(&ReportDefinition, DateTime<Local>)>,
+    definition: &ReportDefinition,
+    comment: &str,
+) -> Result<(), String> {
+    let versions = list_versions(report_path);
+    let now = Local::now();
+    if versions.is_empty() {
+        if let Some((previous, saved_at)) = previous.filter(|(p, _)| *p != definition) {
+            let saved_at = saved_at.min(now - chrono::Duration::milliseconds(1));
+            write_version(
+                report_path,
+                previous,
+                "Versão anterior ao histórico",
+                saved_at,
+            )?;
+        }
+    } else if &versions[0].relatorio == definition && comment.trim().is_empty() {
+        return Ok(());
+    }
+    write_version(report_path, definition, comment.trim(), now)
+}
+
+// Cópia fiel do arquivo antes de uma migração de formato, para poder desfazê-la:
+// basta copiá-la de volta por cima do relatório. Não aparece na lista de versões.
+pub fn keep_original(report_path: &Path, content: &str, version: u32) -> Result<PathBuf, String> {
+    let dir = history_dir(report_path);
+    fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar {}: {}", dir.display(), e))?;
+
+    let stamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+    let file = dir.join(format!("{}-original-v{}.json", stamp, version));
+    fs::write(&file, content).map_err(|e| format!("Erro ao gravar {}: {}", file.display(), e))?;
+    Ok(file)
+}
+
+// Data de gravação do relatório, usada para a versão anterior ao histórico
+pub fn file_saved_at(path: &Path) -> Option<DateTime<Local>> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Local>::from(modified))
+}
+
+// Leva o histórico junto quando o relatório é renomeado ou muda de pasta
+pub fn move_history(old_path: &Path, new_path: &Path) -> Result<(), String> {
+    let from = history_dir(old_path);
+    let to = history_dir(new_path);
+    if from == to || !from.is_dir() {
+        return Ok(());
+    }
+    fs::create_dir_all(&to).map_err(|e| format!("Erro ao criar {}: {}", to.display(), e))?;
+    let entries =
+        fs::read_dir(&from).map_err(|e| format!("Erro ao ler {}: {}", from.display(), e))?;
+    for entry in entries.flatten() {
+        let target = to.join(entry.file_name());
+        if !target.exists() {
+            fs::rename(entry.path(), &target)
+                .map_err(|e| format!("Erro ao mover o histórico: {}", e))?;
+        }
+    }
+    let _ = fs::remove_dir_all(&from);
+    Ok(())
+}
+
+// Parâmetros um por linha, para o diff lado a lado
+pub fn parameters_text(params: &[ReportParameter]) -> String {
+    params
+        .iter()
+        .map(|p| {
+            let obrigatorio = if p.requerido {
+                "obrigatório"
+            } else {
+                "opcional"
+            };
+            let mut line = format!(
+                "[{}] {} | {} | padrão: {} | {}",
+                p.id, p.nome, p.tipo, p.valor_padrao, obrigatorio
+            );
+            if p.numerico {
+                line.push_str(" | numérico");
+            }
+            if !p.extra.trim().is_empty() {
+                line.push_str(&format!(" | extra: {}", p.extra.replace('\n', " ")));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn write_version(
+    report_path: &Path,
+    definition: &ReportDefinition,
+    comment: &str,
+    saved_at: DateTime<Local>,
+) -> Result<(), String> {
+    let dir = history_dir(report_path);
+    fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar {}: {}", dir.display(), e))?;
+
+    let version = ReportVersion {
+        salvo_em: saved_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        comentario: comment.to_string(),
+        relatorio: definition.clone(),
+    };
+    let json = serde_json::to_string_pretty(&version).map_err(|e| e.to_string())?;
+    let file = dir.join(format!("{}.json", saved_at.format("%Y%m%d-%H%M%S-%3f")));
+    fs::write(&file, json).map_err(|e| format!("Erro ao gravar {}: {}", file.display(), e))
+}
@@ -0,0 +1,362 @@
+use crate::core::pg_server::constant_time_eq;
+use crate::core::report::{
+    MULTI_SEPARATOR, REPORTS_DIR, ReportRun, apply_parameters, check_declared_parameters,
+    list_reports, load_report, resolve_report_path, run_report,
+};
+use axum::extract::{Query, Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_PAGE_SIZE: usize = 500;
+// Execuções guardadas para servir as páginas seguintes sem refazer a extração
+const RUN_CACHE_ENTRIES: usize = 4;
+
+pub struct HttpApiConfig {
+    pub host: String,
+    pub port: u16,
+    pub token: Option<String>,
+    pub cache_ttl: Duration,
+}
+
+impl HttpApiConfig {
+    pub fn from_env() -> Self {
+        Self {
+            host: env::var("API_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
+            port: env::var("API_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(8787),
+            token: env::var("API_TOKEN").ok().filter(|v| !v.is_empty()),
+            cache_ttl: Duration::from_secs(
+                env::var("API_CACHE_SEGUNDOS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(120),
+            ),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    token: Option<Arc<String>>,
+    reports_dir: Arc<PathBuf>,
+    runs: Arc<Mutex<VecDeque<CachedRun>>>,
+    cache_ttl: Duration,
+}
+
+// Chave: relatório + SQL final, que já traz os valores dos parâmetros
+struct CachedRun {
+    key: String,
+    loaded_at: Instant,
+    run: Arc<ReportRun>,
+}
+
+impl ApiState {
+    fn cached_run(&self, key: &str) -> Option<Arc<ReportRun>> {
+        let mut runs = self.runs.lock().unwrap();
+        runs.retain(|c| c.loaded_at.elapsed() < self.cache_ttl);
+        runs.iter().find(|c| c.key == key).map(|c| c.run.clone())
+    }
+
+    fn store_run(&self, key: String, run: Arc<ReportRun>) {
+        if self.cache_ttl.is_zero() {
+            return;
+        }
+        let mut runs = self.runs.lock().unwrap();
+        runs.retain(|c| c.key != key);
+        runs.push_back(CachedRun {
+            key,
+            loaded_at: Instant::now(),
+            run,
+        });
+        while runs.len() > RUN_CACHE_ENTRIES {
+            runs.pop_front();
+        }
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "erro": self.1 }))).into_response()
+    }
+}
+
+fn bad_request(message: String) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, message)
+}
+
+#[derive(Deserialize)]
+struct ReportQuery {
+    caminho: String,
+}
+
+#[derive(Deserialize)]
+struct RunRequest {
+    caminho: String,
+    #[serde(default)]
+    parametros: HashMap<String, Value>,
+    #[serde(default)]
+    formato: Option<String>,
+    #[serde(default)]
+    pagina: Option<usize>,
+    #[serde(default)]
+    tamanho_pagina: Option<usize>,
+}
+
+// SERVIDOR
+pub fn run(config: HttpApiConfig) -> Result<(), String> {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    rt.block_on(serve(config))
+}
+
+pub async fn serve(config: HttpApiConfig) -> Result<(), String> {
+    let state = ApiState {
+        token: config.token.map(Arc::new),
+        reports_dir: Arc::new(PathBuf::from(REPORTS_DIR)),
+        runs: Arc::new(Mutex::new(VecDeque::new())),
+        cache_ttl: config.cache_ttl,
+    };
+
+    let app = Router::new()
+        .route("/api/relatorios", get(list_handler))
+        .route("/api/relatorio", get(report_handler))
+        .route("/api/executar", post(run_handler))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind((config.host.as_str(), config.port))
+        .await
+        .map_err(|e| format!("Erro ao abrir {}:{}: {}", config.host, config.port, e))?;
+
+    println!("🌐 API HTTP em http://{}:{}/api", config.host, config.port);
+
+    axum::serve(listener, app).await.map_err(|e| e.to_string())
+}
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let Some(expected) = state.token.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let headers = request.headers();
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let api_token = headers.get("x-api-token").and_then(|v| v.to_str().ok());
+
+    let matches = |token: Option<&str>| {
+        token.is_some_and(|t| constant_time_eq(t.as_bytes(), expected.as_bytes()))
+    };
+    if matches(bearer) || matches(api_token) {
+        next.run(request).await
+    } else {
+        ApiError(StatusCode::UNAUTHORIZED, "Token inválido ou ausente".to_string()).into_response()
+    }
+}
+
+// ROTAS
+async fn list_handler(State(state): State<ApiState>) -> Json<Value> {
+    let reports: Vec<Value> = list_reports(&state.reports_dir)
+        .into_iter()
+        .map(|caminho| {
+            let descricao = load_report(&state.reports_dir.join(&caminho))
+                .map(|r| r.descricao)
+                .unwrap_or_default();
+            json!({ "caminho": caminho, "descricao": descricao })
+        })
+        .collect();
+    Json(json!(reports))
+}
+
+async fn report_handler(
+    State(state): State<ApiState>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let path = resolve_report_path(&state.reports_dir, &query.caminho)
+        .map_err(|e| ApiError(StatusCode::NOT_FOUND, e))?;
+    let report = load_report(&path).map_err(bad_request)?;
+
+    Ok(Json(json!({
+        "caminho": query.caminho,
+        "descricao": report.descricao,
+        "parametros": report.parametros,
+    })))
+}
+
+// Valor do JSON no mesmo texto que o formulário produziria: listas viram "a;b",
+// null vira vazio, números e booleanos entram como estão
+fn json_param_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(json_param_value)
+            .collect::<Vec<_>>()
+            .join(&MULTI_SEPARATOR.to_string()),
+        other => other.to_string(),
+    }
+}
+
+async fn run_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<RunRequest>,
+) -> Result<Response, ApiError> {
+    let path = resolve_report_path(&state.reports_dir, &request.caminho)
+        .map_err(|e| ApiError(StatusCode::NOT_FOUND, e))?;
+    let report = load_report(&path).map_err(bad_request)?;
+
+    let values: HashMap<String, String> = request
+        .parametros
+        .iter()
+        .map(|(k, v)| (k.clone(), json_param_value(v)))
+        .collect();
+    check_declared_parameters(&report.parametros, &values).map_err(bad_request)?;
+    let final_sql =
+        apply_parameters(&report.query_sql, &report.parametros, &values).map_err(bad_request)?;
+
+    let page = request.pagina.unwrap_or(1).max(1);
+    let page_size = request.tamanho_pagina.unwrap_or(DEFAULT_PAGE_SIZE);
+    let offset = (page - 1)
+        .checked_mul(page_size)
+        .ok_or_else(|| bad_request(format!("Página {} fora do intervalo", page)))?;
+
+    let report_name = path.to_string_lossy().to_string();
+    let cache_key = format!("{}\n{}", report_name, final_sql);
+    let (run, from_cache) = match state.cached_run(&cache_key) {
+        Some(run) => (run, true),
+        None => {
+            let run = tokio::task::spawn_blocking(move || {
+                run_report(&final_sql, &report_name, &values)
+            })
+            .await
+            .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .map_err(|e| ApiError(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+            let run = Arc::new(run);
+            state.store_run(cache_key, run.clone());
+            (run, false)
+        }
+    };
+
+    let (offset, limit) = if page_size == 0 {
+        (0, run.total_rows)
+    } else {
+        (offset, page_size)
+    };
+    let mut batches = run.engine.get_batches_slice(offset, limit);
+    if batches.is_empty() {
+        // Sem linhas nesta página: o lote vazio leva o cabeçalho do CSV e o esquema do Arrow
+        batches.push(RecordBatch::new_empty(run.schema.clone()));
+    }
+
+    let mut headers = HeaderMap::new();
+    if let Ok(total) = HeaderValue::from_str(&run.total_rows.to_string()) {
+        headers.insert("x-total-linhas", total);
+    }
+    headers.insert(
+        "x-cache",
+        HeaderValue::from_static(if from_cache { "hit" } else { "miss" }),
+    );
+
+    let format = request.formato.unwrap_or_else(|| "json".to_string());
+    match format.as_str() {
+        "json" => {
+            let rows = batches_to_json(&batches).map_err(internal_error)?;
+            Ok((
+                headers,
+                Json(json!({
+                    "colunas": run.columns,
+                    "total_linhas": run.total_rows,
+                    "pagina": page,
+                    "tamanho_pagina": page_size,
+                    "linhas": rows,
+                })),
+            )
+                .into_response())
+        }
+        "csv" => {
+            let body = batches_to_csv(&batches).map_err(internal_error)?;
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/csv; charset=utf-8"),
+            );
+            Ok((headers, body).into_response())
+        }
+        "arrow" => {
+            let body = batches_to_arrow(&run.schema, &batches).map_err(internal_error)?;
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/vnd.apache.arrow.stream"),
+            );
+            Ok((headers, body).into_response())
+        }
+        other => Err(bad_request(format!(
+            "Formato '{}' inválido: use json, csv ou arrow",
+            other
+        ))),
+    }
+}
+
+fn internal_error(e: datafusion::arrow::error::ArrowError) -> ApiError {
+    ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+// SERIALIZAÇÃO
+fn batches_to_json(
+    batches: &[RecordBatch],
+) -> Result<Value, datafusion::arrow::error::ArrowError> {
+    let mut writer = datafusion::arrow::json::ArrayWriter::new(Vec::new());
+    let refs: Vec<&RecordBatch> = batches.iter().collect();
+    writer.write_batches(&refs)?;
+    writer.finish()?;
+
+    let bytes = writer.into_inner();
+    if bytes.is_empty() {
+        return Ok(json!([]));
+    }
+    serde_json::from_slice(&bytes)
+        .map_err(|e| datafusion::arrow::error::ArrowError::JsonError(e.to_string()))
+}
+
+fn batches_to_csv(batches: &[RecordBatch]) -> Result<Vec<u8>, datafusion::arrow::error::ArrowError> {
+    let mut writer = datafusion::arrow::csv::WriterBuilder::new()
+        .with_header(true)
+        .with_delimiter(b';')
+        .build(Vec::new());
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    Ok(writer.into_inner())
+}
+
+fn batches_to_arrow(
+    schema: &SchemaRef,
+    batches: &[RecordBatch],
+) -> Result<Vec<u8>, datafusion::arrow::error::ArrowError> {
+    let mut writer = datafusion::arrow::ipc::writer::StreamWriter::try_new(Vec::new(), schema)?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()?;
+    writer.into_inner()
+}
@@ -0,0 +1,160 @@
+use crate::core::engine::{Column, DataEngine, TableConfig, arrow_field_for, extract_table};
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::{Schema as ArrowSchema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::{TableProvider, TableType};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::context::SessionState;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_plan::memory::MemoryExec;
+use datafusion::prelude::SessionContext;
+use std::any::Any;
+use std::sync::{Arc, atomic::AtomicBool};
+use std::time::{Duration, Instant};
+
+struct CachedExtraction {
+    loaded_at: Instant,
+    schema: SchemaRef,
+    batches: Vec<RecordBatch>,
+}
+
+// Tabela DBISAM que só é extraída do .dat quando uma consulta a utiliza.
+// Apenas as colunas projetadas são lidas e o resultado fica em cache por `ttl`.
+pub struct LazyDbisamTable {
+    table_name: String,
+    base_path: String,
+    config: TableConfig,
+    schema: SchemaRef,
+    ttl: Duration,
+    cache: tokio::sync::Mutex<Option<CachedExtraction>>,
+}
+
+impl LazyDbisamTable {
+    pub fn new(table_name: &str, base_path: &str, config: TableConfig, ttl: Duration) -> Self {
+        let fields: Vec<_> = config.columns.iter().map(arrow_field_for).collect();
+        Self {
+            table_name: table_name.to_string(),
+            base_path: base_path.to_string(),
+            config,
+            schema: Arc::new(ArrowSchema::new(fields)),
+            ttl,
+            cache: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    async fn load(&self, columns: Vec<Column>) -> Result<CachedExtraction> {
+        let base_path = self.base_path.clone();
+        let table_name = self.table_name.clone();
+        let config = self.config.clone();
+        let fields: Vec<_> = columns.iter().map(arrow_field_for).collect();
+
+        let batches = tokio::task::spawn_blocking(move || {
+            extract_table(
+                &base_path,
+                &table_name,
+                &config,
+                &columns,
+                Arc::new(AtomicBool::new(false)),
+            )
+        })
+        .await
+        .map_err(|e| DataFusionError::Execution(e.to_string()))?
+        .map_err(DataFusionError::Execution)?;
+
+        Ok(CachedExtraction {
+            loaded_at: Instant::now(),
+            schema: Arc::new(ArrowSchema::new(fields)),
+            batches,
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for LazyDbisamTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let wanted: Vec<String> = match projection {
+            Some(indexes) => indexes
+                .iter()
+                .map(|i| self.schema.field(*i).name().clone())
+                .collect(),
+            None => self.schema.fields().iter().map(|f| f.name().clone()).collect(),
+        };
+
+        let mut cache = self.cache.lock().await;
+
+        let fresh_columns: Vec<String> = cache
+            .as_ref()
+            .filter(|c| c.loaded_at.elapsed() < self.ttl)
+            .map(|c| c.schema.fields().iter().map(|f| f.name().clone()).collect())
+            .unwrap_or_default();
+
+        let needs_load = cache.is_none()
+            || fresh_columns.is_empty()
+            || wanted.iter().any(|w| !fresh_columns.contains(w));
+
+        if needs_load {
+            // COUNT(*) não projeta colunas, mas a extração precisa de ao menos uma
+            let mut columns: Vec<Column> = self
+                .config
+                .columns
+                .iter()
+                .filter(|c| {
+                    let name = c.name.to_lowercase();
+                    wanted.contains(&name) || fresh_columns.contains(&name)
+                })
+                .cloned()
+                .collect();
+            if columns.is_empty() {
+                columns.extend(self.config.columns.first().cloned());
+            }
+            *cache = Some(self.load(columns).await?);
+        }
+
+        let cached = cache
+            .as_ref()
+            .ok_or_else(|| DataFusionError::Internal("cache de extração vazio".to_string()))?;
+
+        let mapped = wanted
+            .iter()
+            .map(|name| cached.schema.index_of(name))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(Arc::new(MemoryExec::try_new(
+            &[cached.batches.clone()],
+            cached.schema.clone(),
+            Some(mapped),
+        )?))
+    }
+}
+
+pub fn register_lazy_tables(
+    ctx: &SessionContext,
+    engine: &DataEngine,
+    ttl: Duration,
+) -> Result<(), String> {
+    for (name, config) in &engine.schema {
+        let provider = LazyDbisamTable::new(name, &engine.base_path, config.clone(), ttl);
+        ctx.register_table(name.to_lowercase().as_str(), Arc::new(provider))
+            .map_err(|e| format!("Erro ao registrar tabela {}: {}", name, e))?;
+    }
+    Ok(())
+}
@@ -1 +1,26 @@
-pub mod engine;
\ No newline at end of file
+pub mod autocomplete;
+pub mod engine;
+pub mod export;
+pub mod history;
+pub mod http_api;
+pub mod lazy_table;
+pub mod param_lint;
+pub mod pg_catalog;
+pub mod pg_server;
+pub mod preview;
+pub mod query_plan;
+pub mod report;
+pub mod report_format;
+pub mod report_search;
+pub mod report_tree;
+pub mod run_history;
+pub mod scheduler;
+pub mod sql_analysis;
+pub mod sql_diagnostics;
+pub mod sql_format;
+pub mod sql_highlight;
+pub mod sql_text;
+pub mod telemetry;
+pub mod text_diff;
+pub mod trash;
+pub mod udfs;
@@ -0,0 +1,183 @@
+use crate::core::report::{ParamValue, ReportParameter, list_options, range_bound};
+use crate::core::sql_text::{is_placeholder_id, placeholder_edits};
+use std::collections::HashSet;
+
+const KNOWN_TYPES: &[&str] = &[
+    "string", "int", "float", "data", "bool", "pesquisa", "periodo", "lista", "multi", "moeda",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    // Impede salvar e testar
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintIssue {
+    pub severity: Severity,
+    // Posição do parâmetro em `parametros`, quando o problema é de um parâmetro
+    pub param_index: Option<usize>,
+    pub message: String,
+}
+
+impl LintIssue {
+    fn error(param_index: Option<usize>, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            param_index,
+            message,
+        }
+    }
+
+    fn warning(param_index: Option<usize>, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            param_index,
+            message,
+        }
+    }
+}
+
+// Cruza os parâmetros declarados com os [id] usados no SQL
+pub fn lint_parameters(sql: &str, params: &[ReportParameter]) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let used = used_placeholders(sql);
+    let declared: HashSet<&str> = params.iter().map(|p| p.id.as_str()).collect();
+
+    let mut seen = HashSet::new();
+    for (i, p) in params.iter().enumerate() {
+        if p.id.trim().is_empty() {
+            issues.push(LintIssue::error(
+                Some(i),
+                format!("O parâmetro '{}' está sem ID.", p.nome),
+            ));
+            continue;
+        }
+        if !is_placeholder_id(&p.id) {
+            issues.push(LintIssue::error(
+                Some(i),
+                format!(
+                    "O ID '{}' só pode ter letras sem acento, números e '_'.",
+                    p.id
+                ),
+            ));
+        }
+        if !seen.insert(p.id.as_str()) {
+            issues.push(LintIssue::error(
+                Some(i),
+                format!("O ID '{}' está declarado mais de uma vez.", p.id),
+            ));
+        }
+        if matches!(p.tipo.as_str(), "lista" | "multi") && list_options(&p.extra).is_empty() {
+            issues.push(LintIssue::error(
+                Some(i),
+                format!(
+                    "O parâmetro [{}] precisa das opções no campo Extra, uma por linha (valor=Rótulo).",
+                    p.id
+                ),
+            ));
+        }
+        if p.numerico && matches!(p.tipo.as_str(), "lista" | "multi") {
+            let texts: Vec<String> = list_options(&p.extra)
+                .into_iter()
+                .map(|(value, _)| value)
+                .filter(|value| value.parse::<i64>().is_err())
+                .collect();
+            if !texts.is_empty() {
+                issues.push(LintIssue::error(
+                    Some(i),
+                    format!(
+                        "O parâmetro [{}] é numérico, mas tem opções que não são números: {}.",
+                        p.id,
+                        texts.join(", ")
+                    ),
+                ));
+            }
+        }
+        if !KNOWN_TYPES.contains(&p.tipo.as_str()) {
+            issues.push(LintIssue::error(
+                Some(i),
+                format!("O parâmetro [{}] tem tipo desconhecido '{}'.", p.id, p.tipo),
+            ));
+        } else if let Err(e) = ParamValue::parse(p, &p.valor_padrao) {
+            issues.push(LintIssue::error(
+                Some(i),
+                format!("Valor padrão de [{}]: {}", p.id, e),
+            ));
+        }
+
+        if !used.iter().any(|id| refers_to(id, p)) {
+            issues.push(LintIssue::warning(
+                Some(i),
+                format!("O parâmetro [{}] não é usado no SQL.", p.id),
+            ));
+        } else if p.valor_padrao.trim().is_empty() {
+            issues.push(LintIssue::warning(
+                Some(i),
+                format!(
+                    "O parâmetro [{}] está sem valor padrão; o teste da query não roda sem ele.",
+                    p.id
+                ),
+            ));
+        }
+    }
+
+    let mut reported = HashSet::new();
+    for id in &used {
+        // [0], [1]... são índices de array, não parâmetros
+        if declared.contains(id.as_str())
+            || params.iter().any(|p| refers_to(id, p))
+            || id.chars().all(|c| c.is_ascii_digit())
+            || !reported.insert(id.as_str())
+        {
+            continue;
+        }
+        let similar = params.iter().find(|p| p.id.eq_ignore_ascii_case(id));
+        let hint = similar
+            .map(|p| format!(" Você quis dizer [{}]?", p.id))
+            .unwrap_or_default();
+        issues.push(LintIssue::error(
+            None,
+            format!(
+                "[{}] aparece no SQL, mas não há parâmetro com esse ID.{}",
+                id, hint
+            ),
+        ));
+    }
+
+    issues
+}
+
+pub fn has_errors(issues: &[LintIssue]) -> bool {
+    issues.iter().any(|i| i.severity == Severity::Error)
+}
+
+// Texto para o modal de status quando os erros bloqueiam uma ação
+pub fn errors_summary(issues: &[LintIssue]) -> String {
+    let lines: Vec<String> = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .map(|i| format!("• {}", i.message))
+        .collect();
+    format!(
+        "Corrija os parâmetros antes de continuar (aba ⚙️ Parâmetros):\n\n{}",
+        lines.join("\n")
+    )
+}
+
+// [id] do próprio parâmetro ou, num período, [id_inicio] e [id_fim]
+fn refers_to(id: &str, param: &ReportParameter) -> bool {
+    id == param.id
+        || (param.tipo == "periodo" && range_bound(id).is_some_and(|(base, _)| base == param.id))
+}
+
+// IDs dos [placeholders] no código e nas strings, na ordem em que aparecem
+fn used_placeholders(sql: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    let _ = placeholder_edits(sql, |placeholder| {
+        ids.push(placeholder.id.to_string());
+        Ok(None)
+    });
+    ids
+}
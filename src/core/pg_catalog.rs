@@ -0,0 +1,376 @@
+use crate::core::engine::{DataEngine, arrow_field_for};
+use datafusion::arrow::array::{
+    ArrayRef, BooleanArray, Int16Array, Int32Array, Int64Array, StringArray,
+};
+use datafusion::arrow::datatypes::{DataType, Field, Schema as ArrowSchema};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::catalog::CatalogProvider;
+use datafusion::catalog::schema::MemorySchemaProvider;
+use datafusion::datasource::MemTable;
+use datafusion::logical_expr::{ColumnarValue, Volatility, create_udf};
+use datafusion::prelude::SessionContext;
+use datafusion::scalar::ScalarValue;
+use regex::Regex;
+use std::sync::{Arc, LazyLock};
+
+// Abaixo de 14 o Npgsql não inclui multiranges na carga de tipos
+pub const SERVER_VERSION: &str = "13.0 (NewReport DataFusion)";
+pub const DATABASE_NAME: &str = "newreport";
+const DEFAULT_CATALOG: &str = "datafusion";
+
+const OID_NAMESPACE_CATALOG: i64 = 11;
+const OID_NAMESPACE_PUBLIC: i64 = 2200;
+const OID_NAMESPACE_INFORMATION: i64 = 13_000;
+// Primeiro oid livre para objetos de usuário no Postgres
+const FIRST_TABLE_OID: i64 = 16_384;
+
+// Respondido pelo SHOW e pela tabela pg_settings
+pub const SETTINGS: &[(&str, &str)] = &[
+    ("server_version", SERVER_VERSION),
+    ("server_version_num", "130000"),
+    ("server_encoding", "UTF8"),
+    ("client_encoding", "UTF8"),
+    ("datestyle", "ISO, MDY"),
+    ("timezone", "UTC"),
+    ("integer_datetimes", "on"),
+    ("standard_conforming_strings", "on"),
+    ("transaction_isolation", "read committed"),
+    ("default_transaction_read_only", "on"),
+    ("max_identifier_length", "63"),
+    ("max_index_keys", "32"),
+    ("search_path", "public"),
+];
+
+// (oid, nome, tamanho, categoria, tipo) dos tipos que o servidor devolve
+const TYPES: &[(i64, &str, i16, &str, &str)] = &[
+    (16, "bool", 1, "B", "b"),
+    (17, "bytea", -1, "U", "b"),
+    (18, "char", 1, "S", "b"),
+    (19, "name", 64, "S", "b"),
+    (20, "int8", 8, "N", "b"),
+    (21, "int2", 2, "N", "b"),
+    (23, "int4", 4, "N", "b"),
+    (25, "text", -1, "S", "b"),
+    (26, "oid", 4, "N", "b"),
+    (700, "float4", 4, "N", "b"),
+    (701, "float8", 8, "N", "b"),
+    (705, "unknown", -2, "X", "p"),
+    (1043, "varchar", -1, "S", "b"),
+    (1082, "date", 4, "D", "b"),
+    (1083, "time", 8, "D", "b"),
+    (1114, "timestamp", 8, "D", "b"),
+    (1700, "numeric", -1, "N", "b"),
+    (2249, "record", -1, "P", "p"),
+    (2278, "void", 4, "P", "p"),
+];
+
+static RE_CATALOG_TABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)(^|[^.\w"'])(pg_type|pg_namespace|pg_class|pg_attribute|pg_database|pg_proc|pg_range|pg_enum|pg_index|pg_description|pg_settings)\b"#,
+    )
+    .unwrap()
+});
+static RE_CATALOG_CAST: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)::\s*(regclass|regtype|regproc|regnamespace|name|oid)\b").unwrap()
+});
+
+// Esquema pg_catalog em memória, montado a partir do schema.toml, e as funções de
+// sessão que Npgsql (Power BI), DBeaver e Metabase consultam ao conectar
+pub fn register_pg_catalog(ctx: &SessionContext, engine: &DataEngine) -> Result<(), String> {
+    let catalog = ctx
+        .catalog(DEFAULT_CATALOG)
+        .ok_or("Catálogo padrão do DataFusion não encontrado")?;
+    let schema = Arc::new(MemorySchemaProvider::new());
+    catalog
+        .register_schema("pg_catalog", schema)
+        .map_err(|e| format!("Erro ao criar pg_catalog: {}", e))?;
+
+    for (name, batch) in catalog_tables(engine)? {
+        let table = MemTable::try_new(batch.schema(), vec![vec![batch]])
+            .map_err(|e| format!("Erro ao montar pg_catalog.{}: {}", name, e))?;
+        ctx.register_table(format!("pg_catalog.{}", name).as_str(), Arc::new(table))
+            .map_err(|e| format!("Erro ao registrar pg_catalog.{}: {}", name, e))?;
+    }
+
+    let version = format!("PostgreSQL {}", SERVER_VERSION);
+    for (name, value) in [
+        ("version", version.as_str()),
+        ("current_schema", "public"),
+        ("current_database", DATABASE_NAME),
+    ] {
+        let value = value.to_string();
+        ctx.register_udf(create_udf(
+            name,
+            vec![],
+            Arc::new(DataType::Utf8),
+            Volatility::Stable,
+            Arc::new(move |_| {
+                Ok(ColumnarValue::Scalar(ScalarValue::Utf8(Some(
+                    value.clone(),
+                ))))
+            }),
+        ));
+    }
+    Ok(())
+}
+
+// O Postgres procura as tabelas do catálogo em qualquer search_path; aqui elas só
+// existem qualificadas. Os casts para tipos de objeto (::regclass, ::oid) viram texto
+// ou número comum, o suficiente para os filtros que os drivers montam.
+pub fn rewrite_catalog_query(sql: &str) -> String {
+    if !sql.to_lowercase().contains("pg_") && !sql.contains("::") {
+        return sql.to_string();
+    }
+    let qualified = RE_CATALOG_TABLE.replace_all(sql, "${1}pg_catalog.${2}");
+    RE_CATALOG_CAST
+        .replace_all(&qualified, |caps: &regex::Captures| {
+            match caps[1].to_lowercase().as_str() {
+                "oid" => "::bigint",
+                "name" => "::text",
+                _ => "",
+            }
+            .to_string()
+        })
+        .to_string()
+}
+
+fn catalog_tables(engine: &DataEngine) -> Result<Vec<(&'static str, RecordBatch)>, String> {
+    let mut tables = vec![
+        ("pg_namespace", pg_namespace()?),
+        ("pg_type", pg_type()?),
+        ("pg_database", pg_database()?),
+        ("pg_settings", pg_settings()?),
+    ];
+    let (class, attribute) = pg_class_and_attribute(engine)?;
+    tables.push(("pg_class", class));
+    tables.push(("pg_attribute", attribute));
+
+    // Sem linhas, mas com as colunas que as consultas de carga de tipos usam
+    tables.push((
+        "pg_proc",
+        empty(&[
+            ("oid", DataType::Int64),
+            ("proname", DataType::Utf8),
+            ("pronamespace", DataType::Int64),
+        ])?,
+    ));
+    tables.push((
+        "pg_range",
+        empty(&[
+            ("rngtypid", DataType::Int64),
+            ("rngsubtype", DataType::Int64),
+            ("rngmultitypid", DataType::Int64),
+        ])?,
+    ));
+    tables.push((
+        "pg_enum",
+        empty(&[
+            ("oid", DataType::Int64),
+            ("enumtypid", DataType::Int64),
+            ("enumsortorder", DataType::Float64),
+            ("enumlabel", DataType::Utf8),
+        ])?,
+    ));
+    tables.push((
+        "pg_index",
+        empty(&[
+            ("indexrelid", DataType::Int64),
+            ("indrelid", DataType::Int64),
+            ("indisunique", DataType::Boolean),
+            ("indisprimary", DataType::Boolean),
+            ("indkey", DataType::Utf8),
+        ])?,
+    ));
+    tables.push((
+        "pg_description",
+        empty(&[
+            ("objoid", DataType::Int64),
+            ("classoid", DataType::Int64),
+            ("objsubid", DataType::Int32),
+            ("description", DataType::Utf8),
+        ])?,
+    ));
+    Ok(tables)
+}
+
+fn pg_namespace() -> Result<RecordBatch, String> {
+    let rows = [
+        (OID_NAMESPACE_CATALOG, "pg_catalog"),
+        (OID_NAMESPACE_PUBLIC, "public"),
+        (OID_NAMESPACE_INFORMATION, "information_schema"),
+    ];
+    batch(vec![
+        int64("oid", rows.iter().map(|r| r.0).collect()),
+        text("nspname", rows.iter().map(|r| r.1.to_string()).collect()),
+        int64("nspowner", vec![10; rows.len()]),
+    ])
+}
+
+fn pg_type() -> Result<RecordBatch, String> {
+    let n = TYPES.len();
+    batch(vec![
+        int64("oid", TYPES.iter().map(|t| t.0).collect()),
+        text("typname", TYPES.iter().map(|t| t.1.to_string()).collect()),
+        int64("typnamespace", vec![OID_NAMESPACE_CATALOG; n]),
+        int64("typowner", vec![10; n]),
+        int16("typlen", TYPES.iter().map(|t| t.2).collect()),
+        boolean(
+            "typbyval",
+            TYPES.iter().map(|t| matches!(t.2, 1 | 2 | 4 | 8)).collect(),
+        ),
+        text("typtype", TYPES.iter().map(|t| t.4.to_string()).collect()),
+        text(
+            "typcategory",
+            TYPES.iter().map(|t| t.3.to_string()).collect(),
+        ),
+        boolean("typisdefined", vec![true; n]),
+        text("typdelim", vec![",".to_string(); n]),
+        int64("typrelid", vec![0; n]),
+        int64("typelem", vec![0; n]),
+        int64("typarray", vec![0; n]),
+        int64("typreceive", vec![0; n]),
+        boolean("typnotnull", vec![false; n]),
+        int64("typbasetype", vec![0; n]),
+        int32("typtypmod", vec![-1; n]),
+        int32("typndims", vec![0; n]),
+    ])
+}
+
+fn pg_database() -> Result<RecordBatch, String> {
+    batch(vec![
+        int64("oid", vec![FIRST_TABLE_OID - 1]),
+        text("datname", vec![DATABASE_NAME.to_string()]),
+        int64("datdba", vec![10]),
+        int32("encoding", vec![6]),
+        boolean("datistemplate", vec![false]),
+        boolean("datallowconn", vec![true]),
+    ])
+}
+
+fn pg_settings() -> Result<RecordBatch, String> {
+    batch(vec![
+        text("name", SETTINGS.iter().map(|s| s.0.to_string()).collect()),
+        text(
+            "setting",
+            SETTINGS.iter().map(|s| s.1.to_string()).collect(),
+        ),
+    ])
+}
+
+// Uma linha em pg_class por tabela do schema.toml e uma em pg_attribute por coluna
+fn pg_class_and_attribute(engine: &DataEngine) -> Result<(RecordBatch, RecordBatch), String> {
+    let mut class_oids = Vec::new();
+    let mut class_names = Vec::new();
+    let mut class_columns = Vec::new();
+
+    let mut att_relids = Vec::new();
+    let mut att_names = Vec::new();
+    let mut att_types = Vec::new();
+    let mut att_nums = Vec::new();
+    let mut att_lens = Vec::new();
+
+    for (i, (name, config)) in engine.schema.iter().enumerate() {
+        let oid = FIRST_TABLE_OID + i as i64;
+        class_oids.push(oid);
+        class_names.push(name.to_lowercase());
+        class_columns.push(config.columns.len() as i16);
+
+        for (num, column) in config.columns.iter().enumerate() {
+            let field = arrow_field_for(column);
+            let type_oid = match field.data_type() {
+                DataType::Boolean => 16,
+                DataType::Int64 => 20,
+                DataType::Float64 => 701,
+                DataType::Date32 => 1082,
+                _ => 25,
+            };
+            att_relids.push(oid);
+            att_names.push(field.name().clone());
+            att_types.push(type_oid);
+            att_nums.push(num as i16 + 1);
+            att_lens.push(TYPES.iter().find(|t| t.0 == type_oid).map_or(-1, |t| t.2));
+        }
+    }
+
+    let n = class_oids.len();
+    let class = batch(vec![
+        int64("oid", class_oids),
+        text("relname", class_names),
+        int64("relnamespace", vec![OID_NAMESPACE_PUBLIC; n]),
+        int64("reltype", vec![0; n]),
+        int64("relowner", vec![10; n]),
+        text("relkind", vec!["r".to_string(); n]),
+        text("relpersistence", vec!["p".to_string(); n]),
+        int16("relnatts", class_columns),
+        boolean("relhasindex", vec![false; n]),
+        boolean("relispartition", vec![false; n]),
+        boolean("relhasrules", vec![false; n]),
+        boolean("relhastriggers", vec![false; n]),
+    ])?;
+
+    let n = att_relids.len();
+    let attribute = batch(vec![
+        int64("attrelid", att_relids),
+        text("attname", att_names),
+        int64("atttypid", att_types),
+        int16("attnum", att_nums),
+        int16("attlen", att_lens),
+        int32("atttypmod", vec![-1; n]),
+        boolean("attnotnull", vec![false; n]),
+        boolean("atthasdef", vec![false; n]),
+        boolean("attisdropped", vec![false; n]),
+        int32("attndims", vec![0; n]),
+    ])?;
+    Ok((class, attribute))
+}
+
+// MONTAGEM DAS TABELAS
+type CatalogColumn = (Field, ArrayRef);
+
+fn batch(columns: Vec<CatalogColumn>) -> Result<RecordBatch, String> {
+    let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) = columns.into_iter().unzip();
+    RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), arrays).map_err(|e| e.to_string())
+}
+
+fn empty(columns: &[(&str, DataType)]) -> Result<RecordBatch, String> {
+    let fields: Vec<Field> = columns
+        .iter()
+        .map(|(name, data_type)| Field::new(*name, data_type.clone(), true))
+        .collect();
+    Ok(RecordBatch::new_empty(Arc::new(ArrowSchema::new(fields))))
+}
+
+fn int64(name: &str, values: Vec<i64>) -> CatalogColumn {
+    (
+        Field::new(name, DataType::Int64, false),
+        Arc::new(Int64Array::from(values)),
+    )
+}
+
+fn int32(name: &str, values: Vec<i32>) -> CatalogColumn {
+    (
+        Field::new(name, DataType::Int32, false),
+        Arc::new(Int32Array::from(values)),
+    )
+}
+
+fn int16(name: &str, values: Vec<i16>) -> CatalogColumn {
+    (
+        Field::new(name, DataType::Int16, false),
+        Arc::new(Int16Array::from(values)),
+    )
+}
+
+fn boolean(name: &str, values: Vec<bool>) -> CatalogColumn {
+    (
+        Field::new(name, DataType::Boolean, false),
+        Arc::new(BooleanArray::from(values)),
+    )
+}
+
+fn text(name: &str, values: Vec<String>) -> CatalogColumn {
+    (
+        Field::new(name, DataType::Utf8, false),
+        Arc::new(StringArray::from(values)),
+    )
+}
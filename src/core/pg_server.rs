@@ -0,0 +1,1050 @@
+use crate::core::engine::{DataEngine, new_session_context};
+use crate::core::lazy_table::register_lazy_tables;
+use crate::core::pg_catalog::{
+    SERVER_VERSION, SETTINGS, register_pg_catalog, rewrite_catalog_query,
+};
+use crate::core::sql_text::split_statements;
+use datafusion::arrow::array::{Array, ArrayRef, AsArray};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{
+    DataType, Date32Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type,
+    Schema as ArrowSchema, SchemaRef, Time64MicrosecondType, TimeUnit, TimestampMicrosecondType,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::util::display::array_value_to_string;
+use datafusion::execution::context::SQLOptions;
+use datafusion::prelude::*;
+use datafusion::scalar::ScalarValue;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const PROTOCOL_V3: i32 = 196608;
+const SSL_REQUEST_CODE: i32 = 80877103;
+const GSSENC_REQUEST_CODE: i32 = 80877104;
+const CANCEL_REQUEST_CODE: i32 = 80877102;
+// Tamanhos aceitos antes de alocar o corpo da mensagem (o Postgres usa 10000 na abertura)
+const MAX_STARTUP_LEN: i32 = 10_000;
+const MAX_MESSAGE_LEN: i32 = 16 * 1024 * 1024;
+// Dias entre 1970-01-01 (Arrow) e 2000-01-01 (época do protocolo binário do Postgres)
+const PG_EPOCH_DAYS: i32 = 10_957;
+const PG_EPOCH_MICROS: i64 = 946_684_800_000_000;
+
+const OID_BOOL: i32 = 16;
+const OID_BYTEA: i32 = 17;
+const OID_INT8: i32 = 20;
+const OID_INT2: i32 = 21;
+const OID_INT4: i32 = 23;
+const OID_TEXT: i32 = 25;
+const OID_FLOAT4: i32 = 700;
+const OID_FLOAT8: i32 = 701;
+const OID_DATE: i32 = 1082;
+const OID_TIME: i32 = 1083;
+const OID_TIMESTAMP: i32 = 1114;
+const OID_NUMERIC: i32 = 1700;
+
+pub struct PgServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub password: Option<String>,
+    pub cache_ttl: Duration,
+}
+
+impl PgServerConfig {
+    pub fn from_env() -> Self {
+        Self {
+            host: env::var("PG_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
+            port: env::var("PG_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5433),
+            password: env::var("PG_PASSWORD").ok().filter(|v| !v.is_empty()),
+            cache_ttl: Duration::from_secs(
+                env::var("PG_CACHE_SEGUNDOS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(300),
+            ),
+        }
+    }
+}
+
+struct PgError {
+    code: &'static str,
+    message: String,
+}
+
+impl PgError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<std::io::Error> for PgError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new("08006", e.to_string())
+    }
+}
+
+impl From<datafusion::error::DataFusionError> for PgError {
+    fn from(e: datafusion::error::DataFusionError) -> Self {
+        Self::new("42000", e.to_string())
+    }
+}
+
+enum QueryOutcome {
+    Rows(SchemaRef, Vec<RecordBatch>),
+    Command(String),
+}
+
+struct PreparedStatement {
+    sql: String,
+    param_types: Vec<i32>,
+}
+
+struct Portal {
+    sql: String,
+    params: Vec<ScalarValue>,
+    result_formats: Vec<i16>,
+    // Linhas ainda não enviadas quando o Execute pediu um limite
+    pending: Option<VecDeque<RecordBatch>>,
+}
+
+// SERVIDOR
+pub fn run(config: PgServerConfig) -> Result<(), String> {
+    let engine = DataEngine::new();
+    if engine.schema.is_empty() {
+        return Err("schema.toml não encontrado ou vazio!".to_string());
+    }
+
+    let ctx = new_session_context();
+    register_lazy_tables(&ctx, &engine, config.cache_ttl)?;
+    register_pg_catalog(&ctx, &engine)?;
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    rt.block_on(async move {
+        let listener = TcpListener::bind((config.host.as_str(), config.port))
+            .await
+            .map_err(|e| format!("Erro ao abrir {}:{}: {}", config.host, config.port, e))?;
+
+        println!(
+            "🐘 Servidor PostgreSQL (somente leitura) em {}:{} com {} tabelas do schema.toml",
+            config.host,
+            config.port,
+            engine.schema.len()
+        );
+
+        let password = Arc::new(config.password);
+        let mut next_process_id = 1;
+
+        loop {
+            let (socket, addr) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    println!("Erro ao aceitar conexão: {}", e);
+                    continue;
+                }
+            };
+
+            let connection = PgConnection::new(socket, ctx.clone(), next_process_id);
+            let password = password.clone();
+            next_process_id += 1;
+
+            tokio::spawn(async move {
+                if let Err(e) = connection.serve(password.as_deref()).await {
+                    println!("Conexão {} encerrada com erro: {}", addr, e.message);
+                }
+            });
+        }
+    })
+}
+
+// CONEXÃO
+struct PgConnection {
+    stream: TcpStream,
+    out: Vec<u8>,
+    ctx: SessionContext,
+    process_id: i32,
+    statements: HashMap<String, PreparedStatement>,
+    portals: HashMap<String, Portal>,
+}
+
+impl PgConnection {
+    fn new(stream: TcpStream, ctx: SessionContext, process_id: i32) -> Self {
+        Self {
+            stream,
+            out: Vec::with_capacity(8 * 1024),
+            ctx,
+            process_id,
+            statements: HashMap::new(),
+            portals: HashMap::new(),
+        }
+    }
+
+    async fn serve(mut self, password: Option<&str>) -> Result<(), PgError> {
+        if !self.startup(password).await? {
+            return Ok(());
+        }
+
+        let mut skip_until_sync = false;
+
+        loop {
+            let tag = match self.stream.read_u8().await {
+                Ok(tag) => tag,
+                Err(_) => return Ok(()),
+            };
+            let body = self.read_body().await?;
+
+            if skip_until_sync && tag != b'S' {
+                continue;
+            }
+
+            let result = match tag {
+                b'Q' => {
+                    self.simple_query(&body).await?;
+                    Ok(())
+                }
+                b'P' => self.parse(&body).await,
+                b'B' => self.bind(&body),
+                b'D' => self.describe(&body).await,
+                b'E' => self.execute(&body).await,
+                b'C' => self.close(&body),
+                b'S' => {
+                    skip_until_sync = false;
+                    self.ready_for_query();
+                    self.flush().await?;
+                    Ok(())
+                }
+                b'H' => {
+                    self.flush().await?;
+                    Ok(())
+                }
+                b'X' => return Ok(()),
+                other => Err(PgError::new(
+                    "08P01",
+                    format!("Mensagem '{}' não suportada", other as char),
+                )),
+            };
+
+            if let Err(e) = result {
+                self.error_response(&e);
+                skip_until_sync = true;
+                self.flush().await?;
+            }
+        }
+    }
+
+    async fn startup(&mut self, password: Option<&str>) -> Result<bool, PgError> {
+        loop {
+            let len = self.stream.read_i32().await?;
+            if !(8..=MAX_STARTUP_LEN).contains(&len) {
+                return Err(PgError::new(
+                    "08P01",
+                    format!("Tamanho de pacote de abertura inválido: {}", len),
+                ));
+            }
+            let mut body = vec![0u8; (len - 4) as usize];
+            self.stream.read_exact(&mut body).await?;
+            let mut reader = MessageReader::new(&body);
+
+            match reader.i32()? {
+                SSL_REQUEST_CODE | GSSENC_REQUEST_CODE => {
+                    self.stream.write_all(b"N").await?;
+                }
+                CANCEL_REQUEST_CODE => return Ok(false),
+                PROTOCOL_V3 => break,
+                other => {
+                    self.error_response(&PgError::new(
+                        "08P01",
+                        format!("Versão de protocolo {} não suportada", other),
+                    ));
+                    self.flush().await?;
+                    return Ok(false);
+                }
+            }
+        }
+
+        if let Some(expected) = password {
+            self.message(b'R', &MessageBuilder::new().i32(3).finish());
+            self.flush().await?;
+
+            let tag = self.stream.read_u8().await?;
+            let body = self.read_body().await?;
+            let received = MessageReader::new(&body).cstr()?;
+
+            if tag != b'p' || !constant_time_eq(received.as_bytes(), expected.as_bytes()) {
+                self.error_response(&PgError::new("28P01", "Senha inválida"));
+                self.flush().await?;
+                return Ok(false);
+            }
+        }
+
+        self.message(b'R', &MessageBuilder::new().i32(0).finish());
+        for (name, value) in [
+            ("server_version", SERVER_VERSION),
+            ("server_encoding", "UTF8"),
+            ("client_encoding", "UTF8"),
+            ("DateStyle", "ISO, MDY"),
+            ("TimeZone", "UTC"),
+            ("integer_datetimes", "on"),
+            ("standard_conforming_strings", "on"),
+            ("application_name", "NewReport"),
+        ] {
+            self.message(b'S', &MessageBuilder::new().cstr(name).cstr(value).finish());
+        }
+        let secret = self.process_id.wrapping_mul(7919);
+        self.message(
+            b'K',
+            &MessageBuilder::new().i32(self.process_id).i32(secret).finish(),
+        );
+        self.ready_for_query();
+        self.flush().await?;
+        Ok(true)
+    }
+
+    // PROTOCOLO SIMPLES
+    async fn simple_query(&mut self, body: &[u8]) -> Result<(), PgError> {
+        let sql = MessageReader::new(body).cstr()?;
+        let statements = split_statements(&sql);
+
+        if statements.is_empty() {
+            self.message(b'I', &[]);
+        }
+
+        for statement in statements {
+            match self.run_statement(&statement, Vec::new()).await {
+                Ok(QueryOutcome::Rows(schema, batches)) => {
+                    self.row_description(&schema, &[]);
+                    let count = self.data_rows(&batches, &[])?;
+                    self.command_complete(&format!("SELECT {}", count));
+                }
+                Ok(QueryOutcome::Command(tag)) => self.command_complete(&tag),
+                Err(e) => {
+                    self.error_response(&e);
+                    break;
+                }
+            }
+        }
+
+        self.ready_for_query();
+        self.flush().await
+    }
+
+    // PROTOCOLO ESTENDIDO
+    async fn parse(&mut self, body: &[u8]) -> Result<(), PgError> {
+        let mut reader = MessageReader::new(body);
+        let name = reader.cstr()?;
+        let sql = reader.cstr()?;
+        let count = reader.count()?;
+        let param_types = (0..count)
+            .map(|_| reader.i32())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.statements.insert(
+            name,
+            PreparedStatement {
+                sql: sql.trim().trim_end_matches(';').to_string(),
+                param_types,
+            },
+        );
+        self.message(b'1', &[]);
+        Ok(())
+    }
+
+    fn bind(&mut self, body: &[u8]) -> Result<(), PgError> {
+        let mut reader = MessageReader::new(body);
+        let portal_name = reader.cstr()?;
+        let statement_name = reader.cstr()?;
+
+        let statement = self.statements.get(&statement_name).ok_or_else(|| {
+            PgError::new("26000", format!("Statement '{}' não existe", statement_name))
+        })?;
+
+        let format_count = reader.count()?;
+        let param_formats = (0..format_count)
+            .map(|_| reader.i16())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let value_count = reader.count()?;
+        let mut params = Vec::new();
+        for i in 0..value_count {
+            let len = reader.i32()?;
+            let format = format_for(&param_formats, i);
+            let oid = statement.param_types.get(i).copied().unwrap_or(0);
+            if len < 0 {
+                params.push(ScalarValue::try_from(&pg_type_to_arrow(oid))?);
+            } else {
+                let raw = reader.bytes(len as usize)?;
+                params.push(decode_param(raw, format, oid)?);
+            }
+        }
+
+        let result_count = reader.count()?;
+        let result_formats = (0..result_count)
+            .map(|_| reader.i16())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let sql = statement.sql.clone();
+        self.portals.insert(
+            portal_name,
+            Portal {
+                sql,
+                params,
+                result_formats,
+                pending: None,
+            },
+        );
+        self.message(b'2', &[]);
+        Ok(())
+    }
+
+    async fn describe(&mut self, body: &[u8]) -> Result<(), PgError> {
+        let mut reader = MessageReader::new(body);
+        let kind = reader.u8()?;
+        let name = reader.cstr()?;
+
+        if kind == b'S' {
+            let statement = self
+                .statements
+                .get(&name)
+                .ok_or_else(|| PgError::new("26000", format!("Statement '{}' não existe", name)))?;
+            let sql = statement.sql.clone();
+            let declared = statement.param_types.clone();
+
+            let plan = self.plan(&sql).await?;
+            let mut param_oids = declared;
+            if let Some(df) = &plan {
+                let inferred = df.logical_plan().get_parameter_types()?;
+                for i in 0..inferred.len() {
+                    let inferred_oid = inferred
+                        .get(&format!("${}", i + 1))
+                        .and_then(|t| t.as_ref())
+                        .map(arrow_to_pg_type)
+                        .unwrap_or(OID_TEXT);
+                    match param_oids.get_mut(i) {
+                        Some(oid) if *oid == 0 => *oid = inferred_oid,
+                        Some(_) => {}
+                        None => param_oids.push(inferred_oid),
+                    }
+                }
+            }
+
+            let mut message = MessageBuilder::new().i16(param_oids.len() as i16);
+            for oid in &param_oids {
+                message = message.i32(*oid);
+            }
+            self.message(b't', &message.finish());
+
+            match plan {
+                Some(df) => self.row_description(&Arc::new(ArrowSchema::from(df.schema())), &[]),
+                None => self.message(b'n', &[]),
+            }
+        } else {
+            let portal = self
+                .portals
+                .get(&name)
+                .ok_or_else(|| PgError::new("34000", format!("Portal '{}' não existe", name)))?;
+            let sql = portal.sql.clone();
+            let formats = portal.result_formats.clone();
+
+            match self.plan(&sql).await? {
+                Some(df) => {
+                    self.row_description(&Arc::new(ArrowSchema::from(df.schema())), &formats)
+                }
+                None => self.message(b'n', &[]),
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute(&mut self, body: &[u8]) -> Result<(), PgError> {
+        let mut reader = MessageReader::new(body);
+        let name = reader.cstr()?;
+        // 0 = todas as linhas
+        let max_rows = reader.i32()?.max(0) as usize;
+
+        let mut portal = self
+            .portals
+            .remove(&name)
+            .ok_or_else(|| PgError::new("34000", format!("Portal '{}' não existe", name)))?;
+        let result = self.execute_portal(&mut portal, max_rows).await;
+        self.portals.insert(name, portal);
+        result
+    }
+
+    async fn execute_portal(
+        &mut self,
+        portal: &mut Portal,
+        max_rows: usize,
+    ) -> Result<(), PgError> {
+        if portal.sql.is_empty() {
+            self.message(b'I', &[]);
+            return Ok(());
+        }
+
+        let mut pending = match portal.pending.take() {
+            Some(pending) => pending,
+            None => match self
+                .run_statement(&portal.sql, portal.params.clone())
+                .await?
+            {
+                QueryOutcome::Rows(_, batches) => batches.into(),
+                QueryOutcome::Command(tag) => {
+                    self.command_complete(&tag);
+                    return Ok(());
+                }
+            },
+        };
+
+        let batches = take_rows(&mut pending, max_rows);
+        let count = self.data_rows(&batches, &portal.result_formats)?;
+        if pending.is_empty() {
+            self.command_complete(&format!("SELECT {}", count));
+        } else {
+            // PortalSuspended: o cliente pede o resto com outro Execute
+            self.message(b's', &[]);
+            portal.pending = Some(pending);
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, body: &[u8]) -> Result<(), PgError> {
+        let mut reader = MessageReader::new(body);
+        let kind = reader.u8()?;
+        let name = reader.cstr()?;
+        if kind == b'S' {
+            self.statements.remove(&name);
+        } else {
+            self.portals.remove(&name);
+        }
+        self.message(b'3', &[]);
+        Ok(())
+    }
+
+    // EXECUÇÃO
+    async fn plan(&self, sql: &str) -> Result<Option<DataFrame>, PgError> {
+        if sql.is_empty() || session_command(sql).is_some() {
+            return Ok(None);
+        }
+        let options = SQLOptions::new()
+            .with_allow_ddl(false)
+            .with_allow_dml(false)
+            .with_allow_statements(false);
+        let sql = rewrite_catalog_query(sql);
+        Ok(Some(self.ctx.sql_with_options(&sql, options).await?))
+    }
+
+    async fn run_statement(
+        &self,
+        sql: &str,
+        params: Vec<ScalarValue>,
+    ) -> Result<QueryOutcome, PgError> {
+        if let Some(outcome) = session_command(sql) {
+            return Ok(outcome);
+        }
+
+        let Some(mut df) = self.plan(sql).await? else {
+            return Ok(QueryOutcome::Command("SELECT 0".to_string()));
+        };
+
+        if !params.is_empty() {
+            let expected = df.logical_plan().get_parameter_types()?;
+            let params = params
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| match expected.get(&format!("${}", i + 1)) {
+                    Some(Some(target)) if value.data_type() != *target => value.cast_to(target),
+                    _ => Ok(value),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            df = df.with_param_values(params)?;
+        }
+
+        let schema = Arc::new(ArrowSchema::from(df.schema()));
+        let batches = df.collect().await?;
+        Ok(QueryOutcome::Rows(schema, batches))
+    }
+
+    // MENSAGENS DE SAÍDA
+    fn message(&mut self, tag: u8, body: &[u8]) {
+        self.out.push(tag);
+        self.out.extend_from_slice(&((body.len() + 4) as i32).to_be_bytes());
+        self.out.extend_from_slice(body);
+    }
+
+    fn ready_for_query(&mut self) {
+        self.message(b'Z', b"I");
+    }
+
+    fn command_complete(&mut self, tag: &str) {
+        self.message(b'C', &MessageBuilder::new().cstr(tag).finish());
+    }
+
+    fn error_response(&mut self, error: &PgError) {
+        let body = MessageBuilder::new()
+            .u8(b'S')
+            .cstr("ERROR")
+            .u8(b'V')
+            .cstr("ERROR")
+            .u8(b'C')
+            .cstr(error.code)
+            .u8(b'M')
+            .cstr(&error.message)
+            .u8(0)
+            .finish();
+        self.message(b'E', &body);
+    }
+
+    fn row_description(&mut self, schema: &SchemaRef, formats: &[i16]) {
+        let mut body = MessageBuilder::new().i16(schema.fields().len() as i16);
+        for (i, field) in schema.fields().iter().enumerate() {
+            let oid = arrow_to_pg_type(field.data_type());
+            body = body
+                .cstr(field.name())
+                .i32(0)
+                .i16(0)
+                .i32(oid)
+                .i16(pg_type_size(oid))
+                .i32(-1)
+                .i16(format_for(formats, i));
+        }
+        self.message(b'T', &body.finish());
+    }
+
+    fn data_rows(&mut self, batches: &[RecordBatch], formats: &[i16]) -> Result<usize, PgError> {
+        let mut count = 0;
+        for batch in batches {
+            for row in 0..batch.num_rows() {
+                let mut body = MessageBuilder::new().i16(batch.num_columns() as i16);
+                for (col_idx, column) in batch.columns().iter().enumerate() {
+                    if column.is_null(row) {
+                        body = body.i32(-1);
+                        continue;
+                    }
+                    let value = if format_for(formats, col_idx) == 1 {
+                        encode_binary(column, row)?
+                    } else {
+                        encode_text(column, row)?
+                    };
+                    body = body.i32(value.len() as i32).bytes(&value);
+                }
+                self.message(b'D', &body.finish());
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn read_body(&mut self) -> Result<Vec<u8>, PgError> {
+        let len = self.stream.read_i32().await?;
+        if !(4..=MAX_MESSAGE_LEN).contains(&len) {
+            return Err(PgError::new(
+                "08P01",
+                format!("Tamanho de mensagem inválido: {}", len),
+            ));
+        }
+        let mut body = vec![0u8; (len - 4) as usize];
+        self.stream.read_exact(&mut body).await?;
+        Ok(body)
+    }
+
+    async fn flush(&mut self) -> Result<(), PgError> {
+        if !self.out.is_empty() {
+            self.stream.write_all(&self.out).await?;
+            self.out.clear();
+        }
+        self.stream.flush().await?;
+        Ok(())
+    }
+}
+
+// Comandos de sessão que clientes como DBeaver, Metabase e Npgsql enviam ao conectar
+fn session_command(sql: &str) -> Option<QueryOutcome> {
+    let upper = sql.trim().to_uppercase();
+    let first = upper.split_whitespace().next().unwrap_or_default();
+
+    match first {
+        "SET" | "RESET" | "DISCARD" | "DEALLOCATE" => Some(QueryOutcome::Command(first.to_string())),
+        "BEGIN" | "START" => Some(QueryOutcome::Command("BEGIN".to_string())),
+        "COMMIT" | "END" => Some(QueryOutcome::Command("COMMIT".to_string())),
+        "ROLLBACK" | "ABORT" => Some(QueryOutcome::Command("ROLLBACK".to_string())),
+        "SHOW" => {
+            let name = upper
+                .trim_start_matches("SHOW")
+                .trim()
+                .trim_end_matches(';')
+                .to_lowercase();
+            let (columns, rows): (Vec<&str>, Vec<Vec<&str>>) = if name == "all" {
+                (
+                    vec!["name", "setting"],
+                    vec![
+                        SETTINGS.iter().map(|s| s.0).collect(),
+                        SETTINGS.iter().map(|s| s.1).collect(),
+                    ],
+                )
+            } else {
+                let key = match name.as_str() {
+                    "transaction isolation level" => "transaction_isolation",
+                    other => other,
+                };
+                let value = SETTINGS.iter().find(|s| s.0 == key).map_or("", |s| s.1);
+                (vec![name.as_str()], vec![vec![value]])
+            };
+            let schema = Arc::new(ArrowSchema::new(
+                columns
+                    .iter()
+                    .map(|c| datafusion::arrow::datatypes::Field::new(*c, DataType::Utf8, true))
+                    .collect::<Vec<_>>(),
+            ));
+            let arrays: Vec<ArrayRef> = rows
+                .into_iter()
+                .map(|values| {
+                    Arc::new(datafusion::arrow::array::StringArray::from(values)) as ArrayRef
+                })
+                .collect();
+            let batch = RecordBatch::try_new(schema.clone(), arrays).ok()?;
+            Some(QueryOutcome::Rows(schema, vec![batch]))
+        }
+        _ => None,
+    }
+}
+
+// AUTENTICAÇÃO
+// Compara senhas/tokens sem parar no primeiro byte diferente, para o tempo de resposta
+// não revelar quantos caracteres o cliente acertou
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// CONVERSÃO DE TIPOS
+fn format_for(formats: &[i16], idx: usize) -> i16 {
+    match formats.len() {
+        0 => 0,
+        1 => formats[0],
+        _ => formats.get(idx).copied().unwrap_or(0),
+    }
+}
+
+fn arrow_to_pg_type(data_type: &DataType) -> i32 {
+    match data_type {
+        DataType::Boolean => OID_BOOL,
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 => OID_INT2,
+        DataType::Int32 | DataType::UInt16 => OID_INT4,
+        DataType::Int64 | DataType::UInt32 | DataType::UInt64 => OID_INT8,
+        DataType::Float16 | DataType::Float32 => OID_FLOAT4,
+        DataType::Float64 => OID_FLOAT8,
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => OID_NUMERIC,
+        DataType::Date32 | DataType::Date64 => OID_DATE,
+        DataType::Time32(_) | DataType::Time64(_) => OID_TIME,
+        DataType::Timestamp(_, _) => OID_TIMESTAMP,
+        DataType::Binary | DataType::LargeBinary => OID_BYTEA,
+        _ => OID_TEXT,
+    }
+}
+
+fn pg_type_to_arrow(oid: i32) -> DataType {
+    match oid {
+        OID_BOOL => DataType::Boolean,
+        OID_INT2 => DataType::Int16,
+        OID_INT4 => DataType::Int32,
+        OID_INT8 => DataType::Int64,
+        OID_FLOAT4 => DataType::Float32,
+        OID_FLOAT8 | OID_NUMERIC => DataType::Float64,
+        OID_DATE => DataType::Date32,
+        OID_TIMESTAMP => DataType::Timestamp(TimeUnit::Microsecond, None),
+        _ => DataType::Utf8,
+    }
+}
+
+fn pg_type_size(oid: i32) -> i16 {
+    match oid {
+        OID_BOOL => 1,
+        OID_INT2 => 2,
+        OID_INT4 | OID_FLOAT4 | OID_DATE => 4,
+        OID_INT8 | OID_FLOAT8 | OID_TIME | OID_TIMESTAMP => 8,
+        _ => -1,
+    }
+}
+
+fn decode_param(raw: &[u8], format: i16, oid: i32) -> Result<ScalarValue, PgError> {
+    if format == 0 {
+        let text = String::from_utf8_lossy(raw).to_string();
+        let target = pg_type_to_arrow(oid);
+        return ScalarValue::try_from_string(text, &target).map_err(PgError::from);
+    }
+
+    let invalid = || PgError::new("22P03", format!("Parâmetro binário inválido para o tipo {}", oid));
+    Ok(match oid {
+        OID_BOOL => ScalarValue::Boolean(Some(raw.first().copied().unwrap_or(0) != 0)),
+        OID_INT2 => ScalarValue::Int16(Some(i16::from_be_bytes(raw.try_into().map_err(|_| invalid())?))),
+        OID_INT4 => ScalarValue::Int32(Some(i32::from_be_bytes(raw.try_into().map_err(|_| invalid())?))),
+        OID_INT8 => ScalarValue::Int64(Some(i64::from_be_bytes(raw.try_into().map_err(|_| invalid())?))),
+        OID_FLOAT4 => ScalarValue::Float32(Some(f32::from_be_bytes(raw.try_into().map_err(|_| invalid())?))),
+        OID_FLOAT8 => ScalarValue::Float64(Some(f64::from_be_bytes(raw.try_into().map_err(|_| invalid())?))),
+        OID_DATE => {
+            let days = i32::from_be_bytes(raw.try_into().map_err(|_| invalid())?);
+            ScalarValue::Date32(Some(days + PG_EPOCH_DAYS))
+        }
+        OID_TIMESTAMP => {
+            let micros = i64::from_be_bytes(raw.try_into().map_err(|_| invalid())?);
+            ScalarValue::TimestampMicrosecond(Some(micros + PG_EPOCH_MICROS), None)
+        }
+        OID_TEXT | 0 => ScalarValue::Utf8(Some(String::from_utf8_lossy(raw).to_string())),
+        _ => return Err(invalid()),
+    })
+}
+
+fn encode_text(column: &ArrayRef, row: usize) -> Result<Vec<u8>, PgError> {
+    let text = match column.data_type() {
+        DataType::Boolean => {
+            if column.as_boolean().value(row) { "t" } else { "f" }.to_string()
+        }
+        DataType::Timestamp(_, _) => array_value_to_string(column, row)?.replacen('T', " ", 1),
+        _ => array_value_to_string(column, row)?,
+    };
+    Ok(text.into_bytes())
+}
+
+// Codifica pelo OID anunciado no RowDescription, para que todo tipo anunciado tenha
+// representação binária (Int8/UInt8 saem como int2, decimais como numeric)
+fn encode_binary(column: &ArrayRef, row: usize) -> Result<Vec<u8>, PgError> {
+    let cell = |target: DataType| cast(&column.slice(row, 1), &target);
+    Ok(match arrow_to_pg_type(column.data_type()) {
+        OID_BOOL => vec![column.as_boolean().value(row) as u8],
+        OID_INT2 => cell(DataType::Int16)?
+            .as_primitive::<Int16Type>()
+            .value(0)
+            .to_be_bytes()
+            .to_vec(),
+        OID_INT4 => cell(DataType::Int32)?
+            .as_primitive::<Int32Type>()
+            .value(0)
+            .to_be_bytes()
+            .to_vec(),
+        OID_INT8 => cell(DataType::Int64)?
+            .as_primitive::<Int64Type>()
+            .value(0)
+            .to_be_bytes()
+            .to_vec(),
+        OID_FLOAT4 => cell(DataType::Float32)?
+            .as_primitive::<Float32Type>()
+            .value(0)
+            .to_be_bytes()
+            .to_vec(),
+        OID_FLOAT8 => column
+            .as_primitive::<Float64Type>()
+            .value(row)
+            .to_be_bytes()
+            .to_vec(),
+        OID_NUMERIC => encode_numeric(&array_value_to_string(column, row)?)?,
+        OID_DATE => {
+            let days = cell(DataType::Date32)?
+                .as_primitive::<Date32Type>()
+                .value(0)
+                - PG_EPOCH_DAYS;
+            days.to_be_bytes().to_vec()
+        }
+        OID_TIME => cell(DataType::Time64(TimeUnit::Microsecond))?
+            .as_primitive::<Time64MicrosecondType>()
+            .value(0)
+            .to_be_bytes()
+            .to_vec(),
+        OID_TIMESTAMP => {
+            let micros = cell(DataType::Timestamp(TimeUnit::Microsecond, None))?
+                .as_primitive::<TimestampMicrosecondType>()
+                .value(0);
+            (micros - PG_EPOCH_MICROS).to_be_bytes().to_vec()
+        }
+        OID_BYTEA => cell(DataType::Binary)?.as_binary::<i32>().value(0).to_vec(),
+        _ => encode_text(column, row)?,
+    })
+}
+
+// NUMERIC binário: nº de dígitos, peso, sinal e escala, seguidos dos dígitos na base 10000
+fn encode_numeric(text: &str) -> Result<Vec<u8>, PgError> {
+    let invalid = || PgError::new("22P03", format!("Valor numérico inválido: {}", text));
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if !(integer.chars().chain(fraction.chars())).all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let int_width = integer.len().div_ceil(4) * 4;
+    let frac_width = fraction.len().div_ceil(4) * 4;
+    let padded = format!(
+        "{:0>iw$}{:0<fw$}",
+        integer,
+        fraction,
+        iw = int_width,
+        fw = frac_width
+    );
+    let mut digits: Vec<i16> = padded
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap().parse().unwrap())
+        .collect();
+    let mut weight = (int_width / 4) as i16 - 1;
+
+    let leading = digits.iter().take_while(|d| **d == 0).count();
+    digits.drain(..leading);
+    weight -= leading as i16;
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    if digits.is_empty() {
+        weight = 0;
+    }
+
+    let sign: u16 = if negative && !digits.is_empty() {
+        0x4000
+    } else {
+        0
+    };
+    let mut body = MessageBuilder::new()
+        .i16(digits.len() as i16)
+        .i16(weight)
+        .bytes(&sign.to_be_bytes())
+        .i16(fraction.len() as i16);
+    for digit in digits {
+        body = body.i16(digit);
+    }
+    Ok(body.finish())
+}
+
+// Tira até `limit` linhas do começo (0 = todas), fatiando o lote que ficar no meio
+fn take_rows(pending: &mut VecDeque<RecordBatch>, limit: usize) -> Vec<RecordBatch> {
+    let mut taken: Vec<RecordBatch> = Vec::new();
+    if limit == 0 {
+        taken.extend(pending.drain(..));
+        return taken;
+    }
+    let mut remaining = limit;
+    while remaining > 0 {
+        let Some(batch) = pending.pop_front() else {
+            break;
+        };
+        if batch.num_rows() <= remaining {
+            remaining -= batch.num_rows();
+            taken.push(batch);
+        } else {
+            taken.push(batch.slice(0, remaining));
+            pending.push_front(batch.slice(remaining, batch.num_rows() - remaining));
+            remaining = 0;
+        }
+    }
+    pending.retain(|batch| batch.num_rows() > 0);
+    taken
+}
+
+impl From<datafusion::arrow::error::ArrowError> for PgError {
+    fn from(e: datafusion::arrow::error::ArrowError) -> Self {
+        Self::new("XX000", e.to_string())
+    }
+}
+
+// LEITURA E ESCRITA DO PROTOCOLO
+struct MessageReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MessageReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], PgError> {
+        let slice = self
+            .buf
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| PgError::new("08P01", "Mensagem truncada"))?;
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, PgError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, PgError> {
+        let raw = self.bytes(2)?;
+        Ok(i16::from_be_bytes([raw[0], raw[1]]))
+    }
+
+    // Quantidade de itens que vem a seguir; negativa é erro de protocolo
+    fn count(&mut self) -> Result<usize, PgError> {
+        let count = self.i16()?;
+        usize::try_from(count)
+            .map_err(|_| PgError::new("08P01", format!("Quantidade inválida: {}", count)))
+    }
+
+    fn i32(&mut self) -> Result<i32, PgError> {
+        let raw = self.bytes(4)?;
+        Ok(i32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]))
+    }
+
+    fn cstr(&mut self) -> Result<String, PgError> {
+        let rest = &self.buf[self.pos.min(self.buf.len())..];
+        let end = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| PgError::new("08P01", "String sem terminador"))?;
+        let value = String::from_utf8_lossy(&rest[..end]).to_string();
+        self.pos += end + 1;
+        Ok(value)
+    }
+}
+
+struct MessageBuilder(Vec<u8>);
+
+impl MessageBuilder {
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    fn u8(mut self, value: u8) -> Self {
+        self.0.push(value);
+        self
+    }
+
+    fn i16(mut self, value: i16) -> Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn i32(mut self, value: i32) -> Self {
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn cstr(mut self, value: &str) -> Self {
+        self.0.extend_from_slice(value.as_bytes());
+        self.0.push(0);
+        self
+    }
+
+    fn bytes(mut self, value: &[u8]) -> Self {
+        self.0.extend_from_slice(value);
+        self
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.0
+    }
+}
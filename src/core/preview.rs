@@ -0,0 +1,144 @@
+use datafusion::arrow::array::Array;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::arrow::util::display::array_value_to_string;
+use std::collections::HashSet;
+
+pub const PREVIEW_SIZES: &[usize] = &[100, 500, 1000];
+// Registros lidos de cada tabela no teste com amostra
+pub const SAMPLE_SIZES: &[usize] = &[1_000, 5_000, 20_000];
+// Linhas do resultado mostradas na grade do editor
+const SAMPLE_GRID_ROWS: usize = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnStats {
+    pub nome: String,
+    pub tipo: String,
+    pub nulos: usize,
+    pub vazios: usize,
+    pub distintos: usize,
+    pub minimo: String,
+    pub maximo: String,
+}
+
+// Resultado do último comando rodado sobre a amostra
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct SampleResult {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub total_rows: usize,
+    // Tabela e quantos registros da amostra foram carregados dela
+    pub tables: Vec<(String, usize)>,
+    pub elapsed_ms: u128,
+}
+
+impl SampleResult {
+    pub fn new(batches: &[RecordBatch], tables: Vec<(String, usize)>, elapsed_ms: u128) -> Self {
+        let headers = batches.first().map(batch_headers).unwrap_or_default();
+        let mut rows = Vec::new();
+        // Filtros e joins costumam devolver lotes vazios antes dos que têm linhas
+        for batch in batches.iter().filter(|b| b.num_rows() > 0) {
+            if rows.len() >= SAMPLE_GRID_ROWS {
+                break;
+            }
+            let take = batch.num_rows().min(SAMPLE_GRID_ROWS - rows.len());
+            rows.extend(batch_rows(&batch.slice(0, take)));
+        }
+        Self {
+            headers,
+            rows,
+            total_rows: batches.iter().map(|b| b.num_rows()).sum(),
+            tables,
+            elapsed_ms,
+        }
+    }
+}
+
+pub fn batch_rows(batch: &RecordBatch) -> Vec<Vec<String>> {
+    (0..batch.num_rows())
+        .map(|row| {
+            batch
+                .columns()
+                .iter()
+                .map(|array| array_value_to_string(array, row).unwrap_or_default())
+                .collect()
+        })
+        .collect()
+}
+
+pub fn batch_headers(batch: &RecordBatch) -> Vec<String> {
+    batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect()
+}
+
+// Estatísticas só da amostra carregada, não da tabela inteira
+pub fn column_stats(batch: &RecordBatch) -> Vec<ColumnStats> {
+    let schema = batch.schema();
+
+    schema
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .map(|(field, array)| {
+            let numeric = matches!(
+                field.data_type(),
+                DataType::Int64 | DataType::Float64 | DataType::Boolean
+            );
+
+            let mut distinct = HashSet::new();
+            let mut empty = 0;
+            let mut min: Option<(f64, String)> = None;
+            let mut max: Option<(f64, String)> = None;
+            let mut min_text: Option<String> = None;
+            let mut max_text: Option<String> = None;
+
+            for row in 0..array.len() {
+                if array.is_null(row) {
+                    continue;
+                }
+                let value = array_value_to_string(array, row).unwrap_or_default();
+                if value.trim().is_empty() {
+                    empty += 1;
+                    continue;
+                }
+
+                if numeric {
+                    let n = match value.as_str() {
+                        "true" => 1.0,
+                        "false" => 0.0,
+                        v => v.parse::<f64>().unwrap_or(0.0),
+                    };
+                    if min.as_ref().map_or(true, |(m, _)| n < *m) {
+                        min = Some((n, value.clone()));
+                    }
+                    if max.as_ref().map_or(true, |(m, _)| n > *m) {
+                        max = Some((n, value.clone()));
+                    }
+                } else {
+                    // Datas em AAAA-MM-DD ordenam corretamente como texto
+                    if min_text.as_ref().map_or(true, |m| value < *m) {
+                        min_text = Some(value.clone());
+                    }
+                    if max_text.as_ref().map_or(true, |m| value > *m) {
+                        max_text = Some(value.clone());
+                    }
+                }
+                distinct.insert(value);
+            }
+
+            ColumnStats {
+                nome: field.name().clone(),
+                tipo: field.data_type().to_string(),
+                nulos: array.null_count(),
+                vazios: empty,
+                distintos: distinct.len(),
+                minimo: min.map(|(_, v)| v).or(min_text).unwrap_or_default(),
+                maximo: max.map(|(_, v)| v).or(max_text).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
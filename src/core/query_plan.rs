@@ -0,0 +1,136 @@
+// Leitura do texto do EXPLAIN ANALYZE do DataFusion:
+//   SortExec: expr=[total@1 DESC], metrics=[output_rows=10, elapsed_compute=1.2ms, ...]
+// Cada nível de indentação (2 espaços) é um filho do operador acima.
+
+const INDENT: usize = 2;
+const HOT_SHARE: f64 = 0.25;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanNode {
+    pub depth: usize,
+    pub operator: String,
+    pub details: String,
+    pub output_rows: Option<u64>,
+    pub elapsed_ns: Option<u64>,
+    pub mem_bytes: Option<u64>,
+    pub metrics: Vec<(String, String)>,
+    // Fração do tempo de CPU somado de todos os operadores
+    pub time_share: f64,
+}
+
+impl PlanNode {
+    pub fn is_join_or_sort(&self) -> bool {
+        self.operator.contains("Join") || self.operator.contains("Sort")
+    }
+
+    pub fn is_hot(&self) -> bool {
+        self.time_share >= HOT_SHARE
+    }
+}
+
+pub fn parse_plan(text: &str) -> Vec<PlanNode> {
+    let mut nodes: Vec<PlanNode> = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(parse_line)
+        .collect();
+
+    let total: u64 = nodes.iter().filter_map(|n| n.elapsed_ns).sum();
+    if total > 0 {
+        for node in &mut nodes {
+            node.time_share = node.elapsed_ns.unwrap_or(0) as f64 / total as f64;
+        }
+    }
+    nodes
+}
+
+fn parse_line(line: &str) -> PlanNode {
+    let depth = (line.len() - line.trim_start().len()) / INDENT;
+    let line = line.trim();
+
+    let (head, metrics_text) = match line.rfind(", metrics=[") {
+        Some(idx) => (&line[..idx], line[idx + ", metrics=[".len()..].trim_end_matches(']')),
+        None => (line, ""),
+    };
+    let (operator, details) = match head.split_once(':') {
+        Some((op, rest)) => (op.trim().to_string(), rest.trim().to_string()),
+        None => (head.trim().to_string(), String::new()),
+    };
+
+    let metrics: Vec<(String, String)> = metrics_text
+        .split(", ")
+        .filter_map(|m| m.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let mut output_rows = None;
+    let mut elapsed_ns = None;
+    let mut mem_bytes = None;
+    for (name, value) in &metrics {
+        match name.as_str() {
+            "output_rows" => output_rows = value.parse().ok(),
+            "elapsed_compute" => elapsed_ns = parse_duration_ns(value),
+            n if n.contains("mem_used") => {
+                if let Some(bytes) = parse_bytes(value) {
+                    mem_bytes = Some(mem_bytes.unwrap_or(0) + bytes);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    PlanNode {
+        depth,
+        operator,
+        details,
+        output_rows,
+        elapsed_ns,
+        mem_bytes,
+        metrics,
+        time_share: 0.0,
+    }
+}
+
+// "1.234ms", "12.3µs", "2.5s", "120ns"
+fn parse_duration_ns(value: &str) -> Option<u64> {
+    let units = [("ns", 1.0), ("µs", 1e3), ("us", 1e3), ("ms", 1e6), ("s", 1e9)];
+    units.iter().find_map(|(suffix, factor)| {
+        value
+            .strip_suffix(suffix)
+            .and_then(|n| n.parse::<f64>().ok())
+            .map(|n| (n * factor) as u64)
+    })
+}
+
+// "1024", "1.5 KB", "2.0 MB"
+fn parse_bytes(value: &str) -> Option<u64> {
+    let units = [("KB", 1024.0), ("MB", 1024.0 * 1024.0), ("GB", 1024.0 * 1024.0 * 1024.0)];
+    let value = value.trim();
+    units
+        .iter()
+        .find_map(|(suffix, factor)| {
+            value
+                .strip_suffix(suffix)
+                .and_then(|n| n.trim().parse::<f64>().ok())
+                .map(|n| (n * factor) as u64)
+        })
+        .or_else(|| value.parse::<u64>().ok())
+}
+
+pub fn format_duration(ns: u64) -> String {
+    match ns {
+        n if n >= 1_000_000_000 => format!("{:.2} s", n as f64 / 1e9),
+        n if n >= 1_000_000 => format!("{:.1} ms", n as f64 / 1e6),
+        n if n >= 1_000 => format!("{:.1} µs", n as f64 / 1e3),
+        n => format!("{} ns", n),
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 * 1024 => format!("{:.2} GB", b as f64 / (1024.0 * 1024.0 * 1024.0)),
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
@@ -0,0 +1,726 @@
+use crate::core::engine::DataEngine;
+use crate::core::report_format::parse_report;
+use crate::core::sql_text::{Context, Edit, apply_edits, placeholder_edits, quote_literal};
+use crate::core::telemetry;
+use crate::core::udfs::format_brl;
+use chrono::{Local, NaiveDate};
+use datafusion::arrow::datatypes::{Schema as ArrowSchema, SchemaRef};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, atomic::AtomicBool};
+
+pub const REPORTS_DIR: &str = "relatorios";
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ReportParameter {
+    pub id: String,
+    pub nome: String,
+    pub tipo: String,
+    pub valor_padrao: String,
+    pub requerido: bool,
+    #[serde(default)]
+    pub extra: String,
+    // Lista, seleção múltipla e pesquisa: os valores são números e entram sem aspas.
+    // Sem isso entram como texto, do jeito que estão escritos ("01" continua "01").
+    #[serde(default)]
+    pub numerico: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ReportDefinition {
+    // Ver report_format: arquivos antigos são migrados ao carregar
+    #[serde(default)]
+    pub format_version: u32,
+    #[serde(default)]
+    pub descricao: String,
+    pub query_sql: String,
+    #[serde(default)]
+    pub parametros: Vec<ReportParameter>,
+    #[serde(default)]
+    pub metadados: ReportMetadata,
+}
+
+// Dados de manutenção do relatório; não interferem na execução
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ReportMetadata {
+    #[serde(default)]
+    pub autor: String,
+    #[serde(default)]
+    pub criado_em: String,
+    #[serde(default)]
+    pub modificado_em: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub departamento: String,
+    #[serde(default)]
+    pub notas: String,
+}
+
+// "Vendas, fiscal,  vendas" -> ["Vendas", "fiscal"]: sem vazias nem repetidas
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+// Preenche autor e datas ao salvar. A data de modificação só muda quando o conteúdo
+// mudou, para que salvar sem alterações não gere versão nova no histórico.
+pub fn stamp_metadata(definition: &mut ReportDefinition, previous: Option<&ReportDefinition>) {
+    let now = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let meta = &mut definition.metadados;
+    meta.tags = parse_tags(&meta.tags.join(","));
+    if meta.autor.trim().is_empty() {
+        meta.autor = current_user();
+    }
+    if meta.criado_em.is_empty() {
+        meta.criado_em = previous
+            .map(|p| p.metadados.criado_em.clone())
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| now.clone());
+    }
+
+    let unchanged = previous.is_some_and(|p| {
+        let mut same_stamp = definition.clone();
+        same_stamp.metadados.modificado_em = p.metadados.modificado_em.clone();
+        &same_stamp == p
+    });
+    definition.metadados.modificado_em = match previous {
+        Some(p) if unchanged && !p.metadados.modificado_em.is_empty() => {
+            p.metadados.modificado_em.clone()
+        }
+        _ => now,
+    };
+}
+
+fn current_user() -> String {
+    std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_default()
+}
+
+// Já no formato atual, qualquer que seja a versão gravada no arquivo
+pub fn load_report(path: &Path) -> Result<ReportDefinition, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Erro ao ler {}: {}", path.display(), e))?;
+    parse_report(&content)
+        .map(|loaded| loaded.definition)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+// Caminhos relativos à pasta de relatórios, com '/' como separador
+pub fn list_reports(dir: &Path) -> Vec<String> {
+    let mut found = Vec::new();
+    collect_reports(dir, dir, &mut found);
+    found.sort();
+    found
+}
+
+fn collect_reports(root: &Path, dir: &Path, found: &mut Vec<String>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                // .history e outras pastas internas não são relatórios
+                if !entry.file_name().to_string_lossy().starts_with('.') {
+                    collect_reports(root, &path, found);
+                }
+            } else if path.extension().map_or(false, |ext| ext == "json") {
+                if let Ok(relative) = path.strip_prefix(root) {
+                    found.push(relative.to_string_lossy().replace('\\', "/"));
+                }
+            }
+        }
+    }
+}
+
+// Impede que um caminho vindo de fora escape da pasta de relatórios
+pub fn resolve_report_path(reports_dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let candidate = Path::new(relative);
+    let escapes = candidate
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if relative.trim().is_empty() || escapes {
+        return Err(format!("Caminho de relatório inválido: {}", relative));
+    }
+
+    let path = reports_dir.join(candidate);
+    if !path.is_file() {
+        return Err(format!("Relatório não encontrado: {}", relative));
+    }
+    Ok(path)
+}
+
+// Linha de comando e API recusam ids que o relatório não declara, em vez de ignorá-los
+pub fn check_declared_parameters(
+    params: &[ReportParameter],
+    values: &HashMap<String, String>,
+) -> Result<(), String> {
+    let mut unknown: Vec<&String> = values
+        .keys()
+        .filter(|id| !params.iter().any(|p| &p.id == *id))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort();
+    Err(format!(
+        "Parâmetros não declarados no relatório: {:?}",
+        unknown
+    ))
+}
+
+// Mesma regra do ParamsModal: valor informado, senão o padrão (bool começa em "false")
+pub fn apply_parameters(
+    sql: &str,
+    params: &[ReportParameter],
+    values: &HashMap<String, String>,
+) -> Result<String, String> {
+    let mut resolved = HashMap::new();
+    for p in params {
+        let val = match values.get(&p.id) {
+            Some(v) => v.clone(),
+            None if p.tipo == "bool" => "false".to_string(),
+            None => p.valor_padrao.clone(),
+        };
+        if p.requerido && val.trim().is_empty() {
+            return Err(format!("O campo '{}' é obrigatório.", p.nome));
+        }
+        resolved.insert(p.id.as_str(), (p, val));
+    }
+
+    bind_parameters(sql, |id| resolved.get(id).map(|(p, val)| (*p, val.as_str())))
+}
+
+// Substitui cada [id] por um literal SQL do tipo declarado. Fora de aspas o valor
+// vira literal completo ('texto', 10, DATE '2026-01-31', (1, 2, 3)); dentro de
+// '...[id]...' entra apenas o texto normalizado, com as aspas escapadas.
+pub fn bind_parameters<'a, F>(sql: &str, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<(&'a ReportParameter, &'a str)>,
+{
+    parameter_edits(sql, lookup).map(|edits| apply_edits(sql, &edits))
+}
+
+// As trocas de `bind_parameters`, para quem precisa mapear posições de volta ao SQL salvo
+pub fn parameter_edits<'a, F>(sql: &str, lookup: F) -> Result<Vec<Edit>, String>
+where
+    F: Fn(&str) -> Option<(&'a ReportParameter, &'a str)>,
+{
+    placeholder_edits(sql, |placeholder| {
+        let found = match lookup(placeholder.id) {
+            Some((param, raw)) => Some((param, raw, None)),
+            None => range_bound(placeholder.id).and_then(|(base, bound)| {
+                lookup(base)
+                    .filter(|(param, _)| param.tipo == "periodo")
+                    .map(|(param, raw)| (param, raw, Some(bound)))
+            }),
+        };
+        let Some((param, raw, bound)) = found else {
+            return Ok(None);
+        };
+        let mut value = ParamValue::parse(param, raw)?;
+        if let Some(bound) = bound {
+            value = value.range_bound(bound);
+        }
+        Ok(Some(match placeholder.context {
+            Context::Code => value.to_sql_literal(),
+            Context::StringLiteral => value.to_text().replace('\'', "''"),
+        }))
+    })
+}
+
+// Valores de "periodo" (início;fim) e "multi" (a;b;c) vêm separados por ';'
+pub const MULTI_SEPARATOR: char = ';';
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RangeBound {
+    Inicio,
+    Fim,
+}
+
+// [periodo_inicio] e [periodo_fim] apontam para as pontas do parâmetro [periodo]
+pub fn range_bound(id: &str) -> Option<(&str, RangeBound)> {
+    id.strip_suffix("_inicio")
+        .map(|base| (base, RangeBound::Inicio))
+        .or_else(|| id.strip_suffix("_fim").map(|base| (base, RangeBound::Fim)))
+        .filter(|(base, _)| !base.is_empty())
+}
+
+// Opções de "lista" e "multi", uma por linha no campo extra: "valor=Rótulo" ou só "valor"
+pub fn list_options(extra: &str) -> Vec<(String, String)> {
+    extra
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once('=') {
+            Some((value, label)) => (value.trim().to_string(), label.trim().to_string()),
+            None => (line.to_string(), line.to_string()),
+        })
+        .collect()
+}
+
+pub enum ParamValue {
+    Null,
+    Text(String),
+    Int(i64),
+    Float(f64),
+    Date(NaiveDate),
+    Bool(bool),
+    DateRange(Option<NaiveDate>, Option<NaiveDate>),
+    List(Vec<ParamValue>),
+    // Em centavos, para não perder precisão
+    Money(i64),
+}
+
+impl ParamValue {
+    pub fn parse(param: &ReportParameter, raw: &str) -> Result<Self, String> {
+        let value = raw.trim();
+        let invalid = |tipo: &str| {
+            format!(
+                "Valor '{}' inválido para o campo '{}' ({}).",
+                raw, param.nome, tipo
+            )
+        };
+
+        if value.is_empty() && !matches!(param.tipo.as_str(), "string" | "periodo" | "multi") {
+            return Ok(Self::Null);
+        }
+
+        match param.tipo.as_str() {
+            "int" => value
+                .parse::<i64>()
+                .map(Self::Int)
+                .map_err(|_| invalid("inteiro")),
+            "float" => parse_decimal(value)
+                .map(Self::Float)
+                .ok_or_else(|| invalid("decimal")),
+            "data" => parse_date(value)
+                .map(Self::Date)
+                .ok_or_else(|| invalid("data")),
+            "bool" => match value.to_lowercase().as_str() {
+                "true" | "1" | "sim" | "s" => Ok(Self::Bool(true)),
+                "false" | "0" | "nao" | "não" | "n" => Ok(Self::Bool(false)),
+                _ => Err(invalid("sim/não")),
+            },
+            // Pesquisa devolve o código escolhido na lista
+            "pesquisa" => Self::option(param, value, raw),
+            "periodo" => {
+                if value.is_empty() {
+                    return Ok(Self::DateRange(None, None));
+                }
+                let (start, end) = value
+                    .split_once(MULTI_SEPARATOR)
+                    .map(|(a, b)| (a.trim(), b.trim()))
+                    .filter(|(a, b)| !a.is_empty() && !b.is_empty())
+                    .ok_or_else(|| {
+                        format!("Informe o início e o fim do período '{}'.", param.nome)
+                    })?;
+                let start = parse_date(start).ok_or_else(|| invalid("data inicial"))?;
+                let end = parse_date(end).ok_or_else(|| invalid("data final"))?;
+                if start > end {
+                    return Err(format!(
+                        "No período '{}', o início ({}) é depois do fim ({}).",
+                        param.nome,
+                        start.format("%d/%m/%Y"),
+                        end.format("%d/%m/%Y")
+                    ));
+                }
+                Ok(Self::DateRange(Some(start), Some(end)))
+            }
+            "lista" => {
+                check_options(param, &[value])?;
+                Self::option(param, value, value)
+            }
+            "multi" => {
+                let items: Vec<&str> = value
+                    .split(MULTI_SEPARATOR)
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .collect();
+                check_options(param, &items)?;
+                items
+                    .iter()
+                    .map(|item| Self::option(param, item, item))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Self::List)
+            }
+            "moeda" => parse_money(value)
+                .map(Self::Money)
+                .ok_or_else(|| invalid("valor em reais, ex: 1.234,56")),
+            _ => Ok(Self::Text(raw.to_string())),
+        }
+    }
+
+    // Opção de lista/seleção múltipla ou código da pesquisa: texto exatamente como
+    // escrito (`raw`), ou número se o parâmetro for marcado como numérico
+    fn option(param: &ReportParameter, value: &str, raw: &str) -> Result<Self, String> {
+        if !param.numerico {
+            return Ok(Self::Text(raw.to_string()));
+        }
+        value.parse::<i64>().map(Self::Int).map_err(|_| {
+            format!(
+                "Valor '{}' inválido para o campo '{}' (número).",
+                value, param.nome
+            )
+        })
+    }
+
+    // Uma das pontas de um período; os demais valores ficam como estão
+    pub fn range_bound(self, bound: RangeBound) -> Self {
+        match (self, bound) {
+            (Self::DateRange(start, _), RangeBound::Inicio) => start.map_or(Self::Null, Self::Date),
+            (Self::DateRange(_, end), RangeBound::Fim) => end.map_or(Self::Null, Self::Date),
+            (other, _) => other,
+        }
+    }
+
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            Self::Null => "NULL".to_string(),
+            Self::Text(v) => quote_literal(v),
+            Self::Int(v) => v.to_string(),
+            Self::Float(v) => format!("{:?}", v),
+            Self::Date(d) => format!("DATE '{}'", d.format("%Y-%m-%d")),
+            Self::Bool(v) => v.to_string(),
+            // Feito para "coluna BETWEEN [periodo]"
+            Self::DateRange(start, end) => format!(
+                "{} AND {}",
+                start.map_or(Self::Null, Self::Date).to_sql_literal(),
+                end.map_or(Self::Null, Self::Date).to_sql_literal()
+            ),
+            // Feito para "coluna IN [lista]"; vazia não casa com nada
+            Self::List(items) if items.is_empty() => "(NULL)".to_string(),
+            Self::List(items) => format!(
+                "({})",
+                items
+                    .iter()
+                    .map(|v| v.to_sql_literal())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Money(cents) => money_decimal(*cents),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        match self {
+            Self::Null => String::new(),
+            Self::Text(v) => v.clone(),
+            Self::Int(v) => v.to_string(),
+            Self::Float(v) => format!("{:?}", v),
+            Self::Date(d) => d.format("%Y-%m-%d").to_string(),
+            Self::Bool(v) => v.to_string(),
+            Self::DateRange(start, end) => format!(
+                "{} a {}",
+                start.map_or(Self::Null, Self::Date).to_text(),
+                end.map_or(Self::Null, Self::Date).to_text()
+            ),
+            Self::List(items) => items
+                .iter()
+                .map(|v| v.to_text())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::Money(cents) => format_brl(*cents as f64 / 100.0).replace("R$ ", ""),
+        }
+    }
+}
+
+// Com opções definidas no extra, o valor precisa ser uma delas
+fn check_options(param: &ReportParameter, values: &[&str]) -> Result<(), String> {
+    let options = list_options(&param.extra);
+    if options.is_empty() {
+        return Ok(());
+    }
+    match values
+        .iter()
+        .find(|v| !v.is_empty() && !options.iter().any(|(option, _)| option == *v))
+    {
+        Some(v) => Err(format!(
+            "'{}' não é uma das opções do campo '{}'.",
+            v, param.nome
+        )),
+        None => Ok(()),
+    }
+}
+
+// Digitação brasileira: "1.234,56", "1234,5", "R$ 10", "-R$ 10,00" e também "1234.56".
+// O ponto só é separador de milhar se separar grupos de três dígitos ("1.234" = mil
+// duzentos e trinta e quatro); "1234.567" é recusado. Mais de duas casas decimais
+// também.
+fn parse_money(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let value = value.trim_start().strip_prefix("R$").unwrap_or(value);
+    let (negative, value) = match value.trim_start().strip_prefix('-') {
+        Some(rest) if !negative => (true, rest),
+        _ => (negative, value),
+    };
+    let digits = value.replace(' ', "");
+
+    let (int_part, frac_part) = match digits.rsplit_once(',') {
+        Some((int_part, frac)) => (thousands(int_part)?, frac),
+        None => match thousands(&digits) {
+            Some(int_part) => (int_part, ""),
+            None => match digits.split_once('.') {
+                Some((int_part, frac)) if !frac.contains('.') => (int_part.to_string(), frac),
+                _ => return None,
+            },
+        },
+    };
+    let valid_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if int_part.is_empty()
+        || !valid_digits(&int_part)
+        || !valid_digits(frac_part)
+        || frac_part.len() > 2
+    {
+        return None;
+    }
+    let cents = int_part
+        .parse::<i64>()
+        .ok()?
+        .checked_mul(100)?
+        .checked_add(format!("{:0<2}", frac_part).parse::<i64>().ok()?)?;
+    Some(if negative { -cents } else { cents })
+}
+
+// Parte inteira sem os pontos de milhar: "1.234.567" -> "1234567". Sem ponto, volta
+// como está; com pontos fora dos grupos de três, None
+fn thousands(value: &str) -> Option<String> {
+    let mut groups = value.split('.');
+    let first = groups.next()?;
+    let rest: Vec<&str> = groups.collect();
+    if rest.is_empty() {
+        return Some(value.to_string());
+    }
+    let is_group =
+        |g: &str, min: usize| (min..=3).contains(&g.len()) && g.chars().all(|c| c.is_ascii_digit());
+    if !is_group(first, 1) || !rest.iter().all(|g| is_group(g, 3)) {
+        return None;
+    }
+    Some(format!("{}{}", first, rest.concat()))
+}
+
+fn money_decimal(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, (cents / 100).abs(), (cents % 100).abs())
+}
+
+// Aceita "1234.5", "1234,5" e "1.234,50"
+fn parse_decimal(value: &str) -> Option<f64> {
+    let normalized = if value.contains(',') {
+        value.replace('.', "").replace(',', ".")
+    } else {
+        value.to_string()
+    };
+    normalized.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%d/%m/%Y"))
+        .ok()
+}
+
+pub struct ReportRun {
+    pub engine: DataEngine,
+    pub columns: Vec<String>,
+    pub total_rows: usize,
+    // Do plano da consulta, inclusive quando não há linhas
+    pub schema: SchemaRef,
+}
+
+pub fn run_report(
+    final_sql: &str,
+    report_name: &str,
+    values: &HashMap<String, String>,
+) -> Result<ReportRun, String> {
+    let mut engine = DataEngine::new();
+    engine.parameters_hash = telemetry::parameters_hash(values);
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    engine.process_report_with_progress(final_sql, cancel, report_name, |_| {})?;
+    let (columns, total_rows) = engine.execute_user_sql(final_sql, report_name)?;
+    let schema = engine
+        .cached_results
+        .lock()
+        .unwrap()
+        .first()
+        .map(|b| b.schema())
+        .unwrap_or_else(|| Arc::new(ArrowSchema::empty()));
+
+    Ok(ReportRun {
+        engine,
+        columns,
+        total_rows,
+        schema,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(id: &str, tipo: &str) -> ReportParameter {
+        ReportParameter {
+            id: id.to_string(),
+            nome: id.to_string(),
+            tipo: tipo.to_string(),
+            valor_padrao: String::new(),
+            requerido: false,
+            extra: String::new(),
+            numerico: false,
+        }
+    }
+
+    fn bind(
+        sql: &str,
+        params: &[ReportParameter],
+        values: &[(&str, &str)],
+    ) -> Result<String, String> {
+        let values: HashMap<String, String> = values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        apply_parameters(sql, params, &values)
+    }
+
+    #[test]
+    fn quotes_are_escaped() {
+        let params = [param("nome", "string")];
+        assert_eq!(
+            bind(
+                "WHERE nome = [nome]",
+                &params,
+                &[("nome", "O'Brien'; DROP TABLE x; --")]
+            )
+            .unwrap(),
+            "WHERE nome = 'O''Brien''; DROP TABLE x; --'"
+        );
+        // Dentro de uma string, só o conteúdo entra, escapado no lugar
+        assert_eq!(
+            bind(
+                "WHERE nome LIKE '%[nome]%'",
+                &params,
+                &[("nome", "a'; DROP TABLE x; --")]
+            )
+            .unwrap(),
+            "WHERE nome LIKE '%a''; DROP TABLE x; --%'"
+        );
+    }
+
+    #[test]
+    fn comments_and_quoted_identifiers_are_left_alone() {
+        let params = [param("nome", "string")];
+        let sql = "SELECT \"[nome]\" FROM t -- [nome]\n/* [nome] */ WHERE x = [nome]";
+        assert_eq!(
+            bind(sql, &params, &[("nome", "a")]).unwrap(),
+            "SELECT \"[nome]\" FROM t -- [nome]\n/* [nome] */ WHERE x = 'a'"
+        );
+        // Só [id] declarado é trocado
+        assert_eq!(
+            bind("SELECT [outro]", &params, &[]).unwrap(),
+            "SELECT [outro]"
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let params = [param("n", "int"), param("f", "float"), param("d", "data")];
+        let sql = "SELECT [n], [f], [d]";
+        assert!(bind(sql, &params, &[("n", "1; DROP TABLE x")]).is_err());
+        assert!(bind(sql, &params, &[("f", "1,2,3")]).is_err());
+        assert!(bind(sql, &params, &[("d", "2026-02-30")]).is_err());
+        assert!(bind(sql, &params, &[("d", "ontem'")]).is_err());
+        assert_eq!(
+            bind(
+                sql,
+                &params,
+                &[("n", "10"), ("f", "1.234,5"), ("d", "31/01/2026")]
+            )
+            .unwrap(),
+            "SELECT 10, 1234.5, DATE '2026-01-31'"
+        );
+    }
+
+    #[test]
+    fn periodo() {
+        let params = [param("periodo", "periodo")];
+        let values = [("periodo", "2026-01-01;2026-01-31")];
+        assert_eq!(
+            bind("WHERE emissao BETWEEN [periodo]", &params, &values).unwrap(),
+            "WHERE emissao BETWEEN DATE '2026-01-01' AND DATE '2026-01-31'"
+        );
+        assert_eq!(
+            bind(
+                "WHERE emissao >= [periodo_inicio] AND emissao <= [periodo_fim]",
+                &params,
+                &values
+            )
+            .unwrap(),
+            "WHERE emissao >= DATE '2026-01-01' AND emissao <= DATE '2026-01-31'"
+        );
+        assert!(
+            bind(
+                "SELECT [periodo]",
+                &params,
+                &[("periodo", "2026-02-01;2026-01-01")]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn multi() {
+        let mut lojas = param("lojas", "multi");
+        lojas.extra = "01=Matriz\n02=Filial\nA'B".to_string();
+        let sql = "WHERE loja IN [lojas]";
+        assert_eq!(
+            bind(sql, &[lojas.clone()], &[("lojas", "01;02")]).unwrap(),
+            "WHERE loja IN ('01', '02')"
+        );
+        assert_eq!(
+            bind(sql, &[lojas.clone()], &[("lojas", "A'B")]).unwrap(),
+            "WHERE loja IN ('A''B')"
+        );
+        assert_eq!(
+            bind(sql, &[lojas.clone()], &[("lojas", "")]).unwrap(),
+            "WHERE loja IN (NULL)"
+        );
+        assert!(bind(sql, &[lojas.clone()], &[("lojas", "03")]).is_err());
+
+        lojas.extra = "1\n2\n3".to_string();
+        lojas.numerico = true;
+        assert_eq!(
+            bind(sql, &[lojas], &[("lojas", "1;3")]).unwrap(),
+            "WHERE loja IN (1, 3)"
+        );
+    }
+
+    #[test]
+    fn money() {
+        assert_eq!(parse_money("1.234,56"), Some(123456));
+        assert_eq!(parse_money("1234,5"), Some(123450));
+        assert_eq!(parse_money("R$ 10"), Some(1000));
+        assert_eq!(parse_money("1234.56"), Some(123456));
+        assert_eq!(parse_money("1.234"), Some(123400));
+        assert_eq!(parse_money("1.234.567"), Some(123456700));
+        assert_eq!(parse_money("-R$ 10,00"), Some(-1000));
+        assert_eq!(parse_money("R$ -10,00"), Some(-1000));
+        assert_eq!(parse_money("1234.567"), None);
+        assert_eq!(parse_money("12.34.567"), None);
+        assert_eq!(parse_money("1234.567,00"), None);
+        assert_eq!(parse_money("10,123"), None);
+        assert_eq!(parse_money("92233720368547758,99"), None);
+        assert_eq!(parse_money("--10"), None);
+    }
+}
@@ -0,0 +1,355 @@
+use crate::core::history::{keep_original, record_version};
+use crate::core::param_lint::{Severity, lint_parameters};
+use crate::core::report::{ReportDefinition, list_options, list_reports};
+use crate::core::sql_analysis::analyze_saved;
+use serde_json::{Map, Value, json};
+use std::fs;
+use std::path::Path;
+
+// Versão gravada em todo relatório salvo. Arquivos sem `format_version` são da
+// versão 1 (descricao, query_sql e parametros).
+pub const CURRENT_FORMAT_VERSION: u32 = 3;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+// MIGRATIONS[i] leva um relatório da versão i + 1 para a i + 2. Ao mudar o formato,
+// acrescente a migração aqui e suba CURRENT_FORMAT_VERSION; nunca altere as antigas.
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2, migrate_v2_to_v3];
+
+// Relatório lido do disco e já migrado para a versão atual
+pub struct LoadedReport {
+    pub definition: ReportDefinition,
+    pub original_version: u32,
+}
+
+impl LoadedReport {
+    pub fn was_migrated(&self) -> bool {
+        self.original_version < CURRENT_FORMAT_VERSION
+    }
+}
+
+pub fn parse_report(content: &str) -> Result<LoadedReport, String> {
+    let value: Value =
+        serde_json::from_str(content).map_err(|e| format!("JSON inválido: {}", e))?;
+    let Value::Object(mut object) = value else {
+        return Err("JSON inválido: o relatório deve ser um objeto { ... }".to_string());
+    };
+
+    let original_version = match object.get("format_version") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .filter(|v| *v >= 1)
+            .ok_or(format!("format_version inválido: {}", v))? as u32,
+    };
+    if original_version > CURRENT_FORMAT_VERSION {
+        return Err(format!(
+            "Relatório no formato {}, criado por uma versão mais nova do NewReport (esta lê até o formato {}). Atualize o programa.",
+            original_version, CURRENT_FORMAT_VERSION
+        ));
+    }
+
+    for (i, migration) in MIGRATIONS
+        .iter()
+        .enumerate()
+        .skip(original_version as usize - 1)
+    {
+        migration(&mut object).map_err(|e| {
+            format!(
+                "Erro ao migrar do formato {} para o {}: {}",
+                i + 1,
+                i + 2,
+                e
+            )
+        })?;
+        object.insert("format_version".to_string(), json!(i + 2));
+    }
+
+    let definition: ReportDefinition =
+        serde_json::from_value(Value::Object(object)).map_err(|e| format!("Erro JSON: {}", e))?;
+    Ok(LoadedReport {
+        definition,
+        original_version,
+    })
+}
+
+pub fn to_json(definition: &ReportDefinition) -> Result<String, String> {
+    let mut current = definition.clone();
+    current.format_version = CURRENT_FORMAT_VERSION;
+    serde_json::to_string_pretty(&current).map_err(|e| e.to_string())
+}
+
+// Gravação atômica: escreve num .tmp e renomeia por cima
+pub fn save_report(path: &Path, definition: &ReportDefinition) -> Result<(), String> {
+    let json = to_json(definition)?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &json).map_err(|e| format!("Erro ao gravar {}: {}", path.display(), e))?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Erro ao gravar {}: {}", path.display(), e)
+    })
+}
+
+// v1 -> v2: campos opcionais dos parâmetros preenchidos e bloco de metadados
+fn migrate_v1_to_v2(report: &mut Map<String, Value>) -> Result<(), String> {
+    match report.get("query_sql") {
+        Some(Value::String(_)) => {}
+        Some(_) => return Err("query_sql deve ser texto".to_string()),
+        None => return Err("campo query_sql ausente".to_string()),
+    }
+    if !matches!(report.get("descricao"), Some(Value::String(_))) {
+        report.insert("descricao".to_string(), json!(""));
+    }
+
+    let params = report.entry("parametros").or_insert_with(|| json!([]));
+    if params.is_null() {
+        *params = json!([]);
+    }
+    let Value::Array(params) = params else {
+        return Err("parametros deve ser uma lista".to_string());
+    };
+    for (i, param) in params.iter_mut().enumerate() {
+        let Value::Object(param) = param else {
+            return Err(format!("parâmetro {} não é um objeto", i + 1));
+        };
+        let id = param
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        param.entry("nome").or_insert_with(|| json!(id));
+        param.entry("tipo").or_insert_with(|| json!("string"));
+        param.entry("valor_padrao").or_insert_with(|| json!(""));
+        param.entry("requerido").or_insert_with(|| json!(false));
+        param.entry("extra").or_insert_with(|| json!(""));
+        // Valores padrão numéricos ou booleanos gravados sem aspas
+        if let Some(default) = param.get_mut("valor_padrao") {
+            if !default.is_string() {
+                *default = json!(if default.is_null() {
+                    String::new()
+                } else {
+                    default.to_string()
+                });
+            }
+        }
+    }
+
+    match report.entry("metadados").or_insert_with(|| json!({})) {
+        Value::Object(meta) => {
+            // "vendas, mensal" -> ["vendas", "mensal"]
+            if let Some(Value::String(tags)) = meta.get("tags") {
+                let list: Vec<String> = tags
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                meta.insert("tags".to_string(), json!(list));
+            }
+        }
+        other => *other = json!({}),
+    }
+    Ok(())
+}
+
+// v2 -> v3: `numerico` nos parâmetros. Listas só com números inteiros sem zero à
+// esquerda continuam entrando sem aspas, como antes; as demais passam a entrar como texto.
+fn migrate_v2_to_v3(report: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(Value::Array(params)) = report.get_mut("parametros") else {
+        return Ok(());
+    };
+    for param in params.iter_mut() {
+        let Value::Object(param) = param else {
+            continue;
+        };
+        let tipo = param
+            .get("tipo")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let extra = param
+            .get("extra")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let options = list_options(extra);
+        let numerico = matches!(tipo, "lista" | "multi")
+            && !options.is_empty()
+            && options
+                .iter()
+                .all(|(value, _)| value.parse::<i64>().is_ok_and(|n| n.to_string() == *value));
+        param.entry("numerico").or_insert_with(|| json!(numerico));
+    }
+    Ok(())
+}
+
+pub struct ReportCheck {
+    // Relativo à pasta de relatórios
+    pub caminho: String,
+    pub original_version: Option<u32>,
+    pub issues: Vec<(Severity, String)>,
+}
+
+impl ReportCheck {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|(s, _)| *s == Severity::Error)
+    }
+}
+
+// Confere todos os relatórios da pasta: formato, migração, parâmetros e sintaxe do SQL
+pub fn validate_all(reports_dir: &Path) -> Vec<ReportCheck> {
+    list_reports(reports_dir)
+        .into_iter()
+        .map(|relative| validate_report(reports_dir, &relative))
+        .collect()
+}
+
+pub fn validate_report(reports_dir: &Path, relative: &str) -> ReportCheck {
+    let mut check = ReportCheck {
+        caminho: relative.to_string(),
+        original_version: None,
+        issues: Vec::new(),
+    };
+    let path = reports_dir.join(relative);
+    let content = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) => {
+            check
+                .issues
+                .push((Severity::Error, format!("Erro ao ler: {}", e)));
+            return check;
+        }
+    };
+    let loaded = match parse_report(&content) {
+        Ok(l) => l,
+        Err(e) => {
+            check.issues.push((Severity::Error, e));
+            return check;
+        }
+    };
+    check.original_version = Some(loaded.original_version);
+    if loaded.was_migrated() {
+        check.issues.push((
+            Severity::Warning,
+            format!(
+                "Formato {} (atual: {}). Será atualizado ao salvar no editor ou com --migrar.",
+                loaded.original_version, CURRENT_FORMAT_VERSION
+            ),
+        ));
+    }
+
+    let def = &loaded.definition;
+    if def.query_sql.trim().is_empty() {
+        check
+            .issues
+            .push((Severity::Error, "O SQL está vazio.".to_string()));
+        return check;
+    }
+    for issue in lint_parameters(&def.query_sql, &def.parametros) {
+        check.issues.push((issue.severity, issue.message));
+    }
+    if let Err(e) = analyze_saved(&def.query_sql) {
+        check
+            .issues
+            .push((Severity::Error, format!("Erro de sintaxe SQL: {}", e)));
+    }
+    check
+}
+
+// Regrava no formato atual os relatórios de versões antigas, registrando cada um no
+// histórico antes e guardando lá o arquivo original. Devolve quantos mudaram.
+pub fn migrate_all(reports_dir: &Path) -> Result<usize, String> {
+    let mut migrated = 0;
+    for relative in list_reports(reports_dir) {
+        let path = reports_dir.join(&relative);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(loaded) = parse_report(&content) else {
+            continue;
+        };
+        if loaded.was_migrated() {
+            // O arquivo como estava e a versão migrada entram no histórico antes de
+            // ele ser reescrito
+            keep_original(&path, &content, loaded.original_version)?;
+            let comment = format!("Migração para o formato v{}", CURRENT_FORMAT_VERSION);
+            record_version(&path, None, &loaded.definition, &comment)?;
+            save_report(&path, &loaded.definition)?;
+            migrated += 1;
+        }
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::{history_dir, list_versions};
+
+    const V1: &str = r#"{
+        "descricao": "Vendas",
+        "query_sql": "SELECT * FROM vendas WHERE loja IN [lojas] AND dias > [dias]",
+        "parametros": [
+            { "id": "lojas", "tipo": "multi", "extra": "1=Matriz\n2=Filial" },
+            { "id": "dias", "tipo": "int", "valor_padrao": 30 }
+        ],
+        "metadados": { "tags": "a, b" }
+    }"#;
+
+    #[test]
+    fn v1_is_migrated_to_current() {
+        let loaded = parse_report(V1).unwrap();
+        assert_eq!(loaded.original_version, 1);
+        assert!(loaded.was_migrated());
+
+        let def = loaded.definition;
+        assert_eq!(def.format_version, CURRENT_FORMAT_VERSION);
+        assert_eq!(def.metadados.tags, vec!["a", "b"]);
+        let lojas = &def.parametros[0];
+        assert_eq!(lojas.nome, "lojas");
+        assert!(lojas.numerico);
+        let dias = &def.parametros[1];
+        assert_eq!(dias.valor_padrao, "30");
+        assert!(!dias.numerico);
+
+        let saved = parse_report(&to_json(&def).unwrap()).unwrap();
+        assert!(!saved.was_migrated());
+        assert_eq!(saved.definition, def);
+    }
+
+    #[test]
+    fn invalid_versions_are_rejected() {
+        let newer = format!(
+            r#"{{ "format_version": {}, "query_sql": "SELECT 1" }}"#,
+            CURRENT_FORMAT_VERSION + 1
+        );
+        assert!(parse_report(&newer).is_err_and(|e| e.contains("mais nova")));
+
+        let zero = r#"{ "format_version": 0, "query_sql": "SELECT 1" }"#;
+        assert!(parse_report(zero).is_err_and(|e| e.contains("format_version inválido")));
+    }
+
+    #[test]
+    fn migrate_all_keeps_the_original_file() {
+        let dir = std::env::temp_dir().join(format!("newreport-migrar-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vendas.json");
+        fs::write(&path, V1).unwrap();
+
+        assert_eq!(migrate_all(&dir).unwrap(), 1);
+        let migrated = parse_report(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(!migrated.was_migrated());
+        assert_eq!(list_versions(&path).len(), 1);
+
+        let originals: Vec<String> = fs::read_dir(history_dir(&path))
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with("-original-v1.json"))
+            .collect();
+        assert_eq!(originals.len(), 1);
+        let original = fs::read_to_string(history_dir(&path).join(&originals[0])).unwrap();
+        assert_eq!(original, V1);
+
+        assert_eq!(migrate_all(&dir).unwrap(), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
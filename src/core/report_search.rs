@@ -0,0 +1,305 @@
+use crate::core::report::load_report;
+use crate::core::sql_analysis::referenced_tables;
+use crate::core::udfs::unaccent;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+// Caracteres mostrados antes e depois do termo no trecho de cada ocorrência
+const SNIPPET_CONTEXT: usize = 30;
+const MAX_MATCHES_PER_REPORT: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SearchField {
+    Nome,
+    Tag,
+    Tabela,
+    Parametro,
+    Descricao,
+    Sql,
+}
+
+impl SearchField {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchField::Nome => "Nome",
+            SearchField::Tag => "Tag",
+            SearchField::Tabela => "Tabela",
+            SearchField::Parametro => "Parâmetro",
+            SearchField::Descricao => "Descrição",
+            SearchField::Sql => "SQL",
+        }
+    }
+
+    fn weight(&self) -> u32 {
+        match self {
+            SearchField::Nome => 100,
+            SearchField::Tag => 60,
+            SearchField::Tabela => 60,
+            SearchField::Parametro => 40,
+            SearchField::Descricao => 30,
+            SearchField::Sql => 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct IndexedField {
+    campo: SearchField,
+    texto: String,
+    // Sem acentos e em minúsculas, um caractere para cada caractere de `texto`
+    dobrado: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedReport {
+    pub path: String,
+    pub nome: String,
+    pub tags: Vec<String>,
+    fields: Vec<IndexedField>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    pub campo: SearchField,
+    pub trecho: String,
+    // Só para ocorrências no SQL
+    pub linha: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    pub path: String,
+    pub nome: String,
+    pub score: u32,
+    pub matches: Vec<SearchMatch>,
+}
+
+// Lê todos os relatórios da pasta. Caminhos no mesmo formato da árvore da Home.
+pub fn build_index(dir: &Path) -> Vec<IndexedReport> {
+    let mut index = Vec::new();
+    collect(dir, &mut index);
+    index
+}
+
+fn collect(dir: &Path, index: &mut Vec<IndexedReport>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            collect(&path, index);
+        } else if path.extension().map_or(false, |ext| ext == "json") {
+            index.push(index_report(&path));
+        }
+    }
+}
+
+// Um JSON inválido continua pesquisável pelo nome
+fn index_report(path: &Path) -> IndexedReport {
+    let nome = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let mut fields = vec![field(SearchField::Nome, &nome)];
+    let mut tags = Vec::new();
+
+    let definition = load_report(path).ok();
+    if let Some(def) = definition {
+        for tag in &def.metadados.tags {
+            fields.push(field(SearchField::Tag, tag));
+        }
+        tags = def.metadados.tags;
+        if !def.descricao.trim().is_empty() {
+            fields.push(field(SearchField::Descricao, &def.descricao));
+        }
+        for table in report_tables(&def.query_sql) {
+            fields.push(field(SearchField::Tabela, &table));
+        }
+        for p in &def.parametros {
+            fields.push(field(
+                SearchField::Parametro,
+                &format!("{} [{}]", p.nome, p.id),
+            ));
+        }
+        fields.push(field(SearchField::Sql, &def.query_sql));
+    }
+
+    IndexedReport {
+        path: path.to_string_lossy().to_string(),
+        nome,
+        tags,
+        fields,
+    }
+}
+
+// Tabelas do [SYNC: ...] (inclusive arquivos externos) e as que a query usa
+fn report_tables(sql: &str) -> BTreeSet<String> {
+    let mut tables: BTreeSet<String> = referenced_tables(sql).into_iter().collect();
+    let re_sync = Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").unwrap();
+    let re_table = Regex::new(r"([a-zA-Z0-9_]+)\s*\(").unwrap();
+    if let Some(content) = re_sync.captures(sql).and_then(|caps| caps.get(1)) {
+        for cap in re_table.captures_iter(content.as_str()) {
+            tables.insert(cap[1].to_lowercase());
+        }
+    }
+    tables
+}
+
+// Tags usadas em algum relatório, sem repetir maiúsculas/minúsculas, em ordem alfabética
+pub fn all_tags(index: &[IndexedReport]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in index.iter().flat_map(|r| r.tags.iter()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.clone());
+        }
+    }
+    tags.sort_by_key(|t| fold(t));
+    tags
+}
+
+pub fn has_tag(report: &IndexedReport, tag: &str) -> bool {
+    report.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+}
+
+fn field(campo: SearchField, texto: &str) -> IndexedField {
+    IndexedField {
+        campo,
+        texto: texto.to_string(),
+        dobrado: fold(texto),
+    }
+}
+
+// Sem acentos e em minúsculas, preservando um caractere por caractere
+fn fold(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            let plain = unaccent(c.encode_utf8(&mut [0; 4]))
+                .chars()
+                .next()
+                .unwrap_or(c);
+            plain.to_lowercase().next().unwrap_or(plain)
+        })
+        .collect()
+}
+
+// Todos os termos precisam aparecer em algum campo. A pontuação soma, por termo,
+// o peso do melhor campo (Nome > Tabela > Parâmetro > Descrição > SQL), com bônus
+// quando o termo começa uma palavra e quando o nome bate com a pesquisa inteira.
+pub fn search(index: &[IndexedReport], query: &str) -> Vec<SearchHit> {
+    let folded_query = fold(query.trim());
+    let terms: Vec<&str> = folded_query.split_whitespace().collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<SearchHit> = index
+        .iter()
+        .filter_map(|report| score_report(report, &terms, &folded_query))
+        .collect();
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.nome.cmp(&b.nome)));
+    hits
+}
+
+fn score_report(report: &IndexedReport, terms: &[&str], folded_query: &str) -> Option<SearchHit> {
+    let mut score = 0;
+    let mut found: Vec<(usize, usize, usize)> = Vec::new();
+
+    for term in terms {
+        let mut best = 0;
+        for (i, f) in report.fields.iter().enumerate() {
+            let Some(pos) = f.dobrado.find(term) else {
+                continue;
+            };
+            let at_word_start = f.dobrado[..pos]
+                .chars()
+                .next_back()
+                .map_or(true, |c| !c.is_alphanumeric() && c != '_');
+            let weight = f.campo.weight()
+                + if at_word_start {
+                    f.campo.weight() / 2
+                } else {
+                    0
+                };
+            best = best.max(weight);
+            if !found.iter().any(|(field, _, _)| *field == i) {
+                found.push((i, pos, term.len()));
+            }
+        }
+        if best == 0 {
+            return None;
+        }
+        score += best;
+    }
+
+    let folded_name = fold(&report.nome);
+    if folded_name == folded_query {
+        score += 200;
+    } else if folded_name.starts_with(folded_query) {
+        score += 50;
+    }
+
+    found.sort_by_key(|(i, _, _)| (report.fields[*i].campo, *i));
+    let matches = found
+        .into_iter()
+        .take(MAX_MATCHES_PER_REPORT)
+        .map(|(i, pos, len)| snippet(&report.fields[i], pos, len))
+        .collect();
+
+    Some(SearchHit {
+        path: report.path.clone(),
+        nome: report.nome.clone(),
+        score,
+        matches,
+    })
+}
+
+// `pos` e `len` são em bytes do texto dobrado; como a dobra mantém um caractere por
+// caractere, a posição em caracteres vale para o texto original
+fn snippet(f: &IndexedField, pos: usize, len: usize) -> SearchMatch {
+    let start_char = f.dobrado[..pos].chars().count();
+    let len_chars = f.dobrado[pos..pos + len].chars().count();
+    let chars: Vec<char> = f.texto.chars().collect();
+
+    // No SQL o trecho fica restrito à linha da ocorrência
+    let (line_start, line_end, linha) = if f.campo == SearchField::Sql {
+        let line_start = chars[..start_char]
+            .iter()
+            .rposition(|c| *c == '\n')
+            .map_or(0, |p| p + 1);
+        let line_end = chars[start_char..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map_or(chars.len(), |p| start_char + p);
+        let linha = chars[..start_char].iter().filter(|c| **c == '\n').count() + 1;
+        (line_start, line_end, Some(linha))
+    } else {
+        (0, chars.len(), None)
+    };
+
+    let from = start_char.saturating_sub(SNIPPET_CONTEXT).max(line_start);
+    let to = (start_char + len_chars + SNIPPET_CONTEXT).min(line_end);
+    let mut trecho: String = chars[from..to]
+        .iter()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if from > line_start {
+        trecho = format!("…{}", trecho);
+    }
+    if to < line_end {
+        trecho.push('…');
+    }
+
+    SearchMatch {
+        campo: f.campo,
+        trecho,
+        linha,
+    }
+}
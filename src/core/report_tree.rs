@@ -0,0 +1,163 @@
+use crate::core::history::{HISTORY_DIR, move_history};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Pastas recolhidas na árvore da Home, guardadas entre uma sessão e outra
+const TREE_STATE_FILE: &str = ".arvore.json";
+const INVALID_NAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+#[derive(Deserialize, Serialize, Default)]
+struct TreeState {
+    #[serde(default)]
+    recolhidas: BTreeSet<String>,
+}
+
+// Chave de uma pasta no estado da árvore: caminho com '/' como separador
+pub fn tree_key(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+pub fn load_collapsed(reports_dir: &Path) -> BTreeSet<String> {
+    fs::read_to_string(reports_dir.join(TREE_STATE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<TreeState>(&content).ok())
+        .map(|state| state.recolhidas)
+        .unwrap_or_default()
+}
+
+pub fn save_collapsed(reports_dir: &Path, collapsed: &BTreeSet<String>) -> Result<(), String> {
+    let state = TreeState {
+        recolhidas: collapsed.clone(),
+    };
+    let json = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    fs::write(reports_dir.join(TREE_STATE_FILE), json)
+        .map_err(|e| format!("Erro ao gravar o estado da árvore: {}", e))
+}
+
+pub fn validate_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Informe um nome.".to_string());
+    }
+    if name.starts_with('.') || name.contains(INVALID_NAME_CHARS) {
+        return Err(format!(
+            "Nome inválido: '{}'. Não use ponto no início nem os caracteres / \\ : * ? \" < > |",
+            name
+        ));
+    }
+    Ok(name)
+}
+
+pub fn create_folder(reports_dir: &Path, parent: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = validate_name(name)?;
+    ensure_inside(reports_dir, parent)?;
+    let target = parent.join(name);
+    if target.exists() {
+        return Err(format!("Já existe '{}' em {}.", name, parent.display()));
+    }
+    fs::create_dir(&target).map_err(|e| format!("Erro ao criar a pasta {}: {}", name, e))?;
+    Ok(target)
+}
+
+// O histórico das pastas (.history) fica dentro delas e acompanha a renomeação
+pub fn rename_folder(reports_dir: &Path, folder: &Path, new_name: &str) -> Result<PathBuf, String> {
+    let new_name = validate_name(new_name)?;
+    if ensure_inside(reports_dir, folder)?.as_os_str().is_empty() {
+        return Err("A pasta raiz de relatórios não pode ser renomeada.".to_string());
+    }
+
+    let target = folder.parent().unwrap_or(reports_dir).join(new_name);
+    let same_folder = folder
+        .file_name()
+        .is_some_and(|old| old.to_string_lossy().eq_ignore_ascii_case(new_name));
+    if target.exists() && !same_folder {
+        return Err(format!("Já existe '{}' nessa pasta.", new_name));
+    }
+    fs::rename(folder, &target).map_err(|e| format!("Erro ao renomear a pasta: {}", e))?;
+    Ok(target)
+}
+
+// Só remove pastas sem relatórios nem subpastas; um .history vazio não conta
+pub fn delete_empty_folder(reports_dir: &Path, folder: &Path) -> Result<(), String> {
+    if ensure_inside(reports_dir, folder)?.as_os_str().is_empty() {
+        return Err("A pasta raiz de relatórios não pode ser excluída.".to_string());
+    }
+
+    let entries: Vec<PathBuf> = fs::read_dir(folder)
+        .map_err(|e| format!("Erro ao ler {}: {}", folder.display(), e))?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    let leftover = entries.iter().any(|path| {
+        let is_empty_history = path.file_name().is_some_and(|n| n == HISTORY_DIR)
+            && fs::read_dir(path).map_or(false, |mut d| d.next().is_none());
+        !is_empty_history
+    });
+    if leftover {
+        return Err(
+            "A pasta não está vazia. Mova ou exclua os relatórios e subpastas antes.".to_string(),
+        );
+    }
+
+    for path in entries {
+        let _ = fs::remove_dir(path);
+    }
+    fs::remove_dir(folder).map_err(|e| format!("Erro ao excluir a pasta: {}", e))
+}
+
+// Move o relatório (e o histórico) para outra pasta. Se já houver um arquivo com o
+// mesmo nome no destino, o relatório entra como "nome (2).json", sem sobrescrever.
+pub fn move_report(reports_dir: &Path, report: &Path, folder: &Path) -> Result<PathBuf, String> {
+    let relative_report = ensure_inside(reports_dir, report)?;
+    let relative_folder = ensure_inside(reports_dir, folder)?;
+    if !report.is_file() {
+        return Err(format!("Relatório não encontrado: {}", report.display()));
+    }
+    if !folder.is_dir() {
+        return Err(format!("Pasta não encontrada: {}", folder.display()));
+    }
+    if relative_report.parent() == Some(relative_folder.as_path()) {
+        return Ok(report.to_path_buf());
+    }
+
+    let file_name = report.file_name().unwrap_or_default();
+    let target = free_name(&folder.join(file_name));
+    fs::rename(report, &target).map_err(|e| format!("Erro ao mover o relatório: {}", e))?;
+    move_history(report, &target)?;
+    Ok(target)
+}
+
+fn free_name(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let parent = path.parent().unwrap_or(Path::new("."));
+    (2..)
+        .map(|n| parent.join(format!("{} ({}).json", stem, n)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+// Recusa caminhos fora da pasta de relatórios ou dentro de pastas ocultas. Devolve o
+// caminho relativo (vazio para a própria pasta de relatórios).
+fn ensure_inside(reports_dir: &Path, path: &Path) -> Result<PathBuf, String> {
+    let root = fs::canonicalize(reports_dir).map_err(|e| e.to_string())?;
+    let full = fs::canonicalize(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let relative = full
+        .strip_prefix(&root)
+        .map_err(|_| format!("Caminho fora da pasta de relatórios: {}", path.display()))?;
+    let hidden = relative
+        .components()
+        .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    if hidden {
+        return Err(format!("Caminho inválido: {}", path.display()));
+    }
+    Ok(relative.to_path_buf())
+}
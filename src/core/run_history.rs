@@ -0,0 +1,200 @@
+use crate::core::trash;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+pub const RUN_HISTORY_FILE: &str = "execucoes.jsonl";
+pub const FAVORITES_FILE: &str = "favoritos.json";
+// Ao passar do limite, o arquivo é reescrito só com as execuções mais novas
+const MAX_RUNS: usize = 2000;
+const KEEP_RUNS: usize = 1000;
+
+// Uma execução de relatório pela tela inicial
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReportRunEntry {
+    pub relatorio: String,
+    pub inicio: String,
+    #[serde(default)]
+    pub parametros: BTreeMap<String, String>,
+    pub duracao_ms: u128,
+    pub linhas: usize,
+    pub sucesso: bool,
+    #[serde(default)]
+    pub erro: String,
+}
+
+impl ReportRunEntry {
+    pub fn start(report_path: &str, values: &HashMap<String, String>) -> Self {
+        Self {
+            relatorio: report_path.to_string(),
+            inicio: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            parametros: values.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            duracao_ms: 0,
+            linhas: 0,
+            sucesso: true,
+            erro: String::new(),
+        }
+    }
+}
+
+pub fn record_run(entry: &ReportRunEntry) {
+    let Ok(line) = serde_json::to_string(entry) else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(RUN_HISTORY_FILE)
+    {
+        let _ = writeln!(file, "{}", line);
+    }
+    trim_history();
+}
+
+// Mais recente primeiro
+pub fn read_runs() -> Vec<ReportRunEntry> {
+    let content = fs::read_to_string(RUN_HISTORY_FILE).unwrap_or_default();
+    let mut runs: Vec<ReportRunEntry> = content
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    runs.reverse();
+    runs
+}
+
+// Última execução bem-sucedida de cada relatório que ainda existe, mais recente primeiro
+pub fn recent_reports(limit: usize) -> Vec<ReportRunEntry> {
+    let mut seen = HashSet::new();
+    read_runs()
+        .into_iter()
+        .filter(|run| run.sucesso && report_available(&run.relatorio))
+        .filter(|run| seen.insert(normalize(&run.relatorio)))
+        .take(limit)
+        .collect()
+}
+
+// Valores usados na última execução bem-sucedida do relatório
+pub fn last_parameters(report_path: &str) -> Option<HashMap<String, String>> {
+    let key = normalize(report_path);
+    read_runs()
+        .into_iter()
+        .find(|run| run.sucesso && normalize(&run.relatorio) == key)
+        .map(|run| run.parametros.into_iter().collect())
+}
+
+// O arquivo existe e não está na lixeira
+pub fn report_available(report_path: &str) -> bool {
+    Path::new(report_path).is_file() && !trash::in_trash(report_path)
+}
+
+pub fn load_favorites() -> Vec<String> {
+    fs::read_to_string(FAVORITES_FILE)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn is_favorite(favorites: &[String], report_path: &str) -> bool {
+    let key = normalize(report_path);
+    favorites.iter().any(|f| normalize(f) == key)
+}
+
+// Liga ou desliga o favorito e devolve a lista gravada
+pub fn toggle_favorite(report_path: &str) -> Result<Vec<String>, String> {
+    let mut favorites = load_favorites();
+    let key = normalize(report_path);
+    let before = favorites.len();
+    favorites.retain(|f| normalize(f) != key);
+    if favorites.len() == before {
+        favorites.push(report_path.to_string());
+    }
+    save_favorites(&favorites)?;
+    Ok(favorites)
+}
+
+// Mantém favoritos e execuções apontando para o lugar certo quando um relatório (ou a
+// pasta dele) muda de caminho: sem isso o favorito some e os últimos parâmetros se perdem
+pub fn report_moved(old_path: &str, new_path: &str) -> Result<(), String> {
+    rename_favorites(old_path, new_path)?;
+    rename_runs(old_path, new_path)
+}
+
+fn rename_favorites(old_path: &str, new_path: &str) -> Result<(), String> {
+    let mut favorites = load_favorites();
+    let mut changed = false;
+    for f in favorites.iter_mut() {
+        if let Some(moved) = moved_path(f, old_path, new_path) {
+            *f = moved;
+            changed = true;
+        }
+    }
+    if changed {
+        save_favorites(&favorites)?;
+    }
+    Ok(())
+}
+
+// Reescreve `relatorio` nas linhas do execucoes.jsonl; linhas ilegíveis ficam como estão
+fn rename_runs(old_path: &str, new_path: &str) -> Result<(), String> {
+    let Ok(content) = fs::read_to_string(RUN_HISTORY_FILE) else {
+        return Ok(());
+    };
+    let mut changed = false;
+    let lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let Ok(mut run) = serde_json::from_str::<ReportRunEntry>(line) else {
+                return line.to_string();
+            };
+            let Some(moved) = moved_path(&run.relatorio, old_path, new_path) else {
+                return line.to_string();
+            };
+            run.relatorio = moved;
+            changed = true;
+            serde_json::to_string(&run).unwrap_or_else(|_| line.to_string())
+        })
+        .collect();
+    if changed {
+        fs::write(RUN_HISTORY_FILE, format!("{}\n", lines.join("\n")))
+            .map_err(|e| format!("Erro ao gravar {}: {}", RUN_HISTORY_FILE, e))?;
+    }
+    Ok(())
+}
+
+// Novo caminho de `path` se ele é o próprio `old_path` ou está dentro dessa pasta
+fn moved_path(path: &str, old_path: &str, new_path: &str) -> Option<String> {
+    let (current, key) = (normalize(path), normalize(old_path));
+    if key.is_empty() {
+        None
+    } else if current == key {
+        Some(new_path.to_string())
+    } else if current.starts_with(&format!("{}/", key)) {
+        path.get(old_path.len()..)
+            .map(|rest| format!("{}{}", new_path, rest))
+    } else {
+        None
+    }
+}
+
+fn save_favorites(favorites: &[String]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(favorites).map_err(|e| e.to_string())?;
+    fs::write(FAVORITES_FILE, json).map_err(|e| format!("Erro ao gravar {}: {}", FAVORITES_FILE, e))
+}
+
+// O mesmo relatório pode aparecer com '\' ou '/' dependendo de onde veio o caminho
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+fn trim_history() {
+    let content = fs::read_to_string(RUN_HISTORY_FILE).unwrap_or_default();
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() <= MAX_RUNS {
+        return;
+    }
+    let kept = lines[lines.len() - KEEP_RUNS..].join("\n");
+    let _ = fs::write(RUN_HISTORY_FILE, format!("{}\n", kept));
+}
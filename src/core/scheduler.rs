@@ -0,0 +1,278 @@
+use crate::core::export::{ExportFormat, export_results};
+use crate::core::report::{MULTI_SEPARATOR, apply_parameters, load_report, run_report};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const AGENDA_FILE: &str = "agenda.toml";
+pub const HISTORY_FILE: &str = "agenda_historico.jsonl";
+const POLL_INTERVAL_SECS: u64 = 30;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScheduledTask {
+    pub nome: String,
+    pub relatorio: String,
+    pub agenda: String,
+    #[serde(default = "default_format")]
+    pub formato: String,
+    pub destino: String,
+    #[serde(default)]
+    pub parametros: HashMap<String, String>,
+    #[serde(default = "default_enabled")]
+    pub ativo: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Agenda {
+    #[serde(default, rename = "tarefa")]
+    pub tarefas: Vec<ScheduledTask>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunRecord {
+    pub tarefa: String,
+    pub relatorio: String,
+    pub inicio: String,
+    pub duracao_ms: u128,
+    pub sucesso: bool,
+    pub linhas: usize,
+    pub arquivo: String,
+    pub erro: String,
+}
+
+fn default_format() -> String {
+    "xlsx".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub fn load_agenda(path: &Path) -> Result<Agenda, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Erro ao ler {}: {}", path.display(), e))?;
+    toml::from_str(&content).map_err(|e| format!("Erro em {}: {}", path.display(), e))
+}
+
+// Aceita cron de 5 campos (min hora dia mês semana, como no crontab) ou o formato
+// com segundos do crate cron, que numera a semana de 1 (domingo) a 7
+pub fn parse_schedule(expr: &str) -> Result<Schedule, String> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    let normalized = if fields.len() == 5 {
+        let weekdays = crontab_weekdays(fields[4])
+            .map_err(|e| format!("Agenda inválida '{}': {}", expr, e))?;
+        format!("0 {} {}", fields[..4].join(" "), weekdays)
+    } else {
+        expr.to_string()
+    };
+    Schedule::from_str(&normalized).map_err(|e| format!("Agenda inválida '{}': {}", expr, e))
+}
+
+// No crontab 0 e 7 são domingo e 1 é segunda; os números viram nomes (SUN..SAT), que o
+// crate entende sem depender da numeração dele. "1-5" vira "MON,TUE,WED,THU,FRI".
+fn crontab_weekdays(field: &str) -> Result<String, String> {
+    const NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+    let number = |text: &str| -> Result<usize, String> {
+        text.parse::<usize>()
+            .ok()
+            .filter(|n| *n <= 7)
+            .ok_or(format!("dia da semana inválido: {}", text))
+    };
+
+    // Nomes, '*' e '?' já significam o mesmo nos dois formatos
+    if field == "*" || field == "?" || field.chars().any(|c| c.is_ascii_alphabetic()) {
+        return Ok(field.to_string());
+    }
+
+    let mut days = [false; 7];
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, number(step)?.max(1)),
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            Some((a, b)) => (number(a)?, number(b)?),
+            None if range == "*" => (0, 6),
+            None if step > 1 => (number(range)?, 6),
+            None => (number(range)?, number(range)?),
+        };
+        if start > end {
+            return Err(format!("intervalo invertido: {}", item));
+        }
+        for day in (start..=end).step_by(step) {
+            days[day % 7] = true;
+        }
+    }
+
+    Ok(NAMES
+        .iter()
+        .zip(days)
+        .filter(|(_, on)| *on)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+pub fn next_run(task: &ScheduledTask, after: DateTime<Local>) -> Option<DateTime<Local>> {
+    parse_schedule(&task.agenda).ok()?.after(&after).next()
+}
+
+// {hoje}, {ontem}, {hoje-7}, {inicio_mes}, {fim_mes_anterior}... viram AAAA-MM-DD
+pub fn resolve_date_expression(value: &str, today: NaiveDate) -> Result<String, String> {
+    let Some(expr) = value
+        .trim()
+        .strip_prefix('{')
+        .and_then(|v| v.strip_suffix('}'))
+    else {
+        return Ok(value.to_string());
+    };
+
+    let first_of_month = today.with_day(1).unwrap_or(today);
+    let first_of_next_month = if today.month() == 12 {
+        NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)
+    }
+    .unwrap_or(today);
+    let last_of_previous = first_of_month - Duration::days(1);
+
+    let date = match expr.trim() {
+        "hoje" => today,
+        "ontem" => today - Duration::days(1),
+        "inicio_mes" => first_of_month,
+        "fim_mes" => first_of_next_month - Duration::days(1),
+        "inicio_mes_anterior" => last_of_previous.with_day(1).unwrap_or(last_of_previous),
+        "fim_mes_anterior" => last_of_previous,
+        "inicio_ano" => NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today),
+        other => {
+            let offset = other
+                .strip_prefix("hoje")
+                .map(str::trim)
+                .filter(|rest| rest.starts_with('+') || rest.starts_with('-'))
+                .and_then(|rest| rest.replace(' ', "").parse::<i64>().ok())
+                .ok_or(format!("Expressão de data desconhecida: {}", value))?;
+            today + Duration::days(offset)
+        }
+    };
+    Ok(date.format("%Y-%m-%d").to_string())
+}
+
+pub fn run_task(task: &ScheduledTask) -> RunRecord {
+    let started = Local::now();
+    let start = std::time::Instant::now();
+
+    let result = execute_task(task, started);
+    let (sucesso, linhas, arquivo, erro) = match result {
+        Ok((rows, file)) => (true, rows, file, String::new()),
+        Err(e) => (false, 0, String::new(), e),
+    };
+
+    let record = RunRecord {
+        tarefa: task.nome.clone(),
+        relatorio: task.relatorio.clone(),
+        inicio: started.format("%Y-%m-%d %H:%M:%S").to_string(),
+        duracao_ms: start.elapsed().as_millis(),
+        sucesso,
+        linhas,
+        arquivo,
+        erro,
+    };
+    append_history(&record);
+    record
+}
+
+fn execute_task(task: &ScheduledTask, started: DateTime<Local>) -> Result<(usize, String), String> {
+    let format = ExportFormat::parse(&task.formato)
+        .ok_or(format!("Formato inválido: {}", task.formato))?;
+
+    let report_path = Path::new(&task.relatorio);
+    let report = load_report(report_path)?;
+
+    let today = started.date_naive();
+    let mut values = HashMap::new();
+    for (id, raw) in &task.parametros {
+        // Período e seleção múltipla: "{inicio_mes};{fim_mes}" resolve cada parte
+        let parts = raw
+            .split(MULTI_SEPARATOR)
+            .map(|part| resolve_date_expression(part, today))
+            .collect::<Result<Vec<_>, _>>()?;
+        values.insert(id.clone(), parts.join(&MULTI_SEPARATOR.to_string()));
+    }
+    let final_sql = apply_parameters(&report.query_sql, &report.parametros, &values)?;
+
+    let run = run_report(&final_sql, &task.relatorio, &values)?;
+
+    let stem = report_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "relatorio".to_string());
+    let mut out_path = PathBuf::from(&task.destino);
+    std::fs::create_dir_all(&out_path)
+        .map_err(|e| format!("Erro ao criar {}: {}", out_path.display(), e))?;
+    out_path.push(format!(
+        "{}_{}.{}",
+        stem,
+        started.format("%Y%m%d_%H%M"),
+        format.extension()
+    ));
+
+    export_results(&run.engine, &run.columns, run.total_rows, &out_path, format)?;
+    Ok((run.total_rows, out_path.to_string_lossy().to_string()))
+}
+
+fn append_history(record: &RunRecord) {
+    if let Ok(line) = serde_json::to_string(record) {
+        if let Ok(mut file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(HISTORY_FILE)
+        {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+pub fn read_history(limit: usize) -> Vec<RunRecord> {
+    let content = std::fs::read_to_string(HISTORY_FILE).unwrap_or_default();
+    let mut records: Vec<RunRecord> = content
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    records.reverse();
+    records.truncate(limit);
+    records
+}
+
+// Laço do agendador: relê o agenda.toml a cada ciclo para aceitar edições sem reiniciar
+pub fn run_forever(agenda_path: PathBuf) {
+    let mut last_check = Local::now();
+
+    loop {
+        std::thread::sleep(std::time::Duration::from_secs(POLL_INTERVAL_SECS));
+        let now = Local::now();
+
+        match load_agenda(&agenda_path) {
+            Ok(agenda) => {
+                for task in agenda.tarefas.iter().filter(|t| t.ativo) {
+                    let due = next_run(task, last_check).map_or(false, |next| next <= now);
+                    if due {
+                        let record = run_task(task);
+                        if record.sucesso {
+                            println!("🗓️ [{}] {} linhas -> {}", task.nome, record.linhas, record.arquivo);
+                        } else {
+                            println!("🗓️ [{}] falhou: {}", task.nome, record.erro);
+                        }
+                    }
+                }
+            }
+            Err(e) => println!("{}", e),
+        }
+
+        last_check = now;
+    }
+}
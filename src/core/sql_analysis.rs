@@ -0,0 +1,311 @@
+use crate::core::engine::TableConfig;
+use crate::core::sql_text::{Context, replace_placeholders};
+use datafusion::sql::parser::{DFParser, Statement as DFStatement};
+use datafusion::sql::sqlparser::ast::{
+    Expr, JoinConstraint, JoinOperator, ObjectName, Query, Select, SelectItem, SetExpr,
+    Statement, TableFactor, TableWithJoins, Visit, Visitor,
+};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::ControlFlow;
+use std::sync::LazyLock;
+
+static RE_SYNC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").unwrap());
+
+// Tudo o que a query referencia, em minúsculas
+#[derive(Default, Debug)]
+pub struct SqlReferences {
+    pub tables: Vec<String>,
+    pub identifiers: HashSet<String>,
+    pub wildcard_tables: HashSet<String>,
+    pub local_names: HashSet<String>,
+}
+
+pub fn analyze(sql: &str) -> Result<SqlReferences, String> {
+    let clean_sql = RE_SYNC.replace_all(sql, "");
+
+    let statements = DFParser::parse_sql(&clean_sql).map_err(|e| e.to_string())?;
+
+    let mut collector = ReferenceCollector::default();
+    for statement in &statements {
+        if let DFStatement::Statement(statement) = statement {
+            let _ = statement.visit(&mut collector);
+        }
+    }
+
+    Ok(collector.refs)
+}
+
+// Igual a `analyze`, mas aceita o SQL salvo, com [parametros] ainda não preenchidos
+pub fn analyze_saved(sql: &str) -> Result<SqlReferences, String> {
+    let neutral = replace_placeholders(sql, |p| {
+        Ok((p.context == Context::Code).then(|| " NULL ".to_string()))
+    })
+    .unwrap_or_else(|_| sql.to_string());
+    analyze(&neutral)
+}
+
+// Nomes das tabelas usadas, sem CTEs nem tabelas criadas na própria query.
+// Erros de sintaxe devolvem lista vazia.
+pub fn referenced_tables(sql: &str) -> Vec<String> {
+    analyze_saved(sql).map(|r| r.tables).unwrap_or_default()
+}
+
+// Monta a lista (tabela, colunas) a extrair do DBISAM. Uma coluna entra quando o
+// nome aparece em qualquer ponto da query; sobra uma coluna a mais, nunca uma a menos.
+pub fn infer_extraction(
+    refs: &SqlReferences,
+    schema: &BTreeMap<String, TableConfig>,
+    skip: &HashSet<String>,
+) -> Vec<(String, Vec<String>)> {
+    let mut tasks = Vec::new();
+
+    for table in &refs.tables {
+        if skip.contains(table) {
+            continue;
+        }
+        let Some((schema_name, config)) = schema.iter().find(|(k, _)| k.to_lowercase() == *table)
+        else {
+            continue;
+        };
+
+        let cols: Vec<String> = if refs.wildcard_tables.contains(table) {
+            vec!["*".to_string()]
+        } else {
+            let used: Vec<String> = config
+                .columns
+                .iter()
+                .filter(|c| refs.identifiers.contains(&c.name.to_lowercase()))
+                .map(|c| c.name.clone())
+                .collect();
+
+            // COUNT(*) e afins: basta uma coluna para o DataFusion saber o nº de linhas
+            if used.is_empty() {
+                config.columns.iter().take(1).map(|c| c.name.clone()).collect()
+            } else {
+                used
+            }
+        };
+
+        tasks.push((schema_name.clone(), cols));
+    }
+
+    tasks
+}
+
+// CTEs de um WITH. Sem RECURSIVE, cada CTE só enxerga as anteriores, e não a si
+// mesma: em `WITH nfmestre AS (SELECT ... FROM nfmestre)` o FROM é a tabela real.
+struct CteScope {
+    names: Vec<String>,
+    recursive: bool,
+    // Quantas CTEs já começaram a ser visitadas e qual está em andamento
+    entered: usize,
+    current: Option<usize>,
+}
+
+impl CteScope {
+    fn visible(&self) -> &[String] {
+        match self.current {
+            Some(_) if self.recursive => &self.names,
+            Some(i) => &self.names[..i],
+            None => &self.names[..self.entered],
+        }
+    }
+}
+
+#[derive(Default)]
+struct ReferenceCollector {
+    refs: SqlReferences,
+    scopes: Vec<CteScope>,
+    // CREATE TABLE/VIEW valem para os comandos seguintes
+    created: HashSet<String>,
+}
+
+impl ReferenceCollector {
+    // `in_body`: a referência está no corpo da consulta mais interna, onde todas as
+    // CTEs dela já existem
+    fn is_local(&self, table: &str, in_body: bool) -> bool {
+        let last = self.scopes.len().saturating_sub(1);
+        self.created.contains(table)
+            || self.scopes.iter().enumerate().any(|(i, scope)| {
+                let names = if in_body && i == last {
+                    &scope.names[..]
+                } else {
+                    scope.visible()
+                };
+                names.iter().any(|n| n == table)
+            })
+    }
+
+    fn add_table(&mut self, name: &ObjectName) {
+        if let Some(table) = object_table_name(name) {
+            if !self.is_local(&table, false) && !self.refs.tables.contains(&table) {
+                self.refs.tables.push(table);
+            }
+        }
+    }
+
+    fn collect_wildcards(&mut self, body: &SetExpr) {
+        match body {
+            SetExpr::Select(select) => self.collect_select_wildcards(select),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.collect_wildcards(left);
+                self.collect_wildcards(right);
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_select_wildcards(&mut self, select: &Select) {
+        let mut aliases: HashMap<String, String> = HashMap::new();
+        for table_with_joins in &select.from {
+            // Tabela real de cada posição do FROM (a primeira e depois uma por JOIN)
+            let mut tables = Vec::new();
+            for factor in factors(table_with_joins) {
+                let mut real = None;
+                if let TableFactor::Table { name, alias, .. } = factor {
+                    let table = object_table_name(name).filter(|t| !self.is_local(t, true));
+                    if let Some(table) = table {
+                        if let Some(alias) = alias {
+                            aliases.insert(alias.name.value.to_lowercase(), table.clone());
+                        }
+                        aliases.insert(table.clone(), table.clone());
+                        real = Some(table);
+                    }
+                }
+                tables.push(real);
+            }
+            for (i, join) in table_with_joins.joins.iter().enumerate() {
+                match join_constraint(&join.join_operator) {
+                    Some(JoinConstraint::Using(idents)) => {
+                        for ident in idents {
+                            self.refs.identifiers.insert(ident.value.to_lowercase());
+                        }
+                    }
+                    // As colunas do NATURAL JOIN não aparecem na query: sem todas as
+                    // colunas dos dois lados, o join viraria produto cartesiano ou falharia
+                    Some(JoinConstraint::Natural) => {
+                        let joined = tables[..=i + 1].iter().flatten().cloned();
+                        self.refs.wildcard_tables.extend(joined);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        for item in &select.projection {
+            match item {
+                SelectItem::Wildcard(_) => {
+                    self.refs.wildcard_tables.extend(aliases.values().cloned());
+                }
+                SelectItem::QualifiedWildcard(qualifier, _) => {
+                    if let Some(table) = object_table_name(qualifier).and_then(|q| aliases.get(&q)) {
+                        self.refs.wildcard_tables.insert(table.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Visitor for ReferenceCollector {
+    type Break = ();
+
+    // Registrada depois de visitar o comando: o SELECT de um CREATE TABLE x AS ... lê
+    // as tabelas reais, inclusive uma chamada x
+    fn post_visit_statement(&mut self, statement: &Statement) -> ControlFlow<()> {
+        match statement {
+            Statement::CreateTable { name, .. } | Statement::CreateView { name, .. } => {
+                if let Some(table) = object_table_name(name) {
+                    self.refs.local_names.insert(table.clone());
+                    self.created.insert(table);
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        // As primeiras subconsultas visitadas dentro de uma consulta são as CTEs dela
+        if let Some(parent) = self.scopes.last_mut() {
+            if parent.entered < parent.names.len() {
+                parent.current = Some(parent.entered);
+                parent.entered += 1;
+            }
+        }
+
+        let names: Vec<String> = query
+            .with
+            .iter()
+            .flat_map(|with| &with.cte_tables)
+            .map(|cte| cte.alias.name.value.to_lowercase())
+            .collect();
+        self.refs.local_names.extend(names.iter().cloned());
+        self.scopes.push(CteScope {
+            names,
+            recursive: query.with.as_ref().is_some_and(|w| w.recursive),
+            entered: 0,
+            current: None,
+        });
+
+        self.collect_wildcards(&query.body);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<()> {
+        self.scopes.pop();
+        if let Some(parent) = self.scopes.last_mut() {
+            parent.current = None;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, factor: &TableFactor) -> ControlFlow<()> {
+        if let TableFactor::Table { name, .. } = factor {
+            self.add_table(name);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<()> {
+        match expr {
+            Expr::Identifier(ident) => {
+                self.refs.identifiers.insert(ident.value.to_lowercase());
+            }
+            Expr::CompoundIdentifier(parts) => {
+                if let Some(last) = parts.last() {
+                    self.refs.identifiers.insert(last.value.to_lowercase());
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn object_table_name(name: &ObjectName) -> Option<String> {
+    name.0.last().map(|ident| ident.value.to_lowercase())
+}
+
+fn factors(table_with_joins: &TableWithJoins) -> Vec<&TableFactor> {
+    let mut list = vec![&table_with_joins.relation];
+    list.extend(table_with_joins.joins.iter().map(|j| &j.relation));
+    list
+}
+
+fn join_constraint(operator: &JoinOperator) -> Option<&JoinConstraint> {
+    match operator {
+        JoinOperator::Inner(c)
+        | JoinOperator::LeftOuter(c)
+        | JoinOperator::RightOuter(c)
+        | JoinOperator::FullOuter(c)
+        | JoinOperator::LeftSemi(c)
+        | JoinOperator::RightSemi(c)
+        | JoinOperator::LeftAnti(c)
+        | JoinOperator::RightAnti(c) => Some(c),
+        _ => None,
+    }
+}
@@ -0,0 +1,141 @@
+use crate::core::sql_text::{Context, Edit, MappedSql, context_at_end};
+use regex::Regex;
+use std::ops::Range;
+
+// Erro apontado no texto do editor: `start..end` em bytes, linha e coluna a partir de 1
+#[derive(Clone, Debug, PartialEq)]
+pub struct SqlDiagnostic {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+// Mensagens do planner que citam o nome problemático
+const TERM_PATTERNS: &[&str] = &[
+    r"No field named ([^\s]+)",
+    r"table '([^']+)' not found",
+    r"[Ii]nvalid function '([^']+)'",
+    r"'([^']+)'",
+];
+
+// Remove as tags [SYNC: ...] como o motor faz, mantendo o mapa de posições
+pub fn strip_sync_tags(sql: &MappedSql) -> MappedSql {
+    let re_header = Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").unwrap();
+    let edits: Vec<Edit> = re_header
+        .find_iter(&sql.text)
+        .map(|m| (m.range(), String::new()))
+        .collect();
+    sql.apply(&edits)
+}
+
+// Leva o erro do DataFusion no comando `statement` (intervalo de `sql.text`) de volta
+// ao texto `original`. O parser informa linha e coluna; o planner só cita o nome, que
+// é procurado no próprio comando executado.
+pub fn locate_error(
+    error: &str,
+    sql: &MappedSql,
+    statement: Range<usize>,
+    original: &str,
+) -> Option<SqlDiagnostic> {
+    let command = &sql.text[statement.clone()];
+    let (offset, len) =
+        parser_position(error, command).or_else(|| planner_position(error, command))?;
+
+    let start = sql.original_offset(statement.start + offset);
+    let mapped_end = sql.original_offset(statement.start + offset + len);
+    // Erro dentro do valor de um [parametro]: marca o placeholder inteiro
+    let end = if mapped_end > start {
+        mapped_end
+    } else {
+        start + token_len(&original[start..])
+    };
+
+    let (line, column) = line_column(original, start);
+    Some(SqlDiagnostic {
+        start,
+        end,
+        line,
+        column,
+        message: error.trim().to_string(),
+    })
+}
+
+// "... at Line: 2, Column 15" (sqlparser), relativo ao comando
+fn parser_position(error: &str, command: &str) -> Option<(usize, usize)> {
+    let re = Regex::new(r"Line: (\d+), Column:? (\d+)").unwrap();
+    let caps = re.captures_iter(error).last()?;
+    let line: usize = caps[1].parse().ok()?;
+    let column: usize = caps[2].parse().ok()?;
+    if line == 0 {
+        return None;
+    }
+
+    let line_start: usize = command
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum();
+    let line_text = command[line_start..].lines().next().unwrap_or("");
+    let offset = line_start
+        + line_text
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(line_text.len(), |(i, _)| i);
+    Some((offset, token_len(&command[offset..])))
+}
+
+fn planner_position(error: &str, command: &str) -> Option<(usize, usize)> {
+    for pattern in TERM_PATTERNS {
+        let re = Regex::new(pattern).unwrap();
+        let Some(caps) = re.captures(error) else {
+            continue;
+        };
+        let term = caps[1].replace('"', "");
+        let term = term.trim_matches('.');
+        let last_part = term.rsplit('.').next().unwrap_or(term);
+
+        for candidate in [term, last_part] {
+            if let Some(found) = find_term(command, candidate) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+// Primeira ocorrência da palavra fora de strings e comentários
+fn find_term(command: &str, term: &str) -> Option<(usize, usize)> {
+    if term.is_empty() {
+        return None;
+    }
+    let re = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(term))).ok()?;
+    re.find_iter(command)
+        .find(|m| context_at_end(&command[..m.start()]) == Some(Context::Code))
+        .map(|m| (m.start(), m.len()))
+}
+
+// Tamanho do token que começa no texto: [parametro], palavra ou um caractere
+fn token_len(text: &str) -> usize {
+    if text.starts_with('[') {
+        if let Some(close) = text.find(']') {
+            return close + 1;
+        }
+    }
+    let word = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    if word > 0 {
+        word
+    } else {
+        text.chars().next().map_or(0, char::len_utf8)
+    }
+}
+
+fn line_column(text: &str, pos: usize) -> (usize, usize) {
+    let before = &text[..pos.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, column)
+}
@@ -0,0 +1,428 @@
+use crate::core::sql_highlight::keyword_set;
+use crate::core::sql_text::{Context, Edit, apply_edits, context_at_end, placeholder_edits};
+use datafusion::sql::parser::DFParser;
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::tokenizer::{Token, Tokenizer, Whitespace, Word};
+use regex::Regex;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+    Preserve,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatOptions {
+    pub keyword_case: KeywordCase,
+    pub indent: String,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            keyword_case: KeywordCase::Upper,
+            indent: "    ".to_string(),
+        }
+    }
+}
+
+// Palavras que, seguidas de '(', são funções e não palavras-chave
+const FUNCTION_LIKE_KEYWORDS: &[&str] = &["LEFT", "RIGHT", "REPLACE", "IF", "DATE", "TIMESTAMP"];
+const JOIN_MODIFIERS: &[&str] = &["LEFT", "RIGHT", "INNER", "FULL", "CROSS", "OUTER"];
+
+// Reescreve a query com indentação e caixa das palavras-chave padronizadas.
+// Comentários, a tag SYNC e os [parametros] passam intactos; o resultado só é
+// aceito se o parser enxergar exatamente os mesmos comandos de antes.
+pub fn format_sql(sql: &str, options: &FormatOptions) -> Result<String, String> {
+    let tag = marker_tag(sql);
+    let (protected, originals) = protect(sql, &tag)?;
+
+    let before = parse_signature(&protected)
+        .map_err(|e| format!("Corrija o erro de sintaxe antes de formatar: {}", e))?;
+
+    let formatted = reflow(&protected, options)?;
+
+    let after = parse_signature(&formatted)
+        .map_err(|e| format!("A formatação gerou um SQL inválido e foi cancelada: {}", e))?;
+    if before != after {
+        return Err("A formatação mudaria o significado da query e foi cancelada.".to_string());
+    }
+
+    Ok(restore(&formatted, &originals, &tag))
+}
+
+// Prefixo dos marcadores que não aparece em lugar nenhum do texto do usuário, para
+// que o restore nunca troque algo que ele mesmo escreveu
+fn marker_tag(sql: &str) -> String {
+    (0..)
+        .map(|n| format!("__nr{}_", n))
+        .find(|tag| !sql.contains(tag.as_str()))
+        .unwrap()
+}
+
+// Troca [parametros] em código e tags [SYNC: ...] fora de comentários por marcadores
+// que o tokenizador aceita e o formatador não altera
+fn protect(sql: &str, tag: &str) -> Result<(String, Vec<String>), String> {
+    let mut originals = Vec::new();
+
+    let re_header = Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").unwrap();
+    let sync_edits: Vec<Edit> = re_header
+        .find_iter(sql)
+        .filter(|m| context_at_end(&sql[..m.start()]) == Some(Context::Code))
+        .map(|m| {
+            originals.push(m.as_str().to_string());
+            (m.range(), format!("/*{}{}__*/", tag, originals.len() - 1))
+        })
+        .collect();
+    let without_sync = apply_edits(sql, &sync_edits);
+
+    let param_edits = placeholder_edits(&without_sync, |p| {
+        if p.context != Context::Code {
+            return Ok(None);
+        }
+        originals.push(format!("[{}]", p.id));
+        Ok(Some(format!("{}{}__", tag, originals.len() - 1)))
+    })?;
+
+    Ok((apply_edits(&without_sync, &param_edits), originals))
+}
+
+fn restore(formatted: &str, originals: &[String], tag: &str) -> String {
+    let pattern = format!(r"(?:/\*)?{}(\d+)__(?:\*/)?", regex::escape(tag));
+    let re_marker = Regex::new(&pattern).unwrap();
+    re_marker
+        .replace_all(formatted, |caps: &regex::Captures| {
+            caps[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| originals.get(i))
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+// Comentários e espaços não entram no AST, então a comparação ignora o layout. A caixa
+// também é ignorada: o DataFusion já trata nomes sem aspas em minúsculas.
+fn parse_signature(sql: &str) -> Result<String, String> {
+    DFParser::parse_sql(sql)
+        .map(|statements| format!("{:?}", statements).to_lowercase())
+        .map_err(|e| e.to_string())
+}
+
+// Token com o texto exato da origem e se havia quebra de linha antes dele
+struct SourceToken<'a> {
+    token: Token,
+    text: &'a str,
+    newline_before: bool,
+}
+
+fn tokenize(sql: &str) -> Result<Vec<SourceToken<'_>>, String> {
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, sql)
+        .tokenize_with_location()
+        .map_err(|e| e.to_string())?;
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(sql.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let offset_of = |line: u64, column: u64| -> usize {
+        let Some(start) = line_starts.get((line as usize).saturating_sub(1)) else {
+            return sql.len();
+        };
+        sql[*start..]
+            .char_indices()
+            .nth((column as usize).saturating_sub(1))
+            .map_or(sql.len(), |(i, _)| start + i)
+    };
+
+    let offsets: Vec<usize> = tokens
+        .iter()
+        .map(|t| offset_of(t.location.line, t.location.column))
+        .chain(std::iter::once(sql.len()))
+        .collect();
+
+    let mut list = Vec::new();
+    let mut newline_before = true;
+    for (i, t) in tokens.into_iter().enumerate() {
+        let text = &sql[offsets[i].min(offsets[i + 1])..offsets[i + 1]];
+        match &t.token {
+            Token::EOF => {}
+            Token::Whitespace(Whitespace::Newline) => newline_before = true,
+            Token::Whitespace(Whitespace::Space) | Token::Whitespace(Whitespace::Tab) => {}
+            _ => {
+                let ends_line = matches!(
+                    t.token,
+                    Token::Whitespace(Whitespace::SingleLineComment { .. })
+                );
+                list.push(SourceToken {
+                    token: t.token,
+                    text,
+                    newline_before,
+                });
+                newline_before = ends_line;
+            }
+        }
+    }
+    Ok(list)
+}
+
+enum Paren {
+    // Subconsulta: abre um bloco novo; guarda o bloco de fora para a volta
+    Subquery { base: usize, clause: String },
+    Inline,
+}
+
+struct Writer<'a> {
+    out: String,
+    indent: &'a str,
+    line_start: bool,
+}
+
+impl Writer<'_> {
+    fn newline(&mut self) {
+        if self.line_start {
+            return;
+        }
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+        self.line_start = true;
+    }
+
+    fn write(&mut self, text: &str, depth: usize, space_before: bool) {
+        if self.line_start {
+            self.out.push_str(&self.indent.repeat(depth));
+            self.line_start = false;
+        } else if space_before {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+    }
+}
+
+// Não reimprime o AST: reescreve a sequência de tokens da própria query, mudando só
+// espaços, quebras de linha e a caixa das palavras-chave. Assim os comentários, que o
+// AST descarta, continuam no lugar; o AST só serve para conferir a equivalência.
+fn reflow(sql: &str, options: &FormatOptions) -> Result<String, String> {
+    let tokens = tokenize(sql)?;
+    let keywords = keyword_set();
+
+    let mut writer = Writer {
+        out: String::new(),
+        indent: &options.indent,
+        line_start: true,
+    };
+    let mut stack: Vec<Paren> = Vec::new();
+    let mut base = 0;
+    let mut clause = String::new();
+    let mut statement_start = true;
+    let mut select_items_pending = false;
+    let mut between_pending = false;
+    let mut no_space_next = false;
+    let mut prev: Option<&Token> = None;
+
+    for (i, current) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1).map(|t| &t.token);
+        let word = unquoted_word(&current.token).map(|w| w.value.to_uppercase());
+        let followed_by_paren = matches!(next, Some(Token::LParen));
+        let is_function = word.as_deref().is_some_and(|w| {
+            followed_by_paren && (!keywords.contains(w) || FUNCTION_LIKE_KEYWORDS.contains(&w))
+        });
+        let is_keyword = word.as_deref().is_some_and(|w| keywords.contains(w)) && !is_function;
+        let at_block_level = !matches!(stack.last(), Some(Paren::Inline));
+
+        // Comentários mantêm a posição relativa: na própria linha ou no fim da anterior
+        if let Token::Whitespace(ws) = &current.token {
+            if current.newline_before {
+                writer.newline();
+            }
+            writer.write(current.text.trim_end(), base, !current.newline_before);
+            if matches!(ws, Whitespace::SingleLineComment { .. }) || current.newline_before {
+                writer.newline();
+            }
+            continue;
+        }
+
+        let text = match (&word, is_keyword) {
+            (Some(w), true) => match options.keyword_case {
+                KeywordCase::Upper => w.clone(),
+                KeywordCase::Lower => w.to_lowercase(),
+                KeywordCase::Preserve => current.text.to_string(),
+            },
+            _ => current.text.to_string(),
+        };
+
+        let starts_clause = at_block_level
+            && is_keyword
+            && match word.as_deref() {
+                Some("SELECT" | "FROM" | "WHERE" | "HAVING" | "LIMIT" | "OFFSET" | "VALUES") => {
+                    true
+                }
+                Some("UNION" | "EXCEPT" | "INTERSECT") => true,
+                Some("GROUP" | "ORDER" | "PARTITION") => {
+                    matches!(next.and_then(unquoted_word), Some(w) if w.value.eq_ignore_ascii_case("BY"))
+                }
+                Some("LEFT" | "RIGHT" | "INNER" | "FULL" | "CROSS") => true,
+                Some("JOIN") => !prev
+                    .and_then(unquoted_word)
+                    .is_some_and(|w| JOIN_MODIFIERS.contains(&w.value.to_uppercase().as_str())),
+                _ => false,
+            };
+
+        let mut space_before = !no_space_next
+            && !matches!(
+                current.token,
+                Token::Comma
+                    | Token::RParen
+                    | Token::Period
+                    | Token::SemiColon
+                    | Token::DoubleColon
+            )
+            && !matches!(
+                prev,
+                Some(Token::LParen | Token::Period | Token::DoubleColon)
+            );
+        if matches!(current.token, Token::LParen)
+            && prev.is_some_and(|p| {
+                unquoted_word(p).is_some() && !is_keyword_before_paren(p, &keywords)
+            })
+        {
+            space_before = false;
+        }
+        no_space_next = false;
+
+        let closes_query = matches!(
+            (&current.token, stack.last()),
+            (Token::RParen, Some(Paren::Subquery { .. }))
+        );
+
+        if closes_query {
+            // O ')' da subconsulta volta para a linha e o nível do bloco de fora
+            if let Some(Paren::Subquery {
+                base: outer,
+                clause: outer_clause,
+            }) = stack.pop()
+            {
+                writer.newline();
+                base = outer;
+                clause = outer_clause;
+                writer.write(&text, base, false);
+            }
+        } else if starts_clause {
+            if !statement_start {
+                writer.newline();
+            }
+            writer.write(&text, base, space_before);
+            clause = word.clone().unwrap_or_default();
+            select_items_pending = clause == "SELECT";
+            between_pending = false;
+        } else if select_items_pending && !matches!(word.as_deref(), Some("DISTINCT" | "ALL")) {
+            // Um item do SELECT por linha
+            select_items_pending = false;
+            writer.newline();
+            writer.write(&text, base + 1, false);
+        } else if at_block_level
+            && is_keyword
+            && matches!(word.as_deref(), Some("AND" | "OR"))
+            && !(word.as_deref() == Some("AND") && between_pending)
+            && matches!(
+                clause.as_str(),
+                "WHERE" | "HAVING" | "LEFT" | "RIGHT" | "INNER" | "FULL" | "CROSS" | "JOIN"
+            )
+        {
+            writer.newline();
+            writer.write(&text, base + 1, false);
+        } else {
+            let depth = if statement_start { base } else { base + 1 };
+            writer.write(&text, depth, space_before);
+        }
+
+        match &current.token {
+            Token::LParen => {
+                let opens_query = next.and_then(unquoted_word).is_some_and(|w| {
+                    w.value.eq_ignore_ascii_case("SELECT") || w.value.eq_ignore_ascii_case("WITH")
+                });
+                if opens_query {
+                    stack.push(Paren::Subquery {
+                        base,
+                        clause: clause.clone(),
+                    });
+                    base += 1;
+                    writer.newline();
+                } else {
+                    stack.push(Paren::Inline);
+                }
+            }
+            Token::RParen if !closes_query => {
+                stack.pop();
+            }
+            Token::Comma if at_block_level && matches!(clause.as_str(), "SELECT" | "WITH") => {
+                writer.newline();
+                if clause == "SELECT" {
+                    select_items_pending = true;
+                }
+            }
+            Token::SemiColon => {
+                writer.newline();
+                writer.out.push('\n');
+                stack.clear();
+                base = 0;
+                clause.clear();
+                select_items_pending = false;
+                statement_start = true;
+                prev = None;
+                continue;
+            }
+            Token::Minus | Token::Plus if !prev.is_some_and(|p| is_value_end(p, &keywords)) => {
+                no_space_next = true;
+            }
+            _ => {}
+        }
+
+        if is_keyword && word.as_deref() == Some("BETWEEN") {
+            between_pending = true;
+        } else if is_keyword && word.as_deref() == Some("AND") {
+            between_pending = false;
+        }
+        if statement_start && matches!(word.as_deref(), Some("WITH")) {
+            clause = "WITH".to_string();
+        }
+        statement_start = false;
+        prev = Some(&current.token);
+    }
+
+    Ok(writer.out.trim_end().to_string())
+}
+
+fn unquoted_word(token: &Token) -> Option<&Word> {
+    match token {
+        Token::Word(w) if w.quote_style.is_none() => Some(w),
+        _ => None,
+    }
+}
+
+// IN (, AS (, VALUES (... levam espaço; LEFT(, REPLACE(... são chamadas de função
+fn is_keyword_before_paren(token: &Token, keywords: &HashSet<String>) -> bool {
+    unquoted_word(token).is_some_and(|w| {
+        let upper = w.value.to_uppercase();
+        keywords.contains(&upper) && !FUNCTION_LIKE_KEYWORDS.contains(&upper.as_str())
+    })
+}
+
+// Fim de um valor: depois dele '+' e '-' são operadores binários, não sinais
+fn is_value_end(token: &Token, keywords: &HashSet<String>) -> bool {
+    match token {
+        Token::Word(w) => w.quote_style.is_some() || !keywords.contains(&w.value.to_uppercase()),
+        Token::Number(..)
+        | Token::SingleQuotedString(_)
+        | Token::DoubleQuotedString(_)
+        | Token::NationalStringLiteral(_)
+        | Token::HexStringLiteral(_)
+        | Token::RParen
+        | Token::Placeholder(_) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,213 @@
+use crate::core::autocomplete::KEYWORDS;
+use crate::core::sql_text::{Segment, is_placeholder_id, segments};
+use regex::Regex;
+use std::collections::HashSet;
+use std::ops::Range;
+
+// Palavras realçadas que não são sugeridas sozinhas pelo autocompletar
+const EXTRA_KEYWORDS: &[&str] = &[
+    "DATE",
+    "TIMESTAMP",
+    "INTERVAL",
+    "FILTER",
+    "ALL",
+    "ANY",
+    "TABLE",
+    "VIEW",
+    "INTO",
+    "IF",
+    "REPLACE",
+    "EXPLAIN",
+    "ANALYZE",
+];
+
+// Palavras-chave em maiúsculas, as mesmas que o formatador troca de caixa
+pub fn keyword_set() -> HashSet<String> {
+    KEYWORDS
+        .iter()
+        .flat_map(|k| k.split(' '))
+        .chain(EXTRA_KEYWORDS.iter().copied())
+        .map(|k| k.to_string())
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenClass {
+    Plain,
+    Keyword,
+    Function,
+    Number,
+    String,
+    Comment,
+    Parameter,
+    SyncTag,
+    Identifier,
+}
+
+impl TokenClass {
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            TokenClass::Plain => "sql-plain",
+            TokenClass::Keyword => "sql-keyword",
+            TokenClass::Function => "sql-function",
+            TokenClass::Number => "sql-number",
+            TokenClass::String => "sql-string",
+            TokenClass::Comment => "sql-comment",
+            TokenClass::Parameter => "sql-param",
+            TokenClass::SyncTag => "sql-sync",
+            TokenClass::Identifier => "sql-identifier",
+        }
+    }
+}
+
+// Trechos do texto inteiro com a classe de cada um. `marks` (erros) quebram os
+// trechos nas bordas e marcam o que estiver dentro deles.
+pub fn highlight(sql: &str, marks: &[Range<usize>]) -> Vec<(Range<usize>, TokenClass, bool)> {
+    let keywords = keyword_set();
+
+    let mut tokens = Vec::new();
+    for (range, segment) in segments(sql) {
+        match segment {
+            Segment::Code => tokenize_code(sql, range, &keywords, &mut tokens),
+            Segment::String => split_placeholders(sql, range, TokenClass::String, &mut tokens),
+            Segment::Comment => split_sync_tags(sql, range, &mut tokens),
+            Segment::QuotedIdentifier => tokens.push((range, TokenClass::Identifier)),
+        }
+    }
+
+    let mut spans: Vec<(Range<usize>, TokenClass, bool)> = Vec::new();
+    for (range, class) in tokens {
+        let mut cuts = vec![range.start, range.end];
+        for mark in marks {
+            for edge in [mark.start, mark.end] {
+                if edge > range.start && edge < range.end && sql.is_char_boundary(edge) {
+                    cuts.push(edge);
+                }
+            }
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        for pair in cuts.windows(2) {
+            let piece = pair[0]..pair[1];
+            let marked = marks
+                .iter()
+                .any(|m| m.start < piece.end && piece.start < m.end);
+            match spans.last_mut() {
+                Some((last, last_class, last_marked))
+                    if *last_class == class
+                        && *last_marked == marked
+                        && last.end == piece.start =>
+                {
+                    last.end = piece.end;
+                }
+                _ => spans.push((piece, class, marked)),
+            }
+        }
+    }
+    spans
+}
+
+fn tokenize_code(
+    sql: &str,
+    range: Range<usize>,
+    keywords: &HashSet<String>,
+    tokens: &mut Vec<(Range<usize>, TokenClass)>,
+) {
+    let code = &sql[range.clone()];
+    let mut pos = 0;
+
+    while pos < code.len() {
+        let rest = &code[pos..];
+        let first = rest.chars().next().unwrap_or(' ');
+
+        let (len, class) = if first.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+                .unwrap_or(rest.len());
+            (len, TokenClass::Number)
+        } else if first.is_alphabetic() || first == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let class = if keywords.contains(&word.to_uppercase()) {
+                TokenClass::Keyword
+            } else if rest[len..].trim_start().starts_with('(') {
+                TokenClass::Function
+            } else {
+                TokenClass::Plain
+            };
+            (len, class)
+        } else if first == '[' {
+            bracket_token(rest)
+        } else {
+            (first.len_utf8(), TokenClass::Plain)
+        };
+
+        tokens.push((range.start + pos..range.start + pos + len, class));
+        pos += len;
+    }
+}
+
+// [parametro], [SYNC: ...] ou um colchete qualquer
+fn bracket_token(rest: &str) -> (usize, TokenClass) {
+    if rest
+        .get(..6)
+        .is_some_and(|tag| tag.eq_ignore_ascii_case("[SYNC:"))
+    {
+        let len = rest.find(']').map_or(rest.len(), |close| close + 1);
+        return (len, TokenClass::SyncTag);
+    }
+    match rest.find(']') {
+        Some(close) if is_placeholder_id(&rest[1..close]) => (close + 1, TokenClass::Parameter),
+        _ => (1, TokenClass::Plain),
+    }
+}
+
+fn split_placeholders(
+    sql: &str,
+    range: Range<usize>,
+    class: TokenClass,
+    tokens: &mut Vec<(Range<usize>, TokenClass)>,
+) {
+    let re = Regex::new(r"\[([a-zA-Z0-9_]+)\]").unwrap();
+    let text = &sql[range.clone()];
+    let mut last = 0;
+    for m in re.find_iter(text) {
+        if m.start() > last {
+            tokens.push((range.start + last..range.start + m.start(), class));
+        }
+        tokens.push((
+            range.start + m.start()..range.start + m.end(),
+            TokenClass::Parameter,
+        ));
+        last = m.end();
+    }
+    if last < text.len() {
+        tokens.push((range.start + last..range.end, class));
+    }
+}
+
+// A tag de sincronização costuma ficar num comentário e ganha cor própria
+fn split_sync_tags(sql: &str, range: Range<usize>, tokens: &mut Vec<(Range<usize>, TokenClass)>) {
+    let re = Regex::new(r"(?i)\[SYNC:[^\]]*\]?").unwrap();
+    let text = &sql[range.clone()];
+    let mut last = 0;
+    for m in re.find_iter(text) {
+        if m.start() > last {
+            tokens.push((
+                range.start + last..range.start + m.start(),
+                TokenClass::Comment,
+            ));
+        }
+        tokens.push((
+            range.start + m.start()..range.start + m.end(),
+            TokenClass::SyncTag,
+        ));
+        last = m.end();
+    }
+    if last < text.len() {
+        tokens.push((range.start + last..range.end, TokenClass::Comment));
+    }
+}
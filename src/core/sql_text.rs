@@ -0,0 +1,358 @@
+// Varredura léxica mínima do SQL: sabe onde começam e terminam strings,
+// identificadores entre aspas e comentários, sem depender do parser.
+
+use std::ops::Range;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Context {
+    Code,
+    StringLiteral,
+}
+
+pub struct Placeholder<'a> {
+    pub id: &'a str,
+    pub context: Context,
+}
+
+// Divide em comandos pelo ';' ignorando os que estão dentro de strings e comentários
+pub fn split_statements(sql: &str) -> Vec<String> {
+    statement_ranges(sql)
+        .into_iter()
+        .map(|range| sql[range].to_string())
+        .collect()
+}
+
+// Posição de cada comando no texto, já sem os espaços das pontas
+pub fn statement_ranges(sql: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut scanner = Scanner::new(sql);
+
+    loop {
+        let chunk_start = scanner.pos;
+        let Some((chunk, kind)) = scanner.next_chunk() else {
+            break;
+        };
+        if kind != ChunkKind::Code {
+            continue;
+        }
+        for (i, c) in chunk.char_indices() {
+            if c == ';' {
+                ranges.push(start..chunk_start + i);
+                start = chunk_start + i + 1;
+            }
+        }
+    }
+    ranges.push(start..sql.len());
+
+    ranges
+        .into_iter()
+        .filter_map(|range| {
+            let text = &sql[range.clone()];
+            let start = range.start + (text.len() - text.trim_start().len());
+            let end = range.start + text.trim_end().len();
+            (start < end).then_some(start..end)
+        })
+        .collect()
+}
+
+// Troca cada [id] pelo retorno de `replace`; `None` mantém o texto original.
+// Placeholders dentro de comentários não são tocados.
+pub fn replace_placeholders<F>(sql: &str, replace: F) -> Result<String, String>
+where
+    F: FnMut(Placeholder) -> Result<Option<String>, String>,
+{
+    placeholder_edits(sql, replace).map(|edits| apply_edits(sql, &edits))
+}
+
+// Mesma regra de `replace_placeholders`, mas devolve as trocas com a posição de cada uma
+pub fn placeholder_edits<F>(sql: &str, mut replace: F) -> Result<Vec<Edit>, String>
+where
+    F: FnMut(Placeholder) -> Result<Option<String>, String>,
+{
+    let mut edits = Vec::new();
+    let mut scanner = Scanner::new(sql);
+
+    loop {
+        let chunk_start = scanner.pos;
+        let Some((chunk, kind)) = scanner.next_chunk() else {
+            break;
+        };
+        let context = match kind {
+            ChunkKind::Code => Context::Code,
+            ChunkKind::String => Context::StringLiteral,
+            ChunkKind::Other => continue,
+        };
+
+        let mut from = 0;
+        while let Some(found) = chunk[from..].find('[') {
+            let open = from + found;
+            let after = &chunk[open + 1..];
+            let id = after
+                .find(']')
+                .map(|close| &after[..close])
+                .filter(|id| is_placeholder_id(id));
+
+            let Some(id) = id else {
+                from = open + 1;
+                continue;
+            };
+
+            let end = open + id.len() + 2;
+            if let Some(value) = replace(Placeholder { id, context })? {
+                edits.push((chunk_start + open..chunk_start + end, value));
+            }
+            from = end;
+        }
+    }
+
+    Ok(edits)
+}
+
+// Trocas em ordem e sem sobreposição: (trecho do texto, novo conteúdo)
+pub type Edit = (Range<usize>, String);
+
+pub fn apply_edits(sql: &str, edits: &[Edit]) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut last = 0;
+    for (range, value) in edits {
+        out.push_str(&sql[last..range.start]);
+        out.push_str(value);
+        last = range.end;
+    }
+    out.push_str(&sql[last..]);
+    out
+}
+
+// Texto transformado que lembra de que posição do original veio cada byte, para
+// apontar erros do SQL final (parâmetros trocados, SYNC removido) no texto do editor
+#[derive(Clone, Debug, PartialEq)]
+pub struct MappedSql {
+    pub text: String,
+    // Um item por byte de `text`, mais um para o fim do texto
+    origin: Vec<usize>,
+}
+
+impl MappedSql {
+    pub fn new(sql: &str) -> Self {
+        Self {
+            text: sql.to_string(),
+            origin: (0..=sql.len()).collect(),
+        }
+    }
+
+    // O conteúdo inserido aponta para o início do trecho que ele substituiu
+    pub fn apply(&self, edits: &[Edit]) -> Self {
+        let mut text = String::with_capacity(self.text.len());
+        let mut origin = Vec::with_capacity(self.origin.len());
+        let mut last = 0;
+        for (range, value) in edits {
+            text.push_str(&self.text[last..range.start]);
+            origin.extend_from_slice(&self.origin[last..range.start]);
+            text.push_str(value);
+            origin.extend(std::iter::repeat(self.origin[range.start]).take(value.len()));
+            last = range.end;
+        }
+        text.push_str(&self.text[last..]);
+        origin.extend_from_slice(&self.origin[last..]);
+        Self { text, origin }
+    }
+
+    pub fn original_offset(&self, pos: usize) -> usize {
+        self.origin[pos.min(self.origin.len() - 1)]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Segment {
+    Code,
+    String,
+    Comment,
+    QuotedIdentifier,
+}
+
+// O texto inteiro dividido em trechos de código, strings (com as aspas),
+// comentários e identificadores entre aspas duplas
+pub fn segments(sql: &str) -> Vec<(Range<usize>, Segment)> {
+    let mut list = Vec::new();
+    let mut scanner = Scanner::new(sql);
+
+    loop {
+        let chunk_start = scanner.pos;
+        let Some((chunk, kind)) = scanner.next_chunk() else {
+            break;
+        };
+        let segment = match kind {
+            ChunkKind::Code => Segment::Code,
+            ChunkKind::String => Segment::String,
+            ChunkKind::Other if chunk.starts_with("--") || chunk.starts_with("/*") => {
+                Segment::Comment
+            }
+            ChunkKind::Other if chunk.starts_with('"') => Segment::QuotedIdentifier,
+            ChunkKind::Other => Segment::String,
+        };
+        list.push((chunk_start..chunk_start + chunk.len(), segment));
+    }
+
+    list
+}
+
+// Contexto no fim do texto (ex.: tudo antes do cursor). `None` quando termina
+// dentro de um comentário ou identificador entre aspas duplas.
+pub fn context_at_end(sql: &str) -> Option<Context> {
+    let mut scanner = Scanner::new(sql);
+    let mut last = None;
+    while let Some(chunk) = scanner.next_chunk() {
+        last = Some(chunk);
+    }
+
+    if scanner.in_string {
+        return Some(Context::StringLiteral);
+    }
+    match last {
+        Some((chunk, ChunkKind::Other)) if chunk.starts_with("--") => None,
+        Some((chunk, ChunkKind::Other)) if chunk.starts_with("/*") && !chunk.ends_with("*/") => {
+            None
+        }
+        Some((chunk, ChunkKind::Other))
+            if chunk.starts_with('"') && (chunk.len() == 1 || !chunk.ends_with('"')) =>
+        {
+            None
+        }
+        _ => Some(Context::Code),
+    }
+}
+
+pub fn is_placeholder_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChunkKind {
+    Code,
+    // Conteúdo de '...' sem as aspas
+    String,
+    // Aspas, comentários e identificadores "..."
+    Other,
+}
+
+struct Scanner<'a> {
+    sql: &'a str,
+    pos: usize,
+    in_string: bool,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(sql: &'a str) -> Self {
+        Self {
+            sql,
+            pos: 0,
+            in_string: false,
+        }
+    }
+
+    // Devolve o próximo trecho homogêneo (código, conteúdo de string ou o resto)
+    fn next_chunk(&mut self) -> Option<(&'a str, ChunkKind)> {
+        let rest = &self.sql[self.pos..];
+        if rest.is_empty() {
+            return None;
+        }
+
+        let (len, kind) = if self.in_string {
+            match find_string_end(rest) {
+                Some(0) => {
+                    self.in_string = false;
+                    (1, ChunkKind::Other)
+                }
+                Some(end) => (end, ChunkKind::String),
+                None => (rest.len(), ChunkKind::String),
+            }
+        } else if rest.starts_with('\'') {
+            self.in_string = true;
+            (1, ChunkKind::Other)
+        } else if rest.starts_with("--") {
+            (rest.find('\n').unwrap_or(rest.len()), ChunkKind::Other)
+        } else if rest.starts_with("/*") {
+            (rest.find("*/").map_or(rest.len(), |e| e + 2), ChunkKind::Other)
+        } else if rest.starts_with('"') {
+            (rest[1..].find('"').map_or(rest.len(), |e| e + 2), ChunkKind::Other)
+        } else {
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|(i, _)| {
+                    let r = &rest[*i..];
+                    r.starts_with('\'') || r.starts_with("--") || r.starts_with("/*") || r.starts_with('"')
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            (end, ChunkKind::Code)
+        };
+
+        let chunk = &rest[..len];
+        self.pos += len;
+        Some((chunk, kind))
+    }
+}
+
+// Posição da aspa que fecha a string ('' é aspa escapada e não fecha)
+fn find_string_end(rest: &str) -> Option<usize> {
+    let bytes = rest.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            if bytes.get(i + 1) == Some(&b'\'') {
+                i += 2;
+                continue;
+            }
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(sql: &str) -> Vec<(String, Context)> {
+        let mut list = Vec::new();
+        placeholder_edits(sql, |p| {
+            list.push((p.id.to_string(), p.context));
+            Ok(None)
+        })
+        .unwrap();
+        list
+    }
+
+    #[test]
+    fn placeholder_contexts() {
+        let sql = "SELECT [a], '[b] it''s [c]', \"[d]\" -- [e]\n/* [f] */ FROM t WHERE x = [g]";
+        assert_eq!(
+            found(sql),
+            vec![
+                ("a".to_string(), Context::Code),
+                ("b".to_string(), Context::StringLiteral),
+                ("c".to_string(), Context::StringLiteral),
+                ("g".to_string(), Context::Code),
+            ]
+        );
+        // Colchetes que não são ids válidos ficam de fora
+        assert!(found("SELECT [a b], [], [ç]").is_empty());
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(
+            split_statements("SELECT ';'; -- a;b\nSELECT 2;\n\n"),
+            vec!["SELECT ';'", "-- a;b\nSELECT 2"]
+        );
+        assert_eq!(context_at_end("SELECT 'abc"), Some(Context::StringLiteral));
+        assert_eq!(context_at_end("SELECT 1 -- x"), None);
+        assert_eq!(context_at_end("SELECT 1 /* x */ "), Some(Context::Code));
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub const TELEMETRY_FILE: &str = "telemetria.jsonl";
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES: usize = 3;
+
+pub const STAGE_EXTRACTION: &str = "1. Extração DBISAM (.dat -> Arrow)";
+pub const STAGE_REGISTRATION: &str = "2. Registro DataFusion (Memória Colunar)";
+pub const STAGE_SQL: &str = "3. Execução SQL (DataFusion)";
+pub const STAGE_DISPLAY: &str = "4. Primeira Página na Tela";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TelemetryEvent {
+    pub momento: String,
+    pub relatorio: String,
+    // Hash dos valores dos parâmetros (ver parameters_hash); vazio sem parâmetros
+    #[serde(default)]
+    pub parametros_hash: String,
+    pub etapa: String,
+    pub duracao_ms: u64,
+    #[serde(default)]
+    pub linhas: u64,
+    #[serde(default)]
+    pub bytes: u64,
+    #[serde(default)]
+    pub tabelas: Vec<String>,
+    #[serde(default)]
+    pub colunas: Vec<String>,
+    // Muda quando o schema.toml (layout do ERP) das tabelas usadas muda
+    #[serde(default)]
+    pub schema_hash: String,
+    #[serde(default = "default_success")]
+    pub sucesso: bool,
+    #[serde(default)]
+    pub erro: String,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+impl TelemetryEvent {
+    pub fn new(report_name: &str, stage: &str, duration_ms: u128) -> Self {
+        Self {
+            momento: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            relatorio: report_name.to_string(),
+            etapa: stage.to_string(),
+            duracao_ms: duration_ms as u64,
+            sucesso: true,
+            ..Default::default()
+        }
+    }
+}
+
+// GRAVAÇÃO
+pub fn record(event: &TelemetryEvent) {
+    rotate_if_needed();
+
+    if let Ok(line) = serde_json::to_string(event) {
+        if let Ok(mut file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(TELEMETRY_FILE)
+        {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+fn rotated_path(index: usize) -> PathBuf {
+    PathBuf::from(TELEMETRY_FILE.replace(".jsonl", &format!(".{}.jsonl", index)))
+}
+
+// telemetria.jsonl -> telemetria.1.jsonl -> ... -> telemetria.3.jsonl (descartado)
+fn rotate_if_needed() {
+    let size = fs::metadata(TELEMETRY_FILE).map(|m| m.len()).unwrap_or(0);
+    if size < MAX_FILE_BYTES {
+        return;
+    }
+
+    let _ = fs::remove_file(rotated_path(MAX_ROTATED_FILES));
+    for index in (1..MAX_ROTATED_FILES).rev() {
+        let _ = fs::rename(rotated_path(index), rotated_path(index + 1));
+    }
+    let _ = fs::rename(TELEMETRY_FILE, rotated_path(1));
+}
+
+// Hash estável entre versões (FNV-1a), usado para parâmetros e schema
+pub fn stable_hash(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+// Valores recebidos por apply_parameters, em ordem de id: o mesmo relatório com os
+// mesmos filtros sempre dá o mesmo hash, qualquer que seja o SQL gerado
+pub fn parameters_hash(values: &HashMap<String, String>) -> String {
+    if values.is_empty() {
+        return String::new();
+    }
+    let sorted: BTreeMap<&String, &String> = values.iter().collect();
+    let joined: Vec<String> = sorted
+        .iter()
+        .map(|(id, value)| format!("{}={}", id, value))
+        .collect();
+    stable_hash(&joined.join("\n"))
+}
+
+// LEITURA
+// Eventos do mais antigo para o mais recente, incluindo os arquivos rotacionados
+pub fn read_events() -> Vec<TelemetryEvent> {
+    let mut files: Vec<PathBuf> = (1..=MAX_ROTATED_FILES).rev().map(rotated_path).collect();
+    files.push(PathBuf::from(TELEMETRY_FILE));
+
+    files
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|l| serde_json::from_str::<TelemetryEvent>(l).ok())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageStats {
+    pub relatorio: String,
+    pub etapa: String,
+    pub execucoes: usize,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+    pub linhas_media: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaTrend {
+    pub schema_hash: String,
+    pub desde: String,
+    pub execucoes: usize,
+    pub p50_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportStats {
+    pub relatorio: String,
+    pub execucoes: usize,
+    pub falhas: usize,
+    pub ultima: String,
+    pub etapas: Vec<StageStats>,
+    // Tempo da extração por versão do schema, na ordem em que apareceram
+    pub tendencia: Vec<SchemaTrend>,
+}
+
+pub fn percentile(values: &[u64], p: f64) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let rank = ((p / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn stage_stats(relatorio: &str, etapa: &str, events: &[&TelemetryEvent]) -> StageStats {
+    let durations: Vec<u64> = events.iter().map(|e| e.duracao_ms).collect();
+    let total_rows: u64 = events.iter().map(|e| e.linhas).sum();
+    StageStats {
+        relatorio: relatorio.to_string(),
+        etapa: etapa.to_string(),
+        execucoes: events.len(),
+        p50_ms: percentile(&durations, 50.0),
+        p95_ms: percentile(&durations, 95.0),
+        max_ms: durations.iter().copied().max().unwrap_or(0),
+        linhas_media: total_rows / events.len().max(1) as u64,
+    }
+}
+
+pub fn summarize(events: &[TelemetryEvent]) -> Vec<ReportStats> {
+    let mut by_report: BTreeMap<&str, Vec<&TelemetryEvent>> = BTreeMap::new();
+    for event in events {
+        by_report.entry(&event.relatorio).or_default().push(event);
+    }
+
+    let mut reports: Vec<ReportStats> = by_report
+        .into_iter()
+        .map(|(relatorio, report_events)| {
+            let mut by_stage: BTreeMap<&str, Vec<&TelemetryEvent>> = BTreeMap::new();
+            for event in report_events.iter().filter(|e| e.sucesso) {
+                by_stage.entry(&event.etapa).or_default().push(event);
+            }
+            let etapas = by_stage
+                .into_iter()
+                .map(|(etapa, stage_events)| stage_stats(relatorio, etapa, &stage_events))
+                .collect();
+
+            let mut trend_order: Vec<&str> = Vec::new();
+            let mut trend_durations: HashMap<&str, (String, Vec<u64>)> = HashMap::new();
+            for event in report_events
+                .iter()
+                .filter(|e| e.sucesso && e.etapa == STAGE_EXTRACTION && !e.schema_hash.is_empty())
+            {
+                let entry = trend_durations
+                    .entry(&event.schema_hash)
+                    .or_insert_with(|| {
+                        trend_order.push(&event.schema_hash);
+                        (event.momento.clone(), Vec::new())
+                    });
+                entry.1.push(event.duracao_ms);
+            }
+            let tendencia = trend_order
+                .iter()
+                .filter_map(|hash| {
+                    trend_durations.get(hash).map(|(desde, durations)| SchemaTrend {
+                        schema_hash: hash.to_string(),
+                        desde: desde.clone(),
+                        execucoes: durations.len(),
+                        p50_ms: percentile(durations, 50.0),
+                    })
+                })
+                .collect();
+
+            ReportStats {
+                relatorio: relatorio.to_string(),
+                execucoes: report_events
+                    .iter()
+                    .filter(|e| e.etapa == STAGE_SQL)
+                    .count(),
+                falhas: report_events.iter().filter(|e| !e.sucesso).count(),
+                ultima: report_events
+                    .last()
+                    .map(|e| e.momento.clone())
+                    .unwrap_or_default(),
+                etapas,
+                tendencia,
+            }
+        })
+        .collect();
+
+    reports.sort_by(|a, b| b.ultima.cmp(&a.ultima));
+    reports
+}
+
+// Etapas mais lentas de todos os relatórios, pelo p95
+pub fn slowest_stages(reports: &[ReportStats], limit: usize) -> Vec<StageStats> {
+    let mut stages: Vec<StageStats> = reports
+        .iter()
+        .flat_map(|r| r.etapas.iter().cloned())
+        .collect();
+    stages.sort_by(|a, b| b.p95_ms.cmp(&a.p95_ms));
+    stages.truncate(limit);
+    stages
+}
@@ -0,0 +1,133 @@
+// Acima disso a tabela do LCS fica grande demais e o diff mostra tudo como trocado
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffKind {
+    Same,
+    Removed,
+    Added,
+    Changed,
+}
+
+impl DiffKind {
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            DiffKind::Same => "diff-same",
+            DiffKind::Removed => "diff-removed",
+            DiffKind::Added => "diff-added",
+            DiffKind::Changed => "diff-changed",
+        }
+    }
+}
+
+// Uma linha da visão lado a lado: número e texto de cada lado, quando existe
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffRow {
+    pub left: Option<(usize, String)>,
+    pub right: Option<(usize, String)>,
+    pub kind: DiffKind,
+}
+
+enum Op {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+// Diff por linhas (LCS). Trechos removidos seguidos de adicionados ficam lado a lado
+// como linhas trocadas.
+pub fn side_by_side(old: &str, new: &str) -> Vec<DiffRow> {
+    let left: Vec<&str> = old.lines().collect();
+    let right: Vec<&str> = new.lines().collect();
+
+    let mut rows = Vec::new();
+    let mut removed: Vec<usize> = Vec::new();
+    let mut added: Vec<usize> = Vec::new();
+
+    for op in line_ops(&left, &right) {
+        match op {
+            Op::Removed(i) => removed.push(i),
+            Op::Added(j) => added.push(j),
+            Op::Same(i, j) => {
+                flush(&left, &right, &mut removed, &mut added, &mut rows);
+                rows.push(DiffRow {
+                    left: Some((i + 1, left[i].to_string())),
+                    right: Some((j + 1, right[j].to_string())),
+                    kind: DiffKind::Same,
+                });
+            }
+        }
+    }
+    flush(&left, &right, &mut removed, &mut added, &mut rows);
+    rows
+}
+
+pub fn has_changes(rows: &[DiffRow]) -> bool {
+    rows.iter().any(|r| r.kind != DiffKind::Same)
+}
+
+fn flush(
+    left: &[&str],
+    right: &[&str],
+    removed: &mut Vec<usize>,
+    added: &mut Vec<usize>,
+    rows: &mut Vec<DiffRow>,
+) {
+    let paired = removed.len().max(added.len());
+    for k in 0..paired {
+        let l = removed.get(k).map(|&i| (i + 1, left[i].to_string()));
+        let r = added.get(k).map(|&j| (j + 1, right[j].to_string()));
+        let kind = match (&l, &r) {
+            (Some(_), Some(_)) => DiffKind::Changed,
+            (Some(_), None) => DiffKind::Removed,
+            _ => DiffKind::Added,
+        };
+        rows.push(DiffRow {
+            left: l,
+            right: r,
+            kind,
+        });
+    }
+    removed.clear();
+    added.clear();
+}
+
+fn line_ops(left: &[&str], right: &[&str]) -> Vec<Op> {
+    let (n, m) = (left.len(), right.len());
+    if n * m > MAX_LCS_CELLS {
+        let mut ops: Vec<Op> = (0..n).map(Op::Removed).collect();
+        ops.extend((0..m).map(Op::Added));
+        return ops;
+    }
+
+    // lcs[i][j] = tamanho da maior subsequência comum de left[i..] e right[j..]
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if left[i] == right[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if left[i] == right[j] {
+            ops.push(Op::Same(i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(Op::Removed(i));
+            i += 1;
+        } else {
+            ops.push(Op::Added(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..n).map(Op::Removed));
+    ops.extend((j..m).map(Op::Added));
+    ops
+}
@@ -0,0 +1,202 @@
+use crate::core::history::history_dir;
+use crate::core::run_history;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// relatorios/.lixeira: os arquivos excluídos e o manifesto com a origem de cada um
+pub const TRASH_DIR: &str = ".lixeira";
+const MANIFEST_FILE: &str = "manifesto.json";
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    // Nome do arquivo dentro da lixeira
+    pub id: String,
+    // Relativo à pasta de relatórios, com '/' como separador
+    pub caminho_original: String,
+    pub excluido_em: String,
+    #[serde(default)]
+    pub tem_historico: bool,
+}
+
+// Mais recente primeiro
+pub fn list_trash(reports_dir: &Path) -> Vec<TrashEntry> {
+    let mut entries = read_manifest(reports_dir);
+    entries.reverse();
+    entries
+}
+
+// Só aceita arquivos .json de relatório dentro da pasta de relatórios
+pub fn move_to_trash(reports_dir: &Path, report_path: &Path) -> Result<TrashEntry, String> {
+    let relative = relative_report_path(reports_dir, report_path)?;
+    let dir = trash_dir(reports_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Erro ao criar {}: {}", dir.display(), e))?;
+
+    let now = Local::now();
+    let stem = report_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let id = format!("{}-{}.json", now.format("%Y%m%d-%H%M%S-%3f"), stem);
+
+    let trashed = dir.join(&id);
+    fs::rename(report_path, &trashed)
+        .map_err(|e| format!("Erro ao mover {} para a lixeira: {}", relative, e))?;
+    // Favoritos e execuções acompanham o arquivo e voltam com ele na restauração
+    let _ = run_history::report_moved(&report_path.to_string_lossy(), &trashed.to_string_lossy());
+
+    let history = history_dir(report_path);
+    let tem_historico = history.is_dir() && fs::rename(&history, history_copy(&dir, &id)).is_ok();
+
+    let entry = TrashEntry {
+        id,
+        caminho_original: relative,
+        excluido_em: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+        tem_historico,
+    };
+    let mut manifest = read_manifest(reports_dir);
+    manifest.push(entry.clone());
+    write_manifest(reports_dir, &manifest)?;
+    Ok(entry)
+}
+
+// Devolve o relatório ao caminho original. Se já existir outro com o mesmo nome,
+// restaura como "nome (restaurado).json". Retorna o caminho usado.
+pub fn restore(reports_dir: &Path, id: &str) -> Result<PathBuf, String> {
+    let mut manifest = read_manifest(reports_dir);
+    let pos = manifest
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or_else(|| format!("Item {} não está na lixeira.", id))?;
+    let entry = manifest[pos].clone();
+
+    let original = entry
+        .caminho_original
+        .split('/')
+        .fold(reports_dir.to_path_buf(), |path, part| path.join(part));
+    let target = free_path(&original);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Erro ao criar {}: {}", parent.display(), e))?;
+    }
+
+    let dir = trash_dir(reports_dir);
+    let trashed = dir.join(&entry.id);
+    fs::rename(&trashed, &target)
+        .map_err(|e| format!("Erro ao restaurar {}: {}", entry.caminho_original, e))?;
+    let _ = run_history::report_moved(&trashed.to_string_lossy(), &target.to_string_lossy());
+    if entry.tem_historico && !history_dir(&target).exists() {
+        let _ = fs::create_dir_all(history_dir(&target).parent().unwrap_or(reports_dir));
+        let _ = fs::rename(history_copy(&dir, &entry.id), history_dir(&target));
+    }
+
+    manifest.remove(pos);
+    write_manifest(reports_dir, &manifest)?;
+    Ok(target)
+}
+
+// Apaga de vez um item (e o histórico dele)
+pub fn purge(reports_dir: &Path, id: &str) -> Result<(), String> {
+    let mut manifest = read_manifest(reports_dir);
+    let pos = manifest
+        .iter()
+        .position(|e| e.id == id)
+        .ok_or_else(|| format!("Item {} não está na lixeira.", id))?;
+
+    let dir = trash_dir(reports_dir);
+    let file = dir.join(id);
+    if file.exists() {
+        fs::remove_file(&file).map_err(|e| format!("Erro ao excluir {}: {}", id, e))?;
+    }
+    let _ = fs::remove_dir_all(history_copy(&dir, id));
+
+    manifest.remove(pos);
+    write_manifest(reports_dir, &manifest)
+}
+
+pub fn empty_trash(reports_dir: &Path) -> Result<(), String> {
+    for entry in read_manifest(reports_dir) {
+        purge(reports_dir, &entry.id)?;
+    }
+    Ok(())
+}
+
+// Caminho de um arquivo que está na lixeira (favoritos e execuções dele ficam ocultos)
+pub fn in_trash(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .any(|c| c.as_os_str() == TRASH_DIR)
+}
+
+fn trash_dir(reports_dir: &Path) -> PathBuf {
+    reports_dir.join(TRASH_DIR)
+}
+
+fn history_copy(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.history", id.trim_end_matches(".json")))
+}
+
+fn relative_report_path(reports_dir: &Path, report_path: &Path) -> Result<String, String> {
+    let invalid = || {
+        format!(
+            "Selecione um relatório para excluir: {}",
+            report_path.display()
+        )
+    };
+    if !report_path.is_file() || report_path.extension().is_none_or(|ext| ext != "json") {
+        return Err(invalid());
+    }
+
+    let root = fs::canonicalize(reports_dir).map_err(|e| e.to_string())?;
+    let file = fs::canonicalize(report_path).map_err(|e| e.to_string())?;
+    let relative = file.strip_prefix(&root).map_err(|_| invalid())?;
+    let hidden = relative
+        .components()
+        .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    if hidden {
+        return Err(invalid());
+    }
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn free_path(original: &Path) -> PathBuf {
+    if !original.exists() {
+        return original.to_path_buf();
+    }
+    let stem = original
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let parent = original.parent().unwrap_or(Path::new("."));
+    let mut n = 1;
+    loop {
+        let name = if n == 1 {
+            format!("{} (restaurado).json", stem)
+        } else {
+            format!("{} (restaurado {}).json", stem, n)
+        };
+        let candidate = parent.join(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+fn read_manifest(reports_dir: &Path) -> Vec<TrashEntry> {
+    fs::read_to_string(trash_dir(reports_dir).join(MANIFEST_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_manifest(reports_dir: &Path, entries: &[TrashEntry]) -> Result<(), String> {
+    let path = trash_dir(reports_dir).join(MANIFEST_FILE);
+    let json = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &json).map_err(|e| format!("Erro ao gravar {}: {}", path.display(), e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Erro ao gravar {}: {}", path.display(), e))
+}
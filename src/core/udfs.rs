@@ -0,0 +1,727 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use datafusion::arrow::array::{
+    Array, ArrayRef, AsArray, BooleanArray, Date32Array, Int64Array, StringArray,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{DataType, Date32Type, Float64Type, Int64Type};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::logical_expr::function::AccumulatorArgs;
+use datafusion::logical_expr::{
+    Accumulator, AggregateUDF, AggregateUDFImpl, ColumnarValue, ScalarUDF, ScalarUDFImpl,
+    Signature, Volatility,
+};
+use datafusion::prelude::SessionContext;
+use datafusion::scalar::ScalarValue;
+use std::any::Any;
+use std::sync::Arc;
+
+// Dias entre 0001-01-01 (base das datas DBISAM) e 1970-01-01 (base do Arrow)
+const DBISAM_EPOCH_OFFSET: i32 = 719163;
+
+// Catálogo exibido na documentação e nas sugestões do editor
+pub const BUILTIN_FUNCTIONS: &[(&str, &str)] = &[
+    ("somente_digitos(texto)", "Remove tudo que não for dígito."),
+    ("format_cpf(valor)", "Formata como 000.000.000-00 (completa zeros à esquerda)."),
+    ("format_cnpj(valor)", "Formata como 00.000.000/0000-00, inclusive CNPJ alfanumérico."),
+    ("format_cpf_cnpj(valor)", "Escolhe CPF ou CNPJ pelo tamanho do documento."),
+    ("is_valid_cpf(valor)", "Valida os dígitos verificadores do CPF."),
+    ("is_valid_cnpj(valor)", "Valida os dígitos verificadores do CNPJ (numérico ou alfanumérico)."),
+    ("unaccent(texto)", "Remove acentos e cedilha."),
+    ("mes_extenso(data | mes)", "Nome do mês em português (Janeiro..Dezembro)."),
+    ("is_feriado(data)", "Verdadeiro em feriados nacionais, incluindo a Sexta-feira Santa."),
+    ("dias_uteis(inicio, fim)", "Dias úteis no intervalo fechado, sem fins de semana e feriados nacionais."),
+    ("format_brl(valor)", "Formata como moeda: R$ 1.234,56."),
+    ("total_brl(valor)", "Agregação: soma em centavos exatos e formata como R$ 1.234,56."),
+    ("chave_nfe(uf, aamm, cnpj, modelo, serie, numero, tp_emis, codigo)", "Monta a chave de acesso de 44 dígitos com o DV."),
+    ("dbisam_data(dias)", "Converte o inteiro de data do DBISAM em DATE."),
+    ("dbisam_hora(ms)", "Converte milissegundos desde a meia-noite em HH:MM:SS."),
+];
+
+type UdfFn = fn(&[ArrayRef]) -> Result<ArrayRef>;
+
+#[derive(Debug)]
+struct BrazilUdf {
+    name: &'static str,
+    signature: Signature,
+    return_type: DataType,
+    fun: UdfFn,
+}
+
+impl BrazilUdf {
+    fn new(name: &'static str, arg_count: usize, return_type: DataType, fun: UdfFn) -> ScalarUDF {
+        ScalarUDF::new_from_impl(Self {
+            name,
+            signature: Signature::any(arg_count, Volatility::Immutable),
+            return_type,
+            fun,
+        })
+    }
+}
+
+impl ScalarUDFImpl for BrazilUdf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(self.return_type.clone())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        let array_len = args.iter().find_map(|arg| match arg {
+            ColumnarValue::Array(array) => Some(array.len()),
+            ColumnarValue::Scalar(_) => None,
+        });
+        let all_scalars = array_len.is_none();
+        let len = array_len.unwrap_or(1);
+
+        let arrays = args
+            .iter()
+            .map(|arg| match arg {
+                ColumnarValue::Array(array) => Ok(array.clone()),
+                ColumnarValue::Scalar(scalar) => scalar.to_array_of_size(len),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let result = (self.fun)(&arrays)?;
+
+        if all_scalars {
+            Ok(ColumnarValue::Scalar(ScalarValue::try_from_array(&result, 0)?))
+        } else {
+            Ok(ColumnarValue::Array(result))
+        }
+    }
+}
+
+pub fn register_all(ctx: &SessionContext) {
+    let udfs = [
+        BrazilUdf::new("somente_digitos", 1, DataType::Utf8, udf_only_digits),
+        BrazilUdf::new("format_cpf", 1, DataType::Utf8, udf_format_cpf),
+        BrazilUdf::new("format_cnpj", 1, DataType::Utf8, udf_format_cnpj),
+        BrazilUdf::new("format_cpf_cnpj", 1, DataType::Utf8, udf_format_cpf_cnpj),
+        BrazilUdf::new("is_valid_cpf", 1, DataType::Boolean, udf_is_valid_cpf),
+        BrazilUdf::new("is_valid_cnpj", 1, DataType::Boolean, udf_is_valid_cnpj),
+        BrazilUdf::new("unaccent", 1, DataType::Utf8, udf_unaccent),
+        BrazilUdf::new("mes_extenso", 1, DataType::Utf8, udf_month_name),
+        BrazilUdf::new("is_feriado", 1, DataType::Boolean, udf_is_holiday),
+        BrazilUdf::new("dias_uteis", 2, DataType::Int64, udf_business_days),
+        BrazilUdf::new("format_brl", 1, DataType::Utf8, udf_format_brl),
+        BrazilUdf::new("chave_nfe", 8, DataType::Utf8, udf_nfe_key),
+        BrazilUdf::new("dbisam_data", 1, DataType::Date32, udf_dbisam_date),
+        BrazilUdf::new("dbisam_hora", 1, DataType::Utf8, udf_dbisam_time),
+    ];
+
+    for udf in udfs {
+        ctx.register_udf(udf);
+    }
+    ctx.register_udaf(AggregateUDF::new_from_impl(TotalBrl {
+        signature: Signature::any(1, Volatility::Immutable),
+    }));
+}
+
+// AGREGAÇÃO
+// total_brl(valor): cada valor é arredondado para centavos e somado como inteiro, sem
+// o erro acumulado do SUM em float. Só nulos (ou nenhuma linha) resultam em NULL.
+#[derive(Debug)]
+struct TotalBrl {
+    signature: Signature,
+}
+
+impl AggregateUDFImpl for TotalBrl {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        "total_brl"
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Utf8)
+    }
+
+    fn accumulator(&self, _acc_args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::<CentsAccumulator>::default())
+    }
+
+    // Estado parcial trocado entre partições: o total em centavos
+    fn state_type(&self, _return_type: &DataType) -> Result<Vec<DataType>> {
+        Ok(vec![DataType::Int64])
+    }
+}
+
+#[derive(Debug, Default)]
+struct CentsAccumulator {
+    cents: Option<i64>,
+}
+
+impl CentsAccumulator {
+    fn add(&mut self, cents: i64) -> Result<()> {
+        let total = self.cents.unwrap_or(0).checked_add(cents).ok_or_else(|| {
+            DataFusionError::Execution("total_brl: soma fora do limite".to_string())
+        })?;
+        self.cents = Some(total);
+        Ok(())
+    }
+}
+
+impl Accumulator for CentsAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let values = cast(&values[0], &DataType::Float64)?;
+        for value in values.as_primitive::<Float64Type>().iter().flatten() {
+            self.add((value * 100.0).round() as i64)?;
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        for cents in states[0].as_primitive::<Int64Type>().iter().flatten() {
+            self.add(cents)?;
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Int64(self.cents)])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Utf8(self.cents.map(format_brl_cents)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+// REGRAS DE NEGÓCIO (funções puras, usadas pelas UDFs)
+pub fn only_digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+fn document_chars(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn pad_document(value: &str, size: usize) -> Option<String> {
+    let chars = document_chars(value);
+    if chars.is_empty() || chars.len() > size {
+        return None;
+    }
+    if chars.len() < size && chars.chars().all(|c| c.is_ascii_digit()) {
+        return Some(format!("{:0>width$}", chars, width = size));
+    }
+    (chars.len() == size).then_some(chars)
+}
+
+pub fn format_cpf(value: &str) -> Option<String> {
+    let d = pad_document(value, 11)?;
+    if !d.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}.{}.{}-{}", &d[0..3], &d[3..6], &d[6..9], &d[9..11]))
+}
+
+pub fn format_cnpj(value: &str) -> Option<String> {
+    let d = pad_document(value, 14)?;
+    Some(format!(
+        "{}.{}.{}/{}-{}",
+        &d[0..2],
+        &d[2..5],
+        &d[5..8],
+        &d[8..12],
+        &d[12..14]
+    ))
+}
+
+pub fn format_cpf_cnpj(value: &str) -> Option<String> {
+    if document_chars(value).len() <= 11 {
+        format_cpf(value)
+    } else {
+        format_cnpj(value)
+    }
+}
+
+// Valor de cada caractere no cálculo do DV: dígitos valem 0-9 e letras
+// seguem a tabela ASCII - 48 adotada para o CNPJ alfanumérico
+fn char_value(c: char) -> u32 {
+    (c as u32).saturating_sub(48)
+}
+
+fn mod11_digit(chars: &str, weights: &[u32]) -> u32 {
+    let sum: u32 = chars
+        .chars()
+        .zip(weights)
+        .map(|(c, w)| char_value(c) * w)
+        .sum();
+    let rest = sum % 11;
+    if rest < 2 { 0 } else { 11 - rest }
+}
+
+pub fn is_valid_cpf(value: &str) -> bool {
+    let Some(d) = pad_document(value, 11) else {
+        return false;
+    };
+    if !d.chars().all(|c| c.is_ascii_digit()) || d.chars().all(|c| Some(c) == d.chars().next()) {
+        return false;
+    }
+
+    let dv1 = mod11_digit(&d[0..9], &[10, 9, 8, 7, 6, 5, 4, 3, 2]);
+    let dv2 = mod11_digit(&d[0..10], &[11, 10, 9, 8, 7, 6, 5, 4, 3, 2]);
+    d[9..11] == format!("{}{}", dv1, dv2)
+}
+
+pub fn is_valid_cnpj(value: &str) -> bool {
+    let Some(d) = pad_document(value, 14) else {
+        return false;
+    };
+    if !d[12..14].chars().all(|c| c.is_ascii_digit()) || d.chars().all(|c| Some(c) == d.chars().next())
+    {
+        return false;
+    }
+
+    let dv1 = mod11_digit(&d[0..12], &[5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2]);
+    let dv2 = mod11_digit(&d[0..13], &[6, 5, 4, 3, 2, 9, 8, 7, 6, 5, 4, 3, 2]);
+    d[12..14] == format!("{}{}", dv1, dv2)
+}
+
+pub fn unaccent(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' | 'ª' => 'a',
+            'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'É' | 'È' | 'Ê' | 'Ë' => 'E',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' | 'º' => 'o',
+            'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+            'ç' => 'c',
+            'Ç' => 'C',
+            'ñ' => 'n',
+            'Ñ' => 'N',
+            other => other,
+        })
+        .collect()
+}
+
+pub fn month_name(month: u32) -> Option<&'static str> {
+    const MONTHS: [&str; 12] = [
+        "Janeiro", "Fevereiro", "Março", "Abril", "Maio", "Junho", "Julho", "Agosto",
+        "Setembro", "Outubro", "Novembro", "Dezembro",
+    ];
+    MONTHS.get(month.checked_sub(1)? as usize).copied()
+}
+
+// Algoritmo de Meeus/Jones/Butcher para o domingo de Páscoa
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+// Feriados nacionais de lei; Carnaval e Corpus Christi são ponto facultativo
+pub fn is_national_holiday(date: NaiveDate) -> bool {
+    let fixed = matches!(
+        (date.month(), date.day()),
+        (1, 1) | (4, 21) | (5, 1) | (9, 7) | (10, 12) | (11, 2) | (11, 15) | (12, 25)
+    );
+    let black_consciousness = date.year() >= 2024 && (date.month(), date.day()) == (11, 20);
+    let good_friday = easter_sunday(date.year())
+        .map(|easter| easter - Duration::days(2) == date)
+        .unwrap_or(false);
+
+    fixed || black_consciousness || good_friday
+}
+
+pub fn business_days(start: NaiveDate, end: NaiveDate) -> i64 {
+    let (from, to, sign) = if start <= end {
+        (start, end, 1)
+    } else {
+        (end, start, -1)
+    };
+
+    let count = from
+        .iter_days()
+        .take_while(|d| *d <= to)
+        .filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun) && !is_national_holiday(*d))
+        .count() as i64;
+
+    count * sign
+}
+
+pub fn format_brl(value: f64) -> String {
+    format_brl_cents((value * 100.0).round() as i64)
+}
+
+pub fn format_brl_cents(cents: i64) -> String {
+    let integer = (cents.unsigned_abs() / 100).to_string();
+    let mut grouped = String::with_capacity(integer.len() + integer.len() / 3);
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push('.');
+        }
+        grouped.push(c);
+    }
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}R$ {},{:02}", sign, grouped, cents.unsigned_abs() % 100)
+}
+
+pub fn nfe_access_key(parts: [&str; 8]) -> Option<String> {
+    const SIZES: [usize; 8] = [2, 4, 14, 2, 3, 9, 1, 8];
+
+    let mut key = String::with_capacity(44);
+    for (part, size) in parts.iter().zip(SIZES) {
+        let chars = document_chars(part);
+        if chars.is_empty() || chars.len() > size {
+            return None;
+        }
+        key.push_str(&format!("{:0>width$}", chars, width = size));
+    }
+
+    let sum: u32 = key
+        .chars()
+        .rev()
+        .enumerate()
+        .map(|(i, c)| char_value(c) * (2 + (i as u32 % 8)))
+        .sum();
+    let rest = sum % 11;
+    let dv = if rest < 2 { 0 } else { 11 - rest };
+    key.push_str(&dv.to_string());
+    Some(key)
+}
+
+pub fn dbisam_time(ms: i64) -> Option<String> {
+    if !(0..86_400_000).contains(&ms) {
+        return None;
+    }
+    let secs = ms / 1000;
+    Some(format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60))
+}
+
+fn date_from_days(days: i32) -> Option<NaiveDate> {
+    NaiveDate::from_num_days_from_ce_opt(days + DBISAM_EPOCH_OFFSET)
+}
+
+// ADAPTADORES ARROW
+fn map_text<F>(array: &ArrayRef, f: F) -> Result<ArrayRef>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let values = cast(array, &DataType::Utf8)?;
+    let out: StringArray = values.as_string::<i32>().iter().map(|v| v.and_then(&f)).collect();
+    Ok(Arc::new(out))
+}
+
+fn map_text_bool<F>(array: &ArrayRef, f: F) -> Result<ArrayRef>
+where
+    F: Fn(&str) -> bool,
+{
+    let values = cast(array, &DataType::Utf8)?;
+    let out: BooleanArray = values.as_string::<i32>().iter().map(|v| v.map(&f)).collect();
+    Ok(Arc::new(out))
+}
+
+fn dates(array: &ArrayRef) -> Result<Vec<Option<NaiveDate>>> {
+    let values = cast(array, &DataType::Date32)?;
+    Ok(values
+        .as_primitive::<Date32Type>()
+        .iter()
+        .map(|v| v.and_then(date_from_days))
+        .collect())
+}
+
+fn udf_only_digits(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_text(&args[0], |v| Some(only_digits(v)))
+}
+
+fn udf_format_cpf(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_text(&args[0], |v| format_cpf(v).or_else(|| Some(v.to_string())))
+}
+
+fn udf_format_cnpj(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_text(&args[0], |v| format_cnpj(v).or_else(|| Some(v.to_string())))
+}
+
+fn udf_format_cpf_cnpj(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_text(&args[0], |v| format_cpf_cnpj(v).or_else(|| Some(v.to_string())))
+}
+
+fn udf_is_valid_cpf(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_text_bool(&args[0], is_valid_cpf)
+}
+
+fn udf_is_valid_cnpj(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_text_bool(&args[0], is_valid_cnpj)
+}
+
+fn udf_unaccent(args: &[ArrayRef]) -> Result<ArrayRef> {
+    map_text(&args[0], |v| Some(unaccent(v)))
+}
+
+fn udf_month_name(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let months: Vec<Option<u32>> = if args[0].data_type().is_integer() {
+        let values = cast(&args[0], &DataType::Int64)?;
+        values
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|v| v.and_then(|m| u32::try_from(m).ok()))
+            .collect()
+    } else {
+        dates(&args[0])?.into_iter().map(|d| d.map(|d| d.month())).collect()
+    };
+
+    let out: StringArray = months.into_iter().map(|m| m.and_then(month_name)).collect();
+    Ok(Arc::new(out))
+}
+
+fn udf_is_holiday(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let out: BooleanArray = dates(&args[0])?
+        .into_iter()
+        .map(|d| d.map(is_national_holiday))
+        .collect();
+    Ok(Arc::new(out))
+}
+
+fn udf_business_days(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let starts = dates(&args[0])?;
+    let ends = dates(&args[1])?;
+    let out: Int64Array = starts
+        .into_iter()
+        .zip(ends)
+        .map(|(s, e)| Some(business_days(s?, e?)))
+        .collect();
+    Ok(Arc::new(out))
+}
+
+fn udf_format_brl(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = cast(&args[0], &DataType::Float64)?;
+    let out: StringArray = values
+        .as_primitive::<Float64Type>()
+        .iter()
+        .map(|v| v.map(format_brl))
+        .collect();
+    Ok(Arc::new(out))
+}
+
+fn udf_nfe_key(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let columns = args
+        .iter()
+        .map(|a| cast(a, &DataType::Utf8))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let columns: Vec<&StringArray> = columns.iter().map(|c| c.as_string::<i32>()).collect();
+
+    let out: StringArray = (0..args[0].len())
+        .map(|row| {
+            let mut parts = [""; 8];
+            for (part, column) in parts.iter_mut().zip(&columns) {
+                if column.is_null(row) {
+                    return None;
+                }
+                *part = column.value(row);
+            }
+            nfe_access_key(parts)
+        })
+        .collect();
+    Ok(Arc::new(out))
+}
+
+fn udf_dbisam_date(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = cast(&args[0], &DataType::Int64)?;
+    let out: Date32Array = values
+        .as_primitive::<Int64Type>()
+        .iter()
+        .map(|v| {
+            v.filter(|days| *days > 0)
+                .and_then(|days| i32::try_from(days).ok())
+                .map(|days| days - DBISAM_EPOCH_OFFSET)
+        })
+        .collect();
+    Ok(Arc::new(out))
+}
+
+fn udf_dbisam_time(args: &[ArrayRef]) -> Result<ArrayRef> {
+    let values = cast(&args[0], &DataType::Int64)?;
+    let out: StringArray = values
+        .as_primitive::<Int64Type>()
+        .iter()
+        .map(|v| v.and_then(dbisam_time))
+        .collect();
+    Ok(Arc::new(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datafusion::arrow::array::Float64Array;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn cpf() {
+        assert!(is_valid_cpf("529.982.247-25"));
+        assert!(is_valid_cpf("52998224725"));
+        assert!(!is_valid_cpf("529.982.247-24"));
+        assert!(!is_valid_cpf("111.111.111-11"));
+        assert!(!is_valid_cpf("00000000000"));
+        assert!(!is_valid_cpf("1234567890123"));
+        assert!(!is_valid_cpf(""));
+        assert_eq!(format_cpf("52998224725").as_deref(), Some("529.982.247-25"));
+        // Zeros à esquerda perdidos em colunas numéricas
+        assert_eq!(format_cpf("1234567890").as_deref(), Some("012.345.678-90"));
+    }
+
+    #[test]
+    fn cnpj() {
+        assert!(is_valid_cnpj("11.222.333/0001-81"));
+        assert!(is_valid_cnpj("11222333000181"));
+        assert!(!is_valid_cnpj("11.222.333/0001-80"));
+        assert!(!is_valid_cnpj("11111111111111"));
+        assert!(!is_valid_cnpj("00.000.000/0000-00"));
+        assert!(!is_valid_cnpj("1122233300018"));
+        // CNPJ alfanumérico (exemplo da Receita)
+        assert!(is_valid_cnpj("12.ABC.345/01DE-35"));
+        assert!(!is_valid_cnpj("12.ABC.345/01DE-36"));
+        assert_eq!(
+            format_cpf_cnpj("11222333000181").as_deref(),
+            Some("11.222.333/0001-81")
+        );
+    }
+
+    #[test]
+    fn brl() {
+        assert_eq!(format_brl(0.0), "R$ 0,00");
+        assert_eq!(format_brl(100.0), "R$ 100,00");
+        assert_eq!(format_brl(1000.0), "R$ 1.000,00");
+        assert_eq!(format_brl(1234567.891), "R$ 1.234.567,89");
+        assert_eq!(format_brl(10.126), "R$ 10,13");
+        assert_eq!(format_brl(-1.5), "-R$ 1,50");
+        assert_eq!(format_brl(-1234.5), "-R$ 1.234,50");
+        // Negativo que arredonda para zero não leva sinal
+        assert_eq!(format_brl(-0.004), "R$ 0,00");
+        assert_eq!(format_brl_cents(-123456), "-R$ 1.234,56");
+    }
+
+    #[test]
+    fn total_brl() {
+        let mut left = CentsAccumulator::default();
+        assert_eq!(left.evaluate().unwrap(), ScalarValue::Utf8(None));
+
+        let values: ArrayRef = Arc::new(Float64Array::from(vec![Some(0.1), None, Some(0.2)]));
+        left.update_batch(&[values]).unwrap();
+        let mut right = CentsAccumulator::default();
+        right
+            .update_batch(&[Arc::new(Int64Array::from(vec![1000, 234])) as ArrayRef])
+            .unwrap();
+
+        // Estados parciais de duas partições somados
+        let states = [left.state().unwrap(), right.state().unwrap()];
+        let cents: ArrayRef = Arc::new(Int64Array::from(
+            states
+                .iter()
+                .map(|s| match &s[0] {
+                    ScalarValue::Int64(c) => *c,
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        ));
+        let mut total = CentsAccumulator::default();
+        total.merge_batch(&[cents]).unwrap();
+        assert_eq!(
+            total.evaluate().unwrap(),
+            ScalarValue::Utf8(Some("R$ 1.234,30".to_string()))
+        );
+
+        let mut overflow = CentsAccumulator {
+            cents: Some(i64::MAX),
+        };
+        assert!(overflow.add(1).is_err());
+    }
+
+    #[test]
+    fn easter_and_holidays() {
+        assert_eq!(easter_sunday(2024), Some(date(2024, 3, 31)));
+        assert_eq!(easter_sunday(2025), Some(date(2025, 4, 20)));
+        assert_eq!(easter_sunday(2038), Some(date(2038, 4, 25)));
+        assert!(is_national_holiday(date(2024, 3, 29)));
+        assert!(!is_national_holiday(date(2024, 3, 31)));
+        assert!(is_national_holiday(date(2024, 11, 20)));
+        assert!(!is_national_holiday(date(2023, 11, 20)));
+        assert!(is_national_holiday(date(2025, 12, 25)));
+    }
+
+    #[test]
+    fn business_day_count() {
+        // Sexta-feira Santa
+        assert_eq!(business_days(date(2024, 3, 25), date(2024, 3, 29)), 4);
+        // Consciência Negra só a partir de 2024; 15/11 todo ano
+        assert_eq!(business_days(date(2024, 11, 18), date(2024, 11, 22)), 4);
+        assert_eq!(business_days(date(2023, 11, 20), date(2023, 11, 24)), 5);
+        assert_eq!(business_days(date(2023, 11, 13), date(2023, 11, 17)), 4);
+        assert_eq!(business_days(date(2024, 3, 29), date(2024, 3, 25)), -4);
+        assert_eq!(business_days(date(2024, 3, 30), date(2024, 3, 31)), 0);
+        assert_eq!(business_days(date(2024, 4, 1), date(2024, 4, 1)), 1);
+    }
+
+    #[test]
+    fn nfe_key() {
+        let key = nfe_access_key([
+            "35", "2401", "11222333000181", "55", "1", "1234", "1", "1234",
+        ]);
+        assert_eq!(
+            key.as_deref(),
+            Some("35240111222333000181550010000012341000012349")
+        );
+        assert_eq!(key.unwrap().len(), 44);
+        assert_eq!(
+            nfe_access_key(["35", "2401", "11222333000181", "55", "1", "1234", "12", "1"]),
+            None
+        );
+        assert_eq!(
+            nfe_access_key(["35", "2401", "", "55", "1", "1234", "1", "1"]),
+            None
+        );
+    }
+
+    #[test]
+    fn time_of_day() {
+        assert_eq!(dbisam_time(0).as_deref(), Some("00:00:00"));
+        assert_eq!(dbisam_time(45_296_000).as_deref(), Some("12:34:56"));
+        assert_eq!(dbisam_time(86_399_999).as_deref(), Some("23:59:59"));
+        assert_eq!(dbisam_time(86_400_000), None);
+        assert_eq!(dbisam_time(-1), None);
+    }
+}
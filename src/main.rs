@@ -1,12 +1,24 @@
 #![windows_subsystem = "windows"]
 #![allow(non_snake_case)]
 
+mod cli;
 mod core;
 mod views;
 mod components {
+    pub mod agenda_modal;
+    pub mod confirm_modal;
+    pub mod history_modal;
+    pub mod performance_modal;
+    pub mod plan_modal;
+    pub mod preview_modal;
+    pub mod prompt_modal;
+    pub mod schema_browser;
+    pub mod sql_editor;
     pub mod status_modal;
+    pub mod trash_modal;
 }
 use crate::core::engine::DataEngine;
+use crate::core::run_history::ReportRunEntry;
 use dioxus::desktop::{Config, WindowBuilder};
 use dioxus::prelude::*;
 use std::path::Path;
@@ -37,6 +49,12 @@ fn main() {
 
     dotenvy::dotenv().ok();
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(exit_code) = cli::dispatch(&args) {
+        std::process::exit(exit_code);
+    }
+    cli::start_background_services();
+
     let config = Config::new().with_window(
         WindowBuilder::new()
             .with_title("NewREPORT - Agro Zecão | Powered by DataFusion")
@@ -53,6 +71,8 @@ fn App() -> Element {
 
     let mut engine_signal = use_signal(|| DataEngine::new_empty());
     let current_sql_signal = use_signal(|| String::new());
+    // Execução aberta pela Home; a tela do relatório completa com as linhas e grava
+    let pending_run_signal = use_signal(|| None::<ReportRunEntry>);
 
     use_future(move || async move {
         if !is_loaded() {
@@ -101,14 +121,16 @@ fn App() -> Element {
                     }
                 },
                 engine: engine_signal,
-                current_sql: current_sql_signal
+                current_sql: current_sql_signal,
+                pending_run: pending_run_signal
             }
         },
         Route::ViewReport => rsx! {
             ViewReport {
                 on_back: move |_: MouseEvent| current_route.set(Route::Home),
                 engine: engine_signal,
-                query_sql: current_sql_signal()
+                query_sql: current_sql_signal(),
+                pending_run: pending_run_signal
             }
         },
         Route::EditQuery => rsx! {
@@ -1,28 +1,32 @@
+use crate::components::history_modal::HistoryModal;
+use crate::components::plan_modal::PlanModal;
+use crate::components::schema_browser::SchemaBrowser;
+use crate::components::sql_editor::SqlEditor;
 use crate::components::status_modal::{StatusModal, StatusType};
-use crate::core::engine::DataEngine;
+use crate::core::engine::{
+    DataEngine, arrow_field_for, new_session_context, read_first_rows, register_external_source,
+};
+use crate::core::history::{ReportVersion, file_saved_at, move_history, record_version};
+use crate::core::param_lint::{LintIssue, Severity, errors_summary, has_errors, lint_parameters};
+use crate::core::preview::{SAMPLE_SIZES, SampleResult};
+use crate::core::query_plan::{PlanNode, parse_plan};
+use crate::core::report::{
+    ReportDefinition, ReportMetadata, ReportParameter, load_report, parameter_edits, parse_tags,
+    stamp_metadata,
+};
+use crate::core::report_format::{CURRENT_FORMAT_VERSION, save_report};
+use crate::core::run_history;
+use crate::core::sql_diagnostics::{SqlDiagnostic, locate_error, strip_sync_tags};
+use crate::core::sql_format::{FormatOptions, KeywordCase, format_sql};
+use crate::core::sql_text::{MappedSql, statement_ranges};
+use datafusion::arrow::datatypes::Schema as ArrowSchema;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::datasource::MemTable;
 use dioxus::prelude::*;
-use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::fs;
 use std::path::{Path, PathBuf};
-
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
-pub struct ReportParameter {
-    pub id: String,
-    pub nome: String,
-    pub tipo: String,
-    pub valor_padrao: String,
-    pub requerido: bool,
-    #[serde(default)]
-    pub extra: String,
-}
-
-#[derive(Deserialize, Serialize, Debug, Clone)]
-struct ReportData {
-    descricao: String,
-    query_sql: String,
-    #[serde(default)]
-    parametros: Vec<ReportParameter>,
-}
+use std::sync::Arc;
 
 #[derive(PartialEq, Clone, Copy)]
 enum EditorTab {
@@ -31,14 +35,66 @@ enum EditorTab {
     Sql,
 }
 
+// PARÂMETROS
+// Testes e plano de execução usam o valor padrão de cada parâmetro. O SQL devolvido
+// guarda a posição de origem de cada trecho para apontar erros no editor.
+fn bind_with_defaults(sql: &str, params: &[ReportParameter]) -> Result<MappedSql, String> {
+    // Só conta o [id] que o scanner acha no código ou em strings, nunca em comentários
+    let empty_default = RefCell::new(None);
+    let edits = parameter_edits(sql, |id| {
+        let param = params.iter().find(|p| p.id == id)?;
+        if param.valor_padrao.trim().is_empty() {
+            empty_default
+                .borrow_mut()
+                .get_or_insert_with(|| param.id.clone());
+        }
+        Some((param, param.valor_padrao.as_str()))
+    })
+    .map_err(|e| format!("Erro de Validação: {}", e))?;
+    if let Some(id) = empty_default.into_inner() {
+        return Err(format!(
+            "Erro de Validação: A variável '[{}]' está no SQL, mas o 'Valor Padrão' dela está vazio.",
+            id
+        ));
+    }
+    Ok(MappedSql::new(sql).apply(&edits))
+}
+
+// Textos de ajuda do formulário de parâmetro: (rótulo do Extra, exemplo de valor padrão)
+fn type_hints(tipo: &str) -> (&'static str, &'static str) {
+    match tipo {
+        "periodo" => (
+            "Parâmetros Extras (não usado). No SQL: coluna BETWEEN [id], ou [id_inicio] e [id_fim]",
+            "2026-01-01;2026-01-31",
+        ),
+        "lista" => ("Opções, uma por linha (valor=Rótulo). Ex: 1=Matriz", "1"),
+        "multi" => (
+            "Opções, uma por linha (valor=Rótulo). No SQL: coluna IN [id]",
+            "1;2;3",
+        ),
+        "moeda" => ("Parâmetros Extras (não usado)", "1.234,56"),
+        _ => (
+            "Parâmetros Extras (Ex: SQL de pesquisa com tag [SYNC: produtosprincipal(descricao)])",
+            "",
+        ),
+    }
+}
+
 // COMPONENTES
 #[component]
 fn InfoTab(
     report_pure_name: Signal<String>,
     report_folder: Signal<String>,
     description: Signal<String>,
+    mut metadata: Signal<ReportMetadata>,
+    mut tags_text: Signal<String>,
     on_change_folder: EventHandler<()>,
 ) -> Element {
+    let meta = metadata.read().clone();
+    let tags = parse_tags(&tags_text.read());
+    let created = if meta.criado_em.is_empty() { "ainda não salvo".to_string() } else { meta.criado_em.clone() };
+    let modified = if meta.modificado_em.is_empty() { "-".to_string() } else { meta.modificado_em.clone() };
+
     rsx! {
         div { class: "info-tab-content",
             div { class: "form-group",
@@ -53,6 +109,30 @@ fn InfoTab(
                 label { "Descrição:" }
                 textarea { class: "desc-editor", value: "{description}", oninput: move |evt| description.set(evt.value()) }
             }
+            div { class: "info-row",
+                div { class: "form-group flex-1",
+                    label { "Autor:" }
+                    input { class: "input-classic", placeholder: "Preenchido com o usuário do Windows ao salvar", value: "{meta.autor}", oninput: move |evt| metadata.write().autor = evt.value() }
+                }
+                div { class: "form-group flex-1",
+                    label { "Departamento:" }
+                    input { class: "input-classic", placeholder: "Ex: Financeiro", value: "{meta.departamento}", oninput: move |evt| metadata.write().departamento = evt.value() }
+                }
+            }
+            div { class: "form-group",
+                label { "Tags (separadas por vírgula):" }
+                input { class: "input-classic", placeholder: "Ex: vendas, mensal, diretoria", value: "{tags_text}", oninput: move |evt| tags_text.set(evt.value()) }
+                if !tags.is_empty() {
+                    div { class: "tag-list",
+                        {tags.iter().map(|t| rsx! { span { key: "{t}", class: "tag-chip", "{t}" } })}
+                    }
+                }
+            }
+            div { class: "form-group",
+                label { "Notas para manutenção:" }
+                textarea { class: "desc-editor notes-editor", placeholder: "Regras de negócio, quem pediu, cuidados ao alterar...", value: "{meta.notas}", oninput: move |evt| metadata.write().notas = evt.value() }
+            }
+            div { class: "info-timestamps", "Criado em {created} · Modificado em {modified}" }
         }
     }
 }
@@ -61,9 +141,25 @@ fn InfoTab(
 fn ParametrosTab(
     parameters: Signal<Vec<ReportParameter>>,
     selected_param_idx: Signal<Option<usize>>,
+    issues: Vec<LintIssue>,
 ) -> Element {
     let params_list = parameters.read().clone();
     let sel_idx = *selected_param_idx.read();
+    let (extra_label, default_hint) = type_hints(
+        sel_idx
+            .and_then(|i| params_list.get(i))
+            .map_or("", |p| p.tipo.as_str()),
+    );
+    let severity_of = |i: usize| {
+        let mut own = issues.iter().filter(|issue| issue.param_index == Some(i));
+        if own.clone().any(|issue| issue.severity == Severity::Error) {
+            "❌ "
+        } else if own.next().is_some() {
+            "⚠️ "
+        } else {
+            ""
+        }
+    };
 
     rsx! {
         div { class: "params-container",
@@ -75,8 +171,26 @@ fn ParametrosTab(
                         {params_list.iter().enumerate().map(|(i, p)| {
                             let is_selected = sel_idx == Some(i);
                             let item_class = if is_selected { "param-item selected" } else { "param-item" };
+                            let marker = severity_of(i);
                             rsx! {
-                                div { key: "{i}", class: "{item_class}", onclick: move |_| selected_param_idx.set(Some(i)), "[{p.id}] - {p.nome}" }
+                                div { key: "{i}", class: "{item_class}", onclick: move |_| selected_param_idx.set(Some(i)), "{marker}[{p.id}] - {p.nome}" }
+                            }
+                        })}
+                    }
+                }
+                if !issues.is_empty() {
+                    div { class: "param-lint",
+                        {issues.iter().enumerate().map(|(n, issue)| {
+                            let target = issue.param_index;
+                            let (css, icon) = match issue.severity {
+                                Severity::Error => ("param-lint-item error", "❌"),
+                                Severity::Warning => ("param-lint-item", "⚠️"),
+                            };
+                            rsx! {
+                                div { key: "{n}", class: "{css}",
+                                    onclick: move |_| { if target.is_some() { selected_param_idx.set(target); } },
+                                    "{icon} {issue.message}"
+                                }
                             }
                         })}
                     }
@@ -88,7 +202,8 @@ fn ParametrosTab(
                             let new_idx = p.len();
                             p.push(ReportParameter {
                                 id: format!("param_{}", new_idx + 1), nome: "Novo Parâmetro".to_string(),
-                                tipo: "string".to_string(), valor_padrao: "".to_string(), requerido: true, extra: "".to_string()
+                                tipo: "string".to_string(), valor_padrao: "".to_string(), requerido: true, extra: "".to_string(),
+                                numerico: false
                             });
                             selected_param_idx.set(Some(new_idx));
                         },
@@ -123,11 +238,13 @@ fn ParametrosTab(
                                 onchange: move |evt| { if let Some(i) = *selected_param_idx.read() { parameters.write()[i].tipo = evt.value(); } },
                                 option { value: "string", "Texto (String)" } option { value: "int", "Inteiro (Número inteiro)" }
                                 option { value: "float", "Decimal (Moeda / Quantidade)" } option { value: "data", "Data" } option { value: "pesquisa", "Pesquisa (Busca com SQL)" }
+                                option { value: "periodo", "Período (Data inicial e final)" } option { value: "lista", "Lista de Opções" }
+                                option { value: "multi", "Seleção Múltipla (IN)" } option { value: "moeda", "Moeda (R$)" }
                             }
                         }
                         div { class: "form-group",
                             label { "Valor Padrão (Obrigatório para testar a query)" }
-                            input { class: "input-classic", value: "{parameters.read()[idx].valor_padrao}", oninput: move |evt| { if let Some(i) = *selected_param_idx.read() { parameters.write()[i].valor_padrao = evt.value(); } } }
+                            input { class: "input-classic", placeholder: default_hint, value: "{parameters.read()[idx].valor_padrao}", oninput: move |evt| { if let Some(i) = *selected_param_idx.read() { parameters.write()[i].valor_padrao = evt.value(); } } }
                         }
                         div { class: "form-group checkbox-group",
                             input { class: "checkbox-input", r#type: "checkbox", checked: "{parameters.read()[idx].requerido}",
@@ -135,8 +252,16 @@ fn ParametrosTab(
                             }
                             label { class: "checkbox-label", "Campo Obrigatório" }
                         }
+                        if matches!(parameters.read()[idx].tipo.as_str(), "lista" | "multi" | "pesquisa") {
+                            div { class: "form-group checkbox-group",
+                                input { class: "checkbox-input", r#type: "checkbox", checked: "{parameters.read()[idx].numerico}",
+                                    onchange: move |evt| { if let Some(i) = *selected_param_idx.read() { parameters.write()[i].numerico = evt.checked(); } }
+                                }
+                                label { class: "checkbox-label", "Valores numéricos (entram no SQL sem aspas)" }
+                            }
+                        }
                         div { class: "form-group mt-15",
-                            label { "Parâmetros Extras (Ex: SQL de pesquisa com tag [SYNC: produtosprincipal(descricao)])" }
+                            label { "{extra_label}" }
                             textarea { class: "input-classic extra-sql-area", value: "{parameters.read()[idx].extra}", oninput: move |evt| { if let Some(i) = *selected_param_idx.read() { parameters.write()[i].extra = evt.value(); } } }
                         }
                     }
@@ -149,13 +274,84 @@ fn ParametrosTab(
 }
 
 #[component]
-fn SqlTab(query_text: Signal<String>) -> Element {
+fn SqlTab(
+    mut query_text: Signal<String>,
+    engine: Signal<DataEngine>,
+    parameters: Signal<Vec<ReportParameter>>,
+    mut diagnostics: Signal<Vec<SqlDiagnostic>>,
+    mut sample_result: Signal<Option<SampleResult>>,
+    on_format_error: EventHandler<String>,
+) -> Element {
+    let mut show_schema = use_signal(|| true);
+    let mut keyword_case = use_signal(|| KeywordCase::Upper);
+    let mut indent = use_signal(|| "    ".to_string());
+
+    let handle_format = move |_| {
+        let sql = query_text.read().clone();
+        let options = FormatOptions {
+            keyword_case: keyword_case(),
+            indent: indent(),
+        };
+        match format_sql(&sql, &options) {
+            Ok(formatted) => {
+                query_text.set(formatted);
+                diagnostics.set(Vec::new());
+            }
+            Err(e) => on_format_error.call(e),
+        }
+    };
+
     rsx! {
-        div { class: "sql-editor-container",
-            div { class: "sql-instruction",
-                span { "Defina as tabelas. Ex: " } code { "-- [SYNC: nfmestre(*), pessoas(id, nome)]" }
+        div { class: "sql-tab-layout",
+            div { class: "sql-editor-container",
+                div { class: "sql-instruction",
+                    span { "Ctrl+Espaço sugere tabelas, colunas e [parâmetros]. Tabelas e colunas são detectadas pela query; para sobrescrever ou anexar arquivos: " } code { "-- [SYNC: nfmestre(*), metas('planilhas/metas.csv')]" }
+                    div { class: "sql-toolbar-actions",
+                        select { class: "input-classic sql-format-select", title: "Caixa das palavras-chave",
+                            onchange: move |evt| keyword_case.set(match evt.value().as_str() { "lower" => KeywordCase::Lower, "preserve" => KeywordCase::Preserve, _ => KeywordCase::Upper }),
+                            option { value: "upper", "MAIÚSCULAS" } option { value: "lower", "minúsculas" } option { value: "preserve", "Manter caixa" }
+                        }
+                        select { class: "input-classic sql-format-select", title: "Indentação",
+                            onchange: move |evt| indent.set(match evt.value().as_str() { "2" => "  ".to_string(), "tab" => "\t".to_string(), _ => "    ".to_string() }),
+                            option { value: "4", "4 espaços" } option { value: "2", "2 espaços" } option { value: "tab", "Tab" }
+                        }
+                        button { class: "btn-classic btn-schema-toggle", onclick: handle_format, "🧹 Formatar SQL" }
+                        button { class: "btn-classic btn-schema-toggle", onclick: move |_| show_schema.toggle(), if show_schema() { "Ocultar Schema" } else { "🗂️ Schema" } }
+                    }
+                }
+                SqlEditor { query_text: query_text, engine: engine, parameters: parameters, diagnostics: diagnostics }
+                if let Some(result) = sample_result() {
+                    div { class: "sample-result",
+                        div { class: "sample-result-header",
+                            span { class: "sample-result-title",
+                                "🧪 {result.total_rows} linha(s) na amostra em {result.elapsed_ms} ms"
+                                if result.total_rows > result.rows.len() { {format!(" (exibindo {})", result.rows.len())} }
+                            }
+                            span { class: "sample-result-tables",
+                                {result.tables.iter().map(|(name, rows)| rsx! { span { key: "{name}", class: "sample-table-badge", "{name}: {rows}" } })}
+                            }
+                            button { class: "btn-classic btn-schema-toggle", onclick: move |_| sample_result.set(None), "✖" }
+                        }
+                        div { class: "sample-result-grid",
+                            if result.rows.is_empty() {
+                                div { class: "empty-msg", "A query não devolveu linhas para a amostra." }
+                            } else {
+                                table { class: "pg-table table-wrapper",
+                                    thead { tr { {result.headers.iter().enumerate().map(|(j, h)| rsx! { th { key: "{j}", class: "sticky-header", "{h}" } })} } }
+                                    tbody {
+                                        {result.rows.iter().enumerate().map(|(i, row)| rsx! {
+                                            tr { key: "{i}", {row.iter().enumerate().map(|(j, cell)| rsx! { td { key: "{j}", "{cell}" } })} }
+                                        })}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if show_schema() {
+                SchemaBrowser { engine: engine }
             }
-            textarea { class: "sql-editor", spellcheck: false, value: "{query_text}", oninput: move |evt| query_text.set(evt.value()) }
         }
     }
 }
@@ -170,6 +366,8 @@ pub fn EditQuery(
     let mut query_text = use_signal(|| String::new());
     let mut description = use_signal(|| String::new());
     let mut parameters = use_signal(|| Vec::<ReportParameter>::new());
+    let mut metadata = use_signal(ReportMetadata::default);
+    let mut tags_text = use_signal(|| String::new());
     let selected_param_idx = use_signal(|| None::<usize>);
 
     let report_pure_name = use_signal(|| {
@@ -190,15 +388,34 @@ pub fn EditQuery(
     let mut show_status_modal = use_signal(|| false);
     let mut status_modal_type = use_signal(|| StatusType::Error);
 
+    let mut show_plan = use_signal(|| false);
+    let mut plan_nodes = use_signal(|| Vec::<PlanNode>::new());
+    let mut plan_loading = use_signal(|| false);
+    let mut plan_error = use_signal(|| String::new());
+    let mut sql_diagnostics = use_signal(|| Vec::<SqlDiagnostic>::new());
+    let mut sample_size = use_signal(|| SAMPLE_SIZES[0]);
+    let mut sample_running = use_signal(|| false);
+    let mut sample_result = use_signal(|| None::<SampleResult>);
+    let mut version_comment = use_signal(|| String::new());
+    let mut show_history = use_signal(|| false);
+
     let report_name_for_load = report_name.clone();
     use_effect(move || {
         let path = Path::new(&report_name_for_load);
         if path.exists() {
-            if let Ok(content) = fs::read_to_string(&report_name_for_load) {
-                if let Ok(data) = serde_json::from_str::<ReportData>(&content) {
+            // Formatos antigos chegam migrados e são gravados no formato atual ao salvar
+            match load_report(path) {
+                Ok(data) => {
                     query_text.set(data.query_sql);
                     description.set(data.descricao);
                     parameters.set(data.parametros);
+                    tags_text.set(data.metadados.tags.join(", "));
+                    metadata.set(data.metadados);
+                }
+                Err(e) => {
+                    status_msg.set(format!("Não foi possível abrir o relatório:\n{}", e));
+                    status_modal_type.set(StatusType::Error);
+                    show_status_modal.set(true);
                 }
             }
         }
@@ -224,78 +441,59 @@ pub fn EditQuery(
         }
     };
 
-    let handle_test = move |_| {
+    // Sem `sample`, as tabelas entram vazias e os comandos só são planejados. Com
+    // `sample`, cada tabela recebe os primeiros registros lidos pelo mesmo decodificador
+    // da extração e todos os comandos são executados de fato.
+    let mut run_test = move |sample: Option<usize>| {
         let sql = query_text.read().clone();
         let params_list = parameters.read().clone();
+        sql_diagnostics.set(Vec::new());
 
-        if !sql.to_uppercase().contains("[SYNC:") {
-            status_msg.set("ERRO: Tag [SYNC: ...] não encontrada na query.".to_string());
+        let issues = lint_parameters(&sql, &params_list);
+        if has_errors(&issues) {
+            status_msg.set(errors_summary(&issues));
             status_modal_type.set(StatusType::Error);
             show_status_modal.set(true);
             return;
         }
 
-        let re_header = regex::Regex::new(r"(?i)\[SYNC:\s*(?s)(.*?)\]").unwrap();
-        let re_table = regex::Regex::new(r"([a-zA-Z0-9_]+)\s*\((.*?)\)").unwrap();
-
-        let mut specific_configs = std::collections::HashMap::new();
-        {
-            let engine_lock = engine.read();
-
-            if let Some(content) = re_header.captures(&sql).and_then(|caps| caps.get(1)) {
-                for cap in re_table.captures_iter(content.as_str()) {
-                    let table_name = cap[1].to_string().to_lowercase();
+        let bound = match bind_with_defaults(&sql, &params_list) {
+            Ok(bound) => bound,
+            Err(e) => {
+                status_msg.set(e);
+                status_modal_type.set(StatusType::Error);
+                show_status_modal.set(true);
+                return;
+            }
+        };
 
-                    if let Some((_, config)) = engine_lock
-                        .schema
-                        .iter()
-                        .find(|(k, _)| k.to_lowercase() == table_name)
-                    {
-                        specific_configs.insert(table_name.clone(), config.clone());
-                    } else {
-                        status_msg.set(format!(
-                            "Tabela '{}' não existe no arquivo schema.toml!",
-                            table_name
-                        ));
-                        status_modal_type.set(StatusType::Error);
-                        show_status_modal.set(true);
-                        return;
-                    }
+        let bound_sql = bound.text.clone();
+        let final_sql = strip_sync_tags(&bound);
+        let external_sources = DataEngine::parse_external_sources(&bound_sql);
+        let extraction = engine.read().plan_extraction(&bound_sql, &external_sources);
+        let jobs = match extraction {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                // Erro de sintaxe: a posição vem do SQL inteiro, sem as tags
+                let whole = 0..final_sql.text.len();
+                if let Some(diagnostic) = locate_error(&e, &final_sql, whole, &sql) {
+                    sql_diagnostics.set(vec![diagnostic]);
                 }
+                status_msg.set(e);
+                status_modal_type.set(StatusType::Error);
+                show_status_modal.set(true);
+                return;
             }
-        }
+        };
 
-        if specific_configs.is_empty() {
-            status_msg.set(
-                "Tag SYNC encontrada, mas nenhuma tabela foi declarada corretamente.".to_string(),
-            );
+        if jobs.is_empty() && external_sources.is_empty() {
+            status_msg.set("Nenhuma tabela do schema.toml foi encontrada na query.".to_string());
             status_modal_type.set(StatusType::Error);
             show_status_modal.set(true);
             return;
         }
 
-        let clean_sql_str = re_header.replace_all(&sql, "").to_string();
-        let re_vars = regex::Regex::new(r"\[([a-zA-Z0-9_]+)\]").unwrap();
-        let mut final_sql = clean_sql_str;
-
-        for cap in re_vars.captures_iter(&final_sql.clone()) {
-            let var_id = cap[1].to_string();
-            if let Some(param) = params_list.iter().find(|p| p.id == var_id) {
-                if param.valor_padrao.trim().is_empty() {
-                    status_msg.set(format!("Erro de Validação: A variável '[{}]' está no SQL, mas o 'Valor Padrão' dela está vazio.", var_id));
-                    status_modal_type.set(StatusType::Error);
-                    show_status_modal.set(true);
-                    return;
-                }
-                final_sql = final_sql.replace(&format!("[{}]", var_id), &param.valor_padrao);
-            }
-        }
-
-        let commands: Vec<String> = final_sql
-            .split(';')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
+        let commands = statement_ranges(&final_sql.text);
 
         if commands.is_empty() {
             status_msg.set("O SQL está vazio após a tag de sincronização.".to_string());
@@ -304,45 +502,99 @@ pub fn EditQuery(
             return;
         }
 
+        if sample.is_some() {
+            sample_running.set(true);
+            sample_result.set(None);
+            active_tab.set(EditorTab::Sql);
+        }
+        let base_path = engine.read().base_path.clone();
+
         spawn(async move {
-            let test_ctx = datafusion::prelude::SessionContext::new();
-
-            for (table_name, config) in specific_configs {
-                let mut arrow_fields = Vec::new();
-                for col in &config.columns {
-                    let dtype = match col.field_type.as_str() {
-                        "I" => datafusion::arrow::datatypes::DataType::Int64,
-                        "F" => datafusion::arrow::datatypes::DataType::Float64,
-                        "D" => datafusion::arrow::datatypes::DataType::Date32,
-                        _ => datafusion::arrow::datatypes::DataType::Utf8,
-                    };
-                    arrow_fields.push(datafusion::arrow::datatypes::Field::new(
-                        &col.name.to_lowercase(),
-                        dtype,
-                        true,
-                    ));
+            let started = std::time::Instant::now();
+            let test_ctx = new_session_context();
+
+            let tables = tokio::task::spawn_blocking(move || -> Result<Vec<_>, String> {
+                jobs.into_iter()
+                    .map(|(table_name, config, target_columns)| {
+                        let batch = match sample {
+                            Some(limit) => read_first_rows(
+                                &base_path,
+                                &table_name,
+                                &config,
+                                &target_columns,
+                                limit,
+                            )?,
+                            None => {
+                                let fields: Vec<_> =
+                                    target_columns.iter().map(arrow_field_for).collect();
+                                RecordBatch::new_empty(Arc::new(ArrowSchema::new(fields)))
+                            }
+                        };
+                        Ok((table_name.to_lowercase(), batch))
+                    })
+                    .collect()
+            })
+            .await
+            .unwrap_or(Err("Erro crítico ao ler a amostra".to_string()));
+
+            let mut final_result = Ok(());
+            let mut loaded = Vec::new();
+            match tables {
+                Ok(tables) => {
+                    for (table_name, batch) in tables {
+                        loaded.push((table_name.clone(), batch.num_rows()));
+                        let registered = MemTable::try_new(batch.schema(), vec![vec![batch]])
+                            .map_err(|e| e.to_string())
+                            .and_then(|mem_table| {
+                                test_ctx
+                                    .register_table(table_name.as_str(), Arc::new(mem_table))
+                                    .map_err(|e| e.to_string())
+                            });
+                        if let Err(e) = registered {
+                            final_result = Err(e);
+                            break;
+                        }
+                    }
                 }
-                let schema =
-                    std::sync::Arc::new(datafusion::arrow::datatypes::Schema::new(arrow_fields));
-                let empty_batch =
-                    datafusion::arrow::record_batch::RecordBatch::new_empty(schema.clone());
-                let mem_table =
-                    datafusion::datasource::MemTable::try_new(schema, vec![vec![empty_batch]])
-                        .unwrap();
-
-                let _ =
-                    test_ctx.register_table(table_name.as_str(), std::sync::Arc::new(mem_table));
+                Err(e) => final_result = Err(e),
             }
 
-            let mut final_result = Ok(());
-            for cmd in commands {
-                if let Err(e) = test_ctx.sql(&cmd).await {
-                    final_result = Err(e.to_string());
+            for (source_name, source_path) in &external_sources {
+                if final_result.is_err() {
                     break;
                 }
+                if let Err(e) = register_external_source(&test_ctx, source_name, source_path).await {
+                    final_result = Err(e);
+                }
             }
 
+            let mut last_batches = Vec::new();
+            for range in commands {
+                if final_result.is_err() {
+                    break;
+                }
+                let command = &final_sql.text[range.clone()];
+                let outcome = match test_ctx.sql(command).await {
+                    Ok(df) if sample.is_some() => df.collect().await.map(|b| last_batches = b),
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = outcome {
+                    let e = e.to_string();
+                    if let Some(diagnostic) = locate_error(&e, &final_sql, range, &sql) {
+                        sql_diagnostics.set(vec![diagnostic]);
+                    }
+                    final_result = Err(e);
+                }
+            }
+
+            sample_running.set(false);
             match final_result {
+                Ok(_) if sample.is_some() => {
+                    let elapsed = started.elapsed().as_millis();
+                    sample_result.set(Some(SampleResult::new(&last_batches, loaded, elapsed)));
+                    return;
+                }
                 Ok(_) => {
                     status_msg.set("SQL Validado com Sucesso!\n\nA sintaxe e as colunas foram aprovadas pelo DataFusion.".to_string());
                     status_modal_type.set(StatusType::Success);
@@ -361,6 +613,11 @@ pub fn EditQuery(
 
                     let final_msg = if clean_error.is_empty() {
                         "Erro de sintaxe SQL ou coluna não encontrada.\n(Verifique o uso de maiúsculas e minúsculas)".to_string()
+                    } else if sample.is_some() {
+                        format!(
+                            "{}\n\n💡 Dica: O erro apareceu ao executar a query sobre a amostra. Confira conversões (CAST), divisões por zero e comparações entre tipos diferentes.",
+                            clean_error
+                        )
                     } else {
                         format!(
                             "{}\n\n💡 Dica: Verifique se os nomes das colunas estão corretos no 'schema.toml' e se todos os JOINs possuem as colunas de ligação.",
@@ -375,6 +632,54 @@ pub fn EditQuery(
             show_status_modal.set(true);
         });
     };
+    let handle_test = move |_| run_test(None);
+    let handle_sample = move |_| {
+        if !sample_running() {
+            run_test(Some(sample_size()));
+        }
+    };
+
+    // Carrega as tabelas de verdade (com os valores padrão) para medir cada operador
+    let handle_plan = move |_| {
+        if plan_loading() {
+            return;
+        }
+        let bound_sql = match bind_with_defaults(&query_text.read(), &parameters.read()) {
+            Ok(bound) => bound.text,
+            Err(e) => {
+                status_msg.set(e);
+                status_modal_type.set(StatusType::Error);
+                show_status_modal.set(true);
+                return;
+            }
+        };
+
+        let mut engine_clone = engine.read().clone();
+        plan_error.set(String::new());
+        plan_loading.set(true);
+        show_plan.set(true);
+
+        spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                let cancel = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+                engine_clone.process_report_with_progress(
+                    &bound_sql,
+                    cancel,
+                    "Plano de Execução (Editor)",
+                    |_| {},
+                )?;
+                engine_clone.explain_analyze(&bound_sql, true)
+            })
+            .await
+            .unwrap_or(Err("Erro crítico ao gerar o plano".to_string()));
+
+            match result {
+                Ok(text) => plan_nodes.set(parse_plan(&text)),
+                Err(e) => plan_error.set(e),
+            }
+            plan_loading.set(false);
+        });
+    };
 
     let report_name_original = report_name.clone();
     let on_back_action = on_back.clone();
@@ -396,39 +701,84 @@ pub fn EditQuery(
             show_status_modal.set(true);
             return;
         }
-        if !sql.to_uppercase().contains("[SYNC:") {
-            status_msg.set("ERRO: É obrigatório definir as tabelas de origem na 1ª linha.\n\nExemplo:\n-- [SYNC: nfmestre(*)]".to_string());
+        let issues = lint_parameters(&sql, &parameters.read());
+        if has_errors(&issues) {
+            status_msg.set(errors_summary(&issues));
             status_modal_type.set(StatusType::Error);
             show_status_modal.set(true);
+            active_tab.set(EditorTab::Parametros);
             return;
         }
 
-        let data = ReportData {
+        // Conteúdo que está no disco antes de salvar, para o histórico
+        let old_path = Path::new(&report_name_original);
+
+        // Outro relatório já ocupa o nome/pasta escolhidos: sobrescrevê-lo apagaria o SQL
+        // dele sem passar pelo histórico. Só a caixa mudou (Windows) conta como o mesmo.
+        let same_file = match (fs::canonicalize(&path_to_save), fs::canonicalize(old_path)) {
+            (Ok(new), Ok(old)) => new == old,
+            _ => false,
+        };
+        if path_to_save != old_path && path_to_save.exists() && !same_file {
+            status_msg.set(format!(
+                "Já existe outro relatório em {}. Escolha outro nome ou pasta.",
+                path_to_save.display()
+            ));
+            status_modal_type.set(StatusType::Error);
+            show_status_modal.set(true);
+            active_tab.set(EditorTab::Info);
+            return;
+        }
+        let previous = load_report(old_path)
+            .ok()
+            .map(|def| (def, file_saved_at(old_path).unwrap_or_else(chrono::Local::now)));
+
+        let mut metadados = metadata.read().clone();
+        metadados.tags = parse_tags(&tags_text.read());
+        let mut definition = ReportDefinition {
+            format_version: CURRENT_FORMAT_VERSION,
             descricao: description.read().clone(),
             query_sql: sql,
             parametros: parameters.read().clone(),
+            metadados,
         };
+        stamp_metadata(&mut definition, previous.as_ref().map(|(def, _)| def));
 
-        if let Ok(json_content) = serde_json::to_string_pretty(&data) {
-            let tmp_path = path_to_save.with_extension("tmp");
-
-            if fs::write(&tmp_path, &json_content).is_ok() {
-                if fs::rename(&tmp_path, &path_to_save).is_err() {
-                    let _ = fs::write(&path_to_save, &json_content);
-                    let _ = fs::remove_file(&tmp_path);
+        match save_report(&path_to_save, &definition) {
+            Ok(()) => {
+                if path_to_save != old_path && old_path.exists() && !same_file {
+                    let _ = fs::remove_file(old_path);
+                    let _ = run_history::report_moved(
+                        &report_name_original,
+                        &path_to_save.to_string_lossy(),
+                    );
                 }
 
-                let old_path = Path::new(&report_name_original);
-                if path_to_save != old_path && old_path.exists() {
-                    let _ = fs::remove_file(old_path);
+                let history = move_history(old_path, &path_to_save).and_then(|_| {
+                    record_version(
+                        &path_to_save,
+                        previous.as_ref().map(|(def, saved_at)| (def, *saved_at)),
+                        &definition,
+                        &version_comment.read(),
+                    )
+                });
+                if let Err(err) = history {
+                    status_msg.set(format!(
+                        "O relatório foi salvo, mas a versão não entrou no histórico:\n{}",
+                        err
+                    ));
+                    status_modal_type.set(StatusType::Error);
+                    show_status_modal.set(true);
+                    return;
                 }
 
                 on_back_action.call(e);
-            } else {
-                status_msg.set(
-                    "Falha ao gravar arquivo temporário no disco. Verifique as permissões."
-                        .to_string(),
-                );
+            }
+            Err(err) => {
+                status_msg.set(format!(
+                    "{}\nO relatório não foi alterado. Verifique as permissões da pasta.",
+                    err
+                ));
                 status_modal_type.set(StatusType::Error);
                 show_status_modal.set(true);
             }
@@ -436,6 +786,11 @@ pub fn EditQuery(
     };
 
     let active_tab_val = *active_tab.read();
+    let lint_issues = lint_parameters(&query_text.read(), &parameters.read());
+    let lint_errors = lint_issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
 
     rsx! {
         div { class: "app-container",
@@ -443,31 +798,96 @@ pub fn EditQuery(
                 show: show_status_modal,
                 status: status_modal_type(),
                 message: status_msg(),
-                sql_content: if status_msg().contains("SYNC") { query_text() } else { String::new() },
+                sql_content: if status_msg().contains("SYNC") || !sql_diagnostics.read().is_empty() { query_text() } else { String::new() },
+                error_line: sql_diagnostics.read().first().map(|d| d.line),
                 on_close: move |_| show_status_modal.set(false)
             }
+            HistoryModal {
+                show: show_history,
+                report_path: report_name.clone(),
+                current: ReportDefinition {
+                    format_version: CURRENT_FORMAT_VERSION,
+                    descricao: description(),
+                    query_sql: query_text(),
+                    parametros: parameters(),
+                    metadados: metadata(),
+                },
+                on_restore: move |version: ReportVersion| {
+                    query_text.set(version.relatorio.query_sql);
+                    description.set(version.relatorio.descricao);
+                    parameters.set(version.relatorio.parametros);
+                    // Tags, departamento e notas voltam com a versão; criação e autor
+                    // continuam os do relatório, como o stamp_metadata faria ao salvar
+                    let current = metadata.read().clone();
+                    let restored = ReportMetadata {
+                        autor: current.autor,
+                        criado_em: current.criado_em,
+                        modificado_em: current.modificado_em,
+                        ..version.relatorio.metadados
+                    };
+                    tags_text.set(restored.tags.join(", "));
+                    metadata.set(restored);
+                    sql_diagnostics.set(Vec::new());
+                    version_comment.set(format!("Restaurada a versão de {}", version.salvo_em));
+                    status_msg.set(format!(
+                        "Versão de {} restaurada no editor.\n\nClique em 'Salvar e Sair' para gravá-la.",
+                        version.salvo_em
+                    ));
+                    status_modal_type.set(StatusType::Success);
+                    show_status_modal.set(true);
+                }
+            }
+            PlanModal {
+                show: show_plan,
+                nodes: plan_nodes(),
+                loading: plan_loading(),
+                error: plan_error(),
+                on_close: move |_| show_plan.set(false)
+            }
 
             div { class: "middle-section",
                 div { class: "sidebar",
+                    input { class: "input-classic", placeholder: "Comentário da versão", title: "Gravado no histórico junto com esta versão", value: "{version_comment}", oninput: move |evt| version_comment.set(evt.value()) }
                     button { class: "btn-classic", onclick: save_and_exit, "Salvar e Sair" }
                     button { class: "btn-classic", onclick: move |e| on_back.call(e), "Cancelar" }
                     button { class: "btn-classic", onclick: handle_test, "Testar Query" }
+                    button { class: "btn-classic", disabled: sample_running(), onclick: handle_sample, if sample_running() { "⏳ Rodando..." } else { "🧪 Rodar na Amostra" } }
+                    select { class: "input-classic input-h28", title: "Registros lidos de cada tabela", value: "{sample_size}",
+                        onchange: move |evt| { if let Ok(n) = evt.value().parse() { sample_size.set(n); } },
+                        {SAMPLE_SIZES.iter().map(|n| {
+                            let label = n / 1000;
+                            rsx! { option { key: "{n}", value: "{n}", "Amostra: {label} mil" } }
+                        })}
+                    }
+                    button { class: "btn-classic", onclick: handle_plan, "🔍 Plano de Execução" }
+                    button { class: "btn-classic", onclick: move |_| show_history.set(true), "🕘 Histórico" }
                 }
 
                 div { class: "main-view",
                     div { class: "tabs-header",
                         div { class: if active_tab_val == EditorTab::Info { "tab-item active" } else { "tab-item" }, onclick: move |_| active_tab.set(EditorTab::Info), "ℹ️ Detalhes" }
-                        div { class: if active_tab_val == EditorTab::Parametros { "tab-item active" } else { "tab-item" }, onclick: move |_| active_tab.set(EditorTab::Parametros), "⚙️ Parâmetros" }
+                        div { class: if active_tab_val == EditorTab::Parametros { "tab-item active" } else { "tab-item" }, onclick: move |_| active_tab.set(EditorTab::Parametros), "⚙️ Parâmetros" if lint_errors > 0 { span { class: "tab-lint-badge", "{lint_errors}" } } }
                         div { class: if active_tab_val == EditorTab::Sql { "tab-item active" } else { "tab-item" }, onclick: move |_| active_tab.set(EditorTab::Sql), "📝 SQL & Sincronia" }
                     }
 
                     div { class: "data-container editor-main-container",
                         if active_tab_val == EditorTab::Info {
-                            InfoTab { report_pure_name: report_pure_name, report_folder: report_folder, description: description, on_change_folder: EventHandler::new(change_folder) }
+                            InfoTab { report_pure_name: report_pure_name, report_folder: report_folder, description: description, metadata: metadata, tags_text: tags_text, on_change_folder: EventHandler::new(change_folder) }
                         } else if active_tab_val == EditorTab::Parametros {
-                            ParametrosTab { parameters: parameters, selected_param_idx: selected_param_idx }
+                            ParametrosTab { parameters: parameters, selected_param_idx: selected_param_idx, issues: lint_issues }
                         } else {
-                            SqlTab { query_text: query_text }
+                            SqlTab {
+                                query_text: query_text,
+                                engine: engine,
+                                parameters: parameters,
+                                diagnostics: sql_diagnostics,
+                                sample_result: sample_result,
+                                on_format_error: move |e| {
+                                    status_msg.set(e);
+                                    status_modal_type.set(StatusType::Error);
+                                    show_status_modal.set(true);
+                                }
+                            }
                         }
                     }
                 }
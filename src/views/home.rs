@@ -1,8 +1,21 @@
+use crate::components::agenda_modal::AgendaModal;
+use crate::components::confirm_modal::ConfirmModal;
+use crate::components::performance_modal::PerformanceModal;
+use crate::components::prompt_modal::PromptModal;
 use crate::components::status_modal::{StatusModal, StatusType};
-use crate::core::engine::{DataEngine, append_log};
-use crate::views::editor::ReportParameter;
+use crate::components::trash_modal::TrashModal;
+use crate::core::engine::DataEngine;
+use crate::core::query_plan::format_duration;
+use crate::core::report::{
+    MULTI_SEPARATOR, ParamValue, ReportDefinition, apply_parameters, list_options, load_report,
+};
+use crate::core::report_search;
+use crate::core::report_tree::{self, tree_key};
+use crate::core::run_history::{self, ReportRunEntry};
+use crate::core::telemetry::{self, TelemetryEvent};
+use crate::core::trash;
 use dioxus::prelude::*;
-use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::sync::{
@@ -11,13 +24,6 @@ use std::sync::{
     mpsc,
 };
 
-#[derive(Deserialize, Clone)]
-struct ReportConfig {
-    query_sql: String,
-    #[serde(default)]
-    parametros: Vec<ReportParameter>,
-}
-
 #[derive(Debug, Clone)]
 struct FileItem {
     name: String,
@@ -26,9 +32,18 @@ struct FileItem {
     children: Vec<FileItem>,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum FolderPrompt {
+    Create,
+    Rename,
+}
+
+// Quantos relatórios aparecem em "Recentes"
+const RECENT_LIMIT: usize = 8;
+
 enum LoaderMsg {
     Progress(f32),
-    Finished(DataEngine, String),
+    Finished(DataEngine, String, u128),
     Error(String),
 }
 
@@ -37,6 +52,10 @@ fn read_reports(dir: &Path) -> Vec<FileItem> {
     let mut items = Vec::new();
     if let Ok(entries) = fs::read_dir(dir) {
         for entry in entries.flatten() {
+            // .history e outras pastas internas ficam fora da árvore
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
             let path = entry.path();
             let name = path
                 .file_stem()
@@ -66,30 +85,62 @@ fn read_reports(dir: &Path) -> Vec<FileItem> {
     items
 }
 
-fn filter_nodes(items: &[FileItem], query: &str) -> Vec<FileItem> {
-    if query.is_empty() {
-        return items.to_vec();
-    }
-    let lower_query = query.to_lowercase();
+// Mantém só os relatórios da lista (e as pastas que ainda têm algum)
+fn retain_reports(items: &[FileItem], keep: &HashSet<String>) -> Vec<FileItem> {
     items
         .iter()
         .filter_map(|item| {
             if item.is_dir {
-                let filtered_children = filter_nodes(&item.children, query);
-                if !filtered_children.is_empty() || item.name.to_lowercase().contains(&lower_query)
-                {
-                    let mut new_dir = item.clone();
-                    new_dir.children = filtered_children;
-                    return Some(new_dir);
+                let children = retain_reports(&item.children, keep);
+                if children.is_empty() {
+                    return None;
                 }
-            } else if item.name.to_lowercase().contains(&lower_query) {
-                return Some(item.clone());
+                let mut dir = item.clone();
+                dir.children = children;
+                Some(dir)
+            } else {
+                keep.contains(&item.path_str).then(|| item.clone())
             }
-            None
         })
         .collect()
 }
 
+// "início;fim" do parâmetro período, cada ponta podendo estar vazia
+fn split_range(value: &str) -> (String, String) {
+    match value.split_once(MULTI_SEPARATOR) {
+        Some((start, end)) => (start.trim().to_string(), end.trim().to_string()),
+        None => (value.trim().to_string(), String::new()),
+    }
+}
+
+fn join_range(start: &str, end: &str) -> String {
+    if start.is_empty() && end.is_empty() {
+        String::new()
+    } else {
+        format!("{}{}{}", start, MULTI_SEPARATOR, end)
+    }
+}
+
+fn split_multi(value: &str) -> Vec<String> {
+    value
+        .split(MULTI_SEPARATOR)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn join_multi(values: &[String]) -> String {
+    values.join(MULTI_SEPARATOR.to_string().as_str())
+}
+
+fn report_label(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
 fn count_files(items: &[FileItem]) -> usize {
     items
         .iter()
@@ -104,33 +155,13 @@ fn count_files(items: &[FileItem]) -> usize {
 }
 
 // COMPONENTES
-#[component]
-fn LogsModal(show: Signal<bool>, on_close: EventHandler<()>) -> Element {
-    if !show() {
-        return rsx! {};
-    }
-
-    let logs_content = std::fs::read_to_string("logs_desempenho.txt")
-        .unwrap_or_else(|_| "Nenhum log de desempenho encontrado ainda...".to_string());
-
-    rsx! {
-        div { class: "modal-overlay overlay-dark",
-            div { class: "modal-window modal-w80-h80",
-                h2 { class: "logs-title", "📜 Histórico de Desempenho (Logs)" }
-                textarea { class: "input-classic log-textarea", readonly: true, value: "{logs_content}" }
-                button { class: "btn-classic btn-close-logs", onclick: move |_| on_close.call(()), "✖ Fechar Logs" }
-            }
-        }
-    }
-}
-
 #[component]
 fn LookupView(
     show: Signal<bool>,
     headers: Vec<String>,
     rows: Vec<Vec<String>>,
     active_param: Signal<String>,
-    user_values: Signal<std::collections::HashMap<String, String>>,
+    user_values: Signal<HashMap<String, String>>,
 ) -> Element {
     if !show() {
         return rsx! {};
@@ -172,11 +203,11 @@ fn LookupView(
 #[component]
 fn ParamsModal(
     show: Signal<bool>,
-    report_config: Signal<Option<ReportConfig>>,
+    report_config: Signal<Option<ReportDefinition>>,
     report_path: Signal<String>,
     engine: Signal<DataEngine>,
     on_close: EventHandler<()>,
-    on_generate: EventHandler<(String, String)>,
+    on_generate: EventHandler<(String, String, HashMap<String, String>)>,
 ) -> Element {
     if !show() {
         return rsx! {};
@@ -191,7 +222,7 @@ fn ParamsModal(
     let query_for_generate = config.query_sql.clone();
 
     let mut user_values = use_signal(|| {
-        let mut map = std::collections::HashMap::new();
+        let mut map = HashMap::new();
         for p in &config.parametros {
             let default_val = if p.tipo == "bool" {
                 "false".to_string()
@@ -210,17 +241,16 @@ fn ParamsModal(
     let mut is_lookup_loading = use_signal(|| false);
 
     let handle_generate = move |_| {
-        let mut final_sql = query_for_generate.clone();
-        for p in &params_for_generate {
-            let val = user_values.read().get(&p.id).cloned().unwrap_or_default();
-            if p.requerido && val.trim().is_empty() {
-                validation_error.set(format!("O campo '{}' é obrigatório.", p.nome));
-                return;
-            }
-            final_sql = final_sql.replace(&format!("[{}]", p.id), &val);
-        }
+        let final_sql =
+            match apply_parameters(&query_for_generate, &params_for_generate, &user_values.read()) {
+                Ok(sql) => sql,
+                Err(e) => {
+                    validation_error.set(e);
+                    return;
+                }
+            };
         validation_error.set(String::new());
-        on_generate.call((report_path.read().clone(), final_sql));
+        on_generate.call((report_path.read().clone(), final_sql, user_values.read().clone()));
     };
 
     let params_list = config.parametros.clone();
@@ -242,6 +272,11 @@ fn ParamsModal(
                         {params_list.iter().map(|p| {
                             let p_id = p.id.clone();
                             let current_val = user_values.read().get(&p.id).cloned().unwrap_or_default();
+                            let field_error = match ParamValue::parse(p, &current_val) {
+                                Err(e) if !current_val.trim().is_empty() => e,
+                                _ => String::new(),
+                            };
+                            let options = list_options(&p.extra);
 
                             rsx! {
                                 div { class: "form-group", key: "{p.id}",
@@ -307,9 +342,82 @@ fn ParamsModal(
                                                 if *is_lookup_loading.read() && *lookup_active_param.read() == p.id { "⏳" } else { "🔍" }
                                             }
                                         }
+                                    } else if p.tipo == "periodo" {
+                                        {
+                                            let (start, end) = split_range(&current_val);
+                                            let id_end = p_id.clone();
+                                            rsx! {
+                                                div { class: "flex-row-gap5 range-inputs",
+                                                    input { class: "input-classic input-h30 flex-1", r#type: "date", value: "{start}",
+                                                        oninput: move |evt| {
+                                                            let mut values = user_values.write();
+                                                            let (_, end) = split_range(values.get(&p_id).map(String::as_str).unwrap_or_default());
+                                                            values.insert(p_id.clone(), join_range(&evt.value(), &end));
+                                                        }
+                                                    }
+                                                    span { class: "range-sep", "até" }
+                                                    input { class: "input-classic input-h30 flex-1", r#type: "date", value: "{end}",
+                                                        oninput: move |evt| {
+                                                            let mut values = user_values.write();
+                                                            let (start, _) = split_range(values.get(&id_end).map(String::as_str).unwrap_or_default());
+                                                            values.insert(id_end.clone(), join_range(&start, &evt.value()));
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    } else if p.tipo == "lista" {
+                                        select { class: "input-classic input-h30", value: "{current_val}", onchange: move |evt| { user_values.write().insert(p_id.clone(), evt.value()); },
+                                            option { value: "", "Selecione..." }
+                                            {options.iter().map(|(value, label)| rsx! { option { key: "{value}", value: "{value}", selected: *value == current_val, "{label}" } })}
+                                        }
+                                    } else if p.tipo == "multi" {
+                                        {
+                                            let chosen = split_multi(&current_val);
+                                            let all_values: Vec<String> = options.iter().map(|(value, _)| value.clone()).collect();
+                                            let (id_all, id_none) = (p_id.clone(), p_id.clone());
+                                            let counter = format!("{} de {} selecionado(s)", chosen.len(), options.len());
+                                            rsx! {
+                                                div { class: "multi-select",
+                                                    div { class: "multi-select-actions",
+                                                        span { "{counter}" }
+                                                        button { class: "btn-link", onclick: move |_| { user_values.write().insert(id_all.clone(), join_multi(&all_values)); }, "Todos" }
+                                                        button { class: "btn-link", onclick: move |_| { user_values.write().insert(id_none.clone(), String::new()); }, "Nenhum" }
+                                                    }
+                                                    {options.iter().map(|(value, label)| {
+                                                        let is_checked = chosen.contains(value);
+                                                        let (id_toggle, option_value) = (p_id.clone(), value.clone());
+                                                        rsx! {
+                                                            label { key: "{value}", class: "multi-select-option",
+                                                                input { r#type: "checkbox", checked: is_checked,
+                                                                    onchange: move |evt| {
+                                                                        let mut values = user_values.write();
+                                                                        let mut chosen = split_multi(values.get(&id_toggle).map(String::as_str).unwrap_or_default());
+                                                                        chosen.retain(|v| *v != option_value);
+                                                                        if evt.checked() {
+                                                                            chosen.push(option_value.clone());
+                                                                        }
+                                                                        values.insert(id_toggle.clone(), join_multi(&chosen));
+                                                                    }
+                                                                }
+                                                                "{label}"
+                                                            }
+                                                        }
+                                                    })}
+                                                }
+                                            }
+                                        }
+                                    } else if p.tipo == "moeda" {
+                                        div { class: "flex-row-gap5 money-input",
+                                            span { class: "money-prefix", "R$" }
+                                            input { class: "input-classic input-h30 flex-1", r#type: "text", inputmode: "decimal", placeholder: "0,00", value: "{current_val}", oninput: move |evt| { user_values.write().insert(p_id.clone(), evt.value()); } }
+                                        }
                                     } else {
                                         input { class: "input-classic input-h30", r#type: "text", value: "{current_val}", oninput: move |evt| { user_values.write().insert(p_id.clone(), evt.value()); } }
                                     }
+                                    if !field_error.is_empty() {
+                                        div { class: "field-error", "{field_error}" }
+                                    }
                                 }
                             }
                         })}
@@ -327,11 +435,24 @@ fn ParamsModal(
     }
 }
 
+#[derive(Clone)]
+struct TreeHandlers {
+    on_select: EventHandler<String>,
+    on_open_named: EventHandler<String>,
+    on_select_folder: EventHandler<String>,
+    on_toggle: EventHandler<String>,
+    on_drag_start: EventHandler<String>,
+    on_drop: EventHandler<String>,
+    on_toggle_favorite: EventHandler<String>,
+}
+
 fn render_tree(
     items: &[FileItem],
     selected_path: &str,
-    on_select: EventHandler<String>,
-    on_open_named: EventHandler<String>,
+    selected_folder: &str,
+    collapsed: &BTreeSet<String>,
+    favorites: &[String],
+    handlers: &TreeHandlers,
 ) -> Element {
     rsx! {
         {items.iter().map(|item| {
@@ -339,17 +460,42 @@ fn render_tree(
             let is_selected = selected_path == path;
 
             if item.is_dir {
+                let is_collapsed = collapsed.contains(&tree_key(&path));
+                let is_folder_selected = selected_folder == path;
+                let (p_toggle, p_dbl, p_select, p_drop) = (path.clone(), path.clone(), path.clone(), path.clone());
+                let TreeHandlers { on_toggle, on_select_folder, on_drop, .. } = handlers.clone();
                 rsx! {
                     Fragment { key: "{path}",
-                        li { span { class: "tree-icon", "📁" } span { class: "folder-name", "{name}" } }
-                        ul { {render_tree(&item.children, selected_path, on_select.clone(), on_open_named.clone())} }
+                        li { class: "tree-folder", prevent_default: "ondragover ondrop",
+                            ondragover: move |_| {},
+                            ondrop: move |_| on_drop.call(p_drop.clone()),
+                            span { class: "tree-toggle", onclick: move |_| on_toggle.call(p_toggle.clone()), if is_collapsed { "▸" } else { "▾" } }
+                            span { class: "tree-icon", if is_collapsed { "📁" } else { "📂" } }
+                            span { class: if is_folder_selected { "folder-name selected" } else { "folder-name" },
+                                onclick: move |_| on_select_folder.call(p_select.clone()),
+                                ondoubleclick: move |_| on_toggle.call(p_dbl.clone()),
+                                "{name}"
+                            }
+                        }
+                        if !is_collapsed {
+                            ul { {render_tree(&item.children, selected_path, selected_folder, collapsed, favorites, handlers)} }
+                        }
                     }
                 }
             } else {
-                let p_select = path.clone(); let p_open = path.clone();
+                let p_select = path.clone(); let p_open = path.clone(); let p_drag = path.clone(); let p_fav = path.clone();
+                let is_favorite = run_history::is_favorite(favorites, &path);
+                let TreeHandlers { on_select, on_open_named, on_drag_start, on_toggle_favorite, .. } = handlers.clone();
                 rsx! {
-                    li { key: "{path}", onclick: move |_| on_select.call(p_select.clone()), ondoubleclick: move |_| on_open_named.call(p_open.clone()),
+                    li { key: "{path}", draggable: true,
+                        ondragstart: move |_| on_drag_start.call(p_drag.clone()),
+                        onclick: move |_| on_select.call(p_select.clone()), ondoubleclick: move |_| on_open_named.call(p_open.clone()),
                         span { class: "tree-icon", "📊" } span { class: if is_selected { "file-name selected" } else { "file-name" }, "{name}" }
+                        span { class: if is_favorite { "fav-toggle active" } else { "fav-toggle" },
+                            title: if is_favorite { "Remover dos favoritos" } else { "Adicionar aos favoritos" },
+                            onclick: move |evt| { evt.stop_propagation(); on_toggle_favorite.call(p_fav.clone()); },
+                            if is_favorite { "★" } else { "☆" }
+                        }
                     }
                 }
             }
@@ -366,17 +512,34 @@ pub fn Home(
     on_edit: EventHandler<()>,
     mut engine: Signal<DataEngine>,
     mut current_sql: Signal<String>,
+    mut pending_run: Signal<Option<ReportRunEntry>>,
 ) -> Element {
     let mut reports = use_signal(|| read_reports(Path::new("relatorios")));
     let mut search_text = use_signal(|| String::new());
+    let mut tag_filter = use_signal(|| String::new());
     let mut show_status_modal = use_signal(|| false);
     let mut status_modal_type = use_signal(|| StatusType::Error);
     let mut status_msg = use_signal(|| String::new());
     let last_sql = use_signal(|| String::new());
-    let mut show_logs = use_signal(|| false);
+    let mut show_performance = use_signal(|| false);
+    let mut show_agenda = use_signal(|| false);
+    let mut show_trash = use_signal(|| false);
+    let mut confirm_delete = use_signal(|| false);
+
+    // Pasta selecionada na árvore (vazia = raiz) e estado das pastas recolhidas
+    let mut selected_folder = use_signal(|| String::new());
+    let mut collapsed = use_signal(|| report_tree::load_collapsed(Path::new("relatorios")));
+    let mut dragging = use_signal(|| None::<String>);
+    let mut folder_prompt = use_signal(|| FolderPrompt::Create);
+    let mut show_folder_prompt = use_signal(|| false);
+    let mut confirm_folder_delete = use_signal(|| false);
+
+    // Favoritos e últimos executados, relidos a cada volta para a Home
+    let mut favorites = use_signal(|| run_history::load_favorites());
+    let mut recents = use_signal(|| run_history::recent_reports(RECENT_LIMIT));
 
     let mut show_params_modal = use_signal(|| false);
-    let mut current_report_config = use_signal(|| None::<ReportConfig>);
+    let mut current_report_config = use_signal(|| None::<ReportDefinition>);
     let mut current_report_path = use_signal(|| String::new());
 
     let mut is_loading = use_signal(|| false);
@@ -385,8 +548,35 @@ pub fn Home(
     let mut tx_signal = use_signal(|| None::<mpsc::Sender<LoaderMsg>>);
     let mut rx_signal = use_signal(|| None::<mpsc::Receiver<LoaderMsg>>);
 
-    let filtered_items = filter_nodes(&reports.read(), &search_text.read());
-    let total_files = count_files(&filtered_items);
+    // Índice de nome, descrição, SQL, parâmetros e tabelas, refeito quando a árvore muda
+    let search_index = use_memo(move || {
+        let _ = reports.read();
+        report_search::build_index(Path::new("relatorios"))
+    });
+    let is_searching = !search_text.read().trim().is_empty();
+    let all_tags = report_search::all_tags(&search_index.read());
+    let active_tag = tag_filter.read().clone();
+    let tagged: Option<HashSet<String>> = (!active_tag.is_empty()).then(|| {
+        search_index
+            .read()
+            .iter()
+            .filter(|r| report_search::has_tag(r, &active_tag))
+            .map(|r| r.path.clone())
+            .collect()
+    });
+    let mut search_hits = if is_searching {
+        report_search::search(&search_index.read(), &search_text.read())
+    } else {
+        Vec::new()
+    };
+    let visible_items = match &tagged {
+        Some(keep) => {
+            search_hits.retain(|hit| keep.contains(&hit.path));
+            retain_reports(&reports.read(), keep)
+        }
+        None => reports.read().clone(),
+    };
+    let total_files = if is_searching { search_hits.len() } else { count_files(&visible_items) };
 
     use_future(move || async move {
         loop {
@@ -396,12 +586,21 @@ pub fn Home(
                         match msg {
                             LoaderMsg::Progress(p) => progress.set(p),
                             LoaderMsg::Error(e) => {
+                                let failed_run = pending_run.write().take();
+                                if let Some(mut run) = failed_run {
+                                    run.sucesso = false;
+                                    run.erro = e.clone();
+                                    run_history::record_run(&run);
+                                }
                                 status_msg.set(e);
                                 status_modal_type.set(StatusType::Error);
                                 show_status_modal.set(true);
                                 is_loading.set(false);
                             }
-                            LoaderMsg::Finished(new_engine, sql) => {
+                            LoaderMsg::Finished(new_engine, sql, elapsed_ms) => {
+                                if let Some(run) = pending_run.write().as_mut() {
+                                    run.duracao_ms = elapsed_ms;
+                                }
                                 engine.set(new_engine);
                                 current_sql.set(sql);
                                 is_loading.set(false);
@@ -415,20 +614,11 @@ pub fn Home(
         }
     });
 
-    let prepare_report = move |path_to_open: String| {
-        let content = match fs::read_to_string(&path_to_open) {
+    let mut prepare_report = move |path_to_open: String| {
+        let config = match load_report(Path::new(&path_to_open)) {
             Ok(c) => c,
             Err(e) => {
-                status_msg.set(format!("Erro ao ler: {}", e));
-                status_modal_type.set(StatusType::Error);
-                show_status_modal.set(true);
-                return;
-            }
-        };
-        let config: ReportConfig = match serde_json::from_str(&content) {
-            Ok(c) => c,
-            Err(e) => {
-                status_msg.set(format!("Erro JSON: {}", e));
+                status_msg.set(e);
                 status_modal_type.set(StatusType::Error);
                 show_status_modal.set(true);
                 return;
@@ -439,8 +629,9 @@ pub fn Home(
         show_params_modal.set(true);
     };
 
-    let execute_report = move |(path_to_open, final_sql): (String, String)| {
+    let mut execute_report = move |(path_to_open, final_sql, values): (String, String, HashMap<String, String>)| {
         show_params_modal.set(false);
+        pending_run.set(Some(ReportRunEntry::start(&path_to_open, &values)));
         let (tx, rx) = mpsc::channel();
         tx_signal.set(Some(tx.clone()));
         rx_signal.set(Some(rx));
@@ -451,9 +642,11 @@ pub fn Home(
         let sql_to_process = final_sql.clone();
         let current_cancel = cancel_flag.read().clone();
         let report_name_log = path_to_open.clone();
+        let parameters_hash = telemetry::parameters_hash(&values);
 
         std::thread::spawn(move || {
             let mut new_engine = DataEngine::new();
+            new_engine.parameters_hash = parameters_hash;
             let tx_progress = tx.clone();
             let start_time = std::time::Instant::now();
 
@@ -468,18 +661,18 @@ pub fn Home(
 
             let elapsed_ms = start_time.elapsed().as_millis();
             if result.is_ok() {
-                append_log(
+                telemetry::record(&TelemetryEvent::new(
                     &report_name_log,
                     "Processamento DBISAM -> Memória DataFusion",
                     elapsed_ms,
-                );
+                ));
             }
 
             match result {
                 Ok(_) => {
                     let _ = tx.send(LoaderMsg::Progress(100.0));
                     std::thread::sleep(std::time::Duration::from_millis(50));
-                    let _ = tx.send(LoaderMsg::Finished(new_engine, sql_to_process));
+                    let _ = tx.send(LoaderMsg::Finished(new_engine, sql_to_process, elapsed_ms));
                 }
                 Err(err) => {
                     if err != "Operação cancelada pelo usuário" {
@@ -490,18 +683,213 @@ pub fn Home(
         });
     };
 
-    let delete_selected = {
+    // FAVORITOS E RECENTES
+    // Executa direto com os valores da última execução. Sem histórico (ou se os
+    // parâmetros mudaram e não fecham mais), abre o formulário de parâmetros.
+    let mut rerun_report = move |path: String| {
+        let config = load_report(Path::new(&path)).ok();
+        let last_values = run_history::last_parameters(&path);
+        let Some(config) = config else {
+            prepare_report(path);
+            return;
+        };
+        let values = match last_values {
+            Some(values) => values,
+            None if config.parametros.is_empty() => HashMap::new(),
+            None => {
+                prepare_report(path);
+                return;
+            }
+        };
+        match apply_parameters(&config.query_sql, &config.parametros, &values) {
+            Ok(sql) => execute_report((path, sql, values)),
+            Err(_) => prepare_report(path),
+        }
+    };
+
+    let mut toggle_favorite = move |path: String| match run_history::toggle_favorite(&path) {
+        Ok(list) => favorites.set(list),
+        Err(e) => {
+            status_msg.set(e);
+            status_modal_type.set(StatusType::Error);
+            show_status_modal.set(true);
+        }
+    };
+
+    // Só pede confirmação quando a seleção é mesmo um relatório
+    let ask_delete = {
         let selected_name_delete = selected_name.clone();
         move |_| {
-            if !selected_name_delete.is_empty() && selected_name_delete != "Nenhum selecionado" {
-                if fs::remove_file(&selected_name_delete).is_ok() {
+            let path = Path::new(&selected_name_delete);
+            if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
+                confirm_delete.set(true);
+            } else {
+                status_msg.set("Selecione um relatório na lista para excluir.".to_string());
+                status_modal_type.set(StatusType::Error);
+                show_status_modal.set(true);
+            }
+        }
+    };
+
+    let delete_selected = {
+        let selected_name_delete = selected_name.clone();
+        move |_| match trash::move_to_trash(Path::new("relatorios"), Path::new(&selected_name_delete)) {
+            Ok(_) => {
+                reports.set(read_reports(Path::new("relatorios")));
+                favorites.set(run_history::load_favorites());
+                recents.set(run_history::recent_reports(RECENT_LIMIT));
+                on_select.call(String::from("Nenhum selecionado"));
+            }
+            Err(e) => {
+                status_msg.set(e);
+                status_modal_type.set(StatusType::Error);
+                show_status_modal.set(true);
+            }
+        }
+    };
+
+    // PASTAS
+    let mut show_error = move |msg: String| {
+        status_msg.set(msg);
+        status_modal_type.set(StatusType::Error);
+        show_status_modal.set(true);
+    };
+
+    let toggle_folder = move |path: String| {
+        let key = tree_key(&path);
+        let mut set = collapsed.write();
+        if !set.remove(&key) {
+            set.insert(key);
+        }
+        let _ = report_tree::save_collapsed(Path::new("relatorios"), &set);
+    };
+
+    // Solta o relatório arrastado na pasta. O primeiro destino (a pasta mais interna)
+    // consome o arraste; o da raiz só age quando o soltar foi fora de qualquer pasta.
+    let drop_into = {
+        let selected_name_drop = selected_name.clone();
+        move |folder: String| {
+            let Some(source) = dragging.write().take() else {
+                return;
+            };
+            match report_tree::move_report(Path::new("relatorios"), Path::new(&source), Path::new(&folder)) {
+                Ok(target) => {
+                    let target = target.to_string_lossy().to_string();
+                    let _ = run_history::report_moved(&source, &target);
+                    favorites.set(run_history::load_favorites());
+                    recents.set(run_history::recent_reports(RECENT_LIMIT));
                     reports.set(read_reports(Path::new("relatorios")));
-                    on_select.call(String::from("Nenhum selecionado"));
+                    if selected_name_drop == source {
+                        on_select.call(target);
+                    }
+                }
+                Err(e) => show_error(e),
+            }
+        }
+    };
+    let mut drop_into_root = drop_into.clone();
+
+    let submit_folder_name = {
+        let selected_name_rename = selected_name.clone();
+        move |name: String| {
+            let current = selected_folder.read().clone();
+            match folder_prompt() {
+                FolderPrompt::Create => {
+                    let parent = if current.is_empty() { "relatorios".to_string() } else { current.clone() };
+                    match report_tree::create_folder(Path::new("relatorios"), Path::new(&parent), &name) {
+                        Ok(created) => {
+                            let mut set = collapsed.write();
+                            set.remove(&tree_key(&parent));
+                            let _ = report_tree::save_collapsed(Path::new("relatorios"), &set);
+                            drop(set);
+                            reports.set(read_reports(Path::new("relatorios")));
+                            selected_folder.set(created.to_string_lossy().to_string());
+                        }
+                        Err(e) => show_error(e),
+                    }
+                }
+                FolderPrompt::Rename => {
+                    match report_tree::rename_folder(Path::new("relatorios"), Path::new(&current), &name) {
+                        Ok(renamed) => {
+                            let new_path = renamed.to_string_lossy().to_string();
+                            let (old_key, new_key) = (tree_key(&current), tree_key(&new_path));
+                            let moved: BTreeSet<String> = collapsed
+                                .read()
+                                .iter()
+                                .map(|k| match k.strip_prefix(&old_key) {
+                                    Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", new_key, rest),
+                                    _ => k.clone(),
+                                })
+                                .collect();
+                            let _ = report_tree::save_collapsed(Path::new("relatorios"), &moved);
+                            collapsed.set(moved);
+                            let _ = run_history::report_moved(&current, &new_path);
+                            favorites.set(run_history::load_favorites());
+                            recents.set(run_history::recent_reports(RECENT_LIMIT));
+
+                            if let Some(rest) = selected_name_rename.strip_prefix(&current) {
+                                if rest.starts_with(['/', '\\']) {
+                                    on_select.call(format!("{}{}", new_path, rest));
+                                }
+                            }
+                            reports.set(read_reports(Path::new("relatorios")));
+                            selected_folder.set(new_path);
+                        }
+                        Err(e) => show_error(e),
+                    }
                 }
             }
         }
     };
 
+    let delete_folder = move |_| {
+        let current = selected_folder.read().clone();
+        match report_tree::delete_empty_folder(Path::new("relatorios"), Path::new(&current)) {
+            Ok(_) => {
+                let mut set = collapsed.write();
+                set.remove(&tree_key(&current));
+                let _ = report_tree::save_collapsed(Path::new("relatorios"), &set);
+                drop(set);
+                reports.set(read_reports(Path::new("relatorios")));
+                selected_folder.set(String::new());
+            }
+            Err(e) => show_error(e),
+        }
+    };
+
+    let folder_prompt_initial = match folder_prompt() {
+        FolderPrompt::Create => String::new(),
+        FolderPrompt::Rename => Path::new(&*selected_folder.read())
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    let folder_prompt_title = match folder_prompt() {
+        FolderPrompt::Create => "📁 Nova Pasta",
+        FolderPrompt::Rename => "✏️ Renomear Pasta",
+    };
+    let tree_collapsed = collapsed.read().clone();
+    let tree_handlers = TreeHandlers {
+        on_select: EventHandler::new(move |path: String| {
+            selected_folder.set(String::new());
+            on_select.call(path);
+        }),
+        on_open_named: EventHandler::new(prepare_report),
+        on_select_folder: EventHandler::new(move |path: String| selected_folder.set(path)),
+        on_toggle: EventHandler::new(toggle_folder),
+        on_drag_start: EventHandler::new(move |path: String| dragging.set(Some(path))),
+        on_drop: EventHandler::new(drop_into),
+        on_toggle_favorite: EventHandler::new(toggle_favorite),
+    };
+    let favorite_items: Vec<String> = favorites
+        .read()
+        .iter()
+        .filter(|path| run_history::report_available(path))
+        .cloned()
+        .collect();
+    let recent_items = recents.read().clone();
+    let show_quick_access = !is_searching;
+
     rsx! {
         div { class: "app-container",
             StatusModal {
@@ -511,7 +899,40 @@ pub fn Home(
                 sql_content: last_sql(),
                 on_close: move |_| show_status_modal.set(false)
             }
-            LogsModal { show: show_logs, on_close: move |_| show_logs.set(false) }
+            PerformanceModal { show: show_performance, on_close: move |_| show_performance.set(false) }
+            AgendaModal { show: show_agenda, on_close: move |_| show_agenda.set(false) }
+            ConfirmModal {
+                show: confirm_delete,
+                title: "Excluir relatório".to_string(),
+                message: format!("Mover '{}' para a lixeira? Ele pode ser restaurado depois em 🗑️ Lixeira.", selected_name),
+                confirm_label: "Mover para a Lixeira".to_string(),
+                on_confirm: delete_selected
+            }
+            if show_folder_prompt() {
+                PromptModal {
+                    show: show_folder_prompt,
+                    title: folder_prompt_title.to_string(),
+                    label: "Nome da pasta:".to_string(),
+                    initial: folder_prompt_initial,
+                    on_submit: submit_folder_name
+                }
+            }
+            ConfirmModal {
+                show: confirm_folder_delete,
+                title: "Excluir pasta".to_string(),
+                message: format!("Excluir a pasta '{}'? Só pastas vazias podem ser excluídas.", selected_folder()),
+                confirm_label: "Excluir Pasta".to_string(),
+                on_confirm: delete_folder
+            }
+            TrashModal {
+                show: show_trash,
+                on_restored: move |path: String| {
+                    reports.set(read_reports(Path::new("relatorios")));
+                    favorites.set(run_history::load_favorites());
+                    recents.set(run_history::recent_reports(RECENT_LIMIT));
+                    on_select.call(path);
+                }
+            }
             ParamsModal {
                 show: show_params_modal,
                 report_config: current_report_config,
@@ -525,20 +946,120 @@ pub fn Home(
                 div { class: "sidebar",
                     button { class: "btn-classic", onclick: move |_| { on_select.call(String::from("relatorios/novo_relatorio.json")); on_edit.call(()); }, "✚ Novo" }
                     button { class: "btn-classic", onclick: move |_| on_edit.call(()), "✎ Editar" }
-                    button { class: "btn-classic btn-danger", onclick: delete_selected, "✖ Excluir" }
+                    button { class: "btn-classic btn-danger", onclick: ask_delete, "✖ Excluir" }
+                    button { class: "btn-classic", onclick: move |_| show_trash.set(true), "🗑️ Lixeira" }
+                    button { class: "btn-classic", title: "Cria dentro da pasta selecionada (ou na raiz)", onclick: move |_| { folder_prompt.set(FolderPrompt::Create); show_folder_prompt.set(true); }, "📁 Nova Pasta" }
+                    button { class: "btn-classic", disabled: selected_folder().is_empty(), onclick: move |_| { folder_prompt.set(FolderPrompt::Rename); show_folder_prompt.set(true); }, "✏️ Renomear Pasta" }
+                    button { class: "btn-classic", disabled: selected_folder().is_empty(), onclick: move |_| confirm_folder_delete.set(true), "🗂️ Excluir Pasta" }
                     div { class: "sidebar-spacer" }
-                    button { class: "btn-classic btn-dark", onclick: move |_| show_logs.set(true), "📜 Logs" }
+                    button { class: "btn-classic", onclick: move |_| show_agenda.set(true), "🗓️ Agenda" }
+                    button { class: "btn-classic btn-dark", onclick: move |_| show_performance.set(true), "📈 Desempenho" }
                 }
 
                 div { class: "main-view",
                     div { class: "top-toolbar",
-                        input { class: "input-classic search-input", placeholder: "Pesquisa...", value: "{search_text}", oninput: move |evt| search_text.set(evt.value()) }
+                        input { class: "input-classic search-input", placeholder: "Pesquisar nome, descrição, SQL, tabela...", value: "{search_text}", oninput: move |evt| search_text.set(evt.value()) }
+                        select { class: "input-classic tag-filter", title: "Mostrar só os relatórios com a tag", value: "{active_tag}",
+                            onchange: move |evt| tag_filter.set(evt.value()),
+                            option { value: "", "🏷️ Todas as tags" }
+                            {all_tags.iter().map(|t| rsx! { option { key: "{t}", value: "{t}", "🏷️ {t}" } })}
+                        }
                         span { "Total: {total_files}" }
                         input { class: "input-classic selected-report-display", readonly: true, value: "{selected_name}" }
                     }
-                    div { class: "tree-container",
-                        ul { class: "tree-list",
-                            {render_tree(&filtered_items, &selected_name, on_select, EventHandler::new(prepare_report))}
+                    if show_quick_access {
+                        div { class: "quick-access",
+                            div { class: "quick-section",
+                                div { class: "quick-title", "⭐ Favoritos" }
+                                if favorite_items.is_empty() {
+                                    div { class: "quick-empty", "Clique na ☆ ao lado de um relatório para fixá-lo aqui." }
+                                }
+                                ul { class: "quick-list",
+                                    {favorite_items.iter().map(|path| {
+                                        let label = report_label(path);
+                                        let (p_select, p_open, p_run, p_fav) = (path.clone(), path.clone(), path.clone(), path.clone());
+                                        let is_selected = selected_name == *path;
+                                        rsx! {
+                                            li { key: "{path}", title: "{path}",
+                                                button { class: "quick-run", title: "Executar com os últimos parâmetros", disabled: is_loading(), onclick: move |_| rerun_report(p_run.clone()), "▶" }
+                                                span { class: if is_selected { "file-name selected" } else { "file-name" },
+                                                    onclick: move |_| on_select.call(p_select.clone()),
+                                                    ondoubleclick: move |_| prepare_report(p_open.clone()),
+                                                    "{label}"
+                                                }
+                                                span { class: "fav-toggle active", title: "Remover dos favoritos", onclick: move |_| toggle_favorite(p_fav.clone()), "★" }
+                                            }
+                                        }
+                                    })}
+                                }
+                            }
+                            div { class: "quick-section",
+                                div { class: "quick-title", "🕘 Recentes" }
+                                if recent_items.is_empty() {
+                                    div { class: "quick-empty", "Os relatórios executados aparecem aqui." }
+                                }
+                                ul { class: "quick-list",
+                                    {recent_items.iter().map(|run| {
+                                        let label = report_label(&run.relatorio);
+                                        let details = format!("{} · {} linhas · {}", run.inicio, run.linhas, format_duration(run.duracao_ms as u64 * 1_000_000));
+                                        let (p_select, p_open, p_run) = (run.relatorio.clone(), run.relatorio.clone(), run.relatorio.clone());
+                                        let is_selected = selected_name == run.relatorio;
+                                        rsx! {
+                                            li { key: "{run.relatorio}", title: "{run.relatorio}",
+                                                button { class: "quick-run", title: "Executar de novo com os mesmos parâmetros", disabled: is_loading(), onclick: move |_| rerun_report(p_run.clone()), "▶" }
+                                                span { class: if is_selected { "file-name selected" } else { "file-name" },
+                                                    onclick: move |_| on_select.call(p_select.clone()),
+                                                    ondoubleclick: move |_| prepare_report(p_open.clone()),
+                                                    "{label}"
+                                                }
+                                                span { class: "quick-details", "{details}" }
+                                            }
+                                        }
+                                    })}
+                                }
+                            }
+                        }
+                    }
+                    div { class: "tree-container", prevent_default: "ondragover ondrop",
+                        ondragover: move |_| {},
+                        ondrop: move |_| drop_into_root("relatorios".to_string()),
+                        if is_searching {
+                            if search_hits.is_empty() {
+                                div { class: "empty-msg", "Nenhum relatório encontrado." }
+                            }
+                            ul { class: "search-results",
+                                {search_hits.iter().map(|hit| {
+                                    let (p_select, p_open) = (hit.path.clone(), hit.path.clone());
+                                    let is_selected = selected_name == hit.path;
+                                    rsx! {
+                                        li { key: "{hit.path}", class: "search-hit",
+                                            onclick: move |_| on_select.call(p_select.clone()),
+                                            ondoubleclick: move |_| prepare_report(p_open.clone()),
+                                            div { class: "search-hit-title",
+                                                span { class: "tree-icon", "📊" }
+                                                span { class: if is_selected { "file-name selected" } else { "file-name" }, "{hit.nome}" }
+                                                span { class: "search-hit-path", "{hit.path}" }
+                                            }
+                                            {hit.matches.iter().map(|m| {
+                                                let place = match m.linha {
+                                                    Some(n) => format!("{}, linha {}", m.campo.label(), n),
+                                                    None => m.campo.label().to_string(),
+                                                };
+                                                rsx! {
+                                                    div { class: "search-hit-match",
+                                                        span { class: "search-hit-field", "{place}" }
+                                                        span { class: "search-hit-snippet", "{m.trecho}" }
+                                                    }
+                                                }
+                                            })}
+                                        }
+                                    }
+                                })}
+                            }
+                        } else {
+                            ul { class: "tree-list",
+                                {render_tree(&visible_items, &selected_name, &selected_folder(), &tree_collapsed, &favorite_items, &tree_handlers)}
+                            }
                         }
                     }
                 }
@@ -550,7 +1071,7 @@ pub fn Home(
                     div { class: "loading-group-minimal",
                         span { "{*progress.read() as i32}%" }
                         div { class: "progress-bar-mini-bg", div { class: "progress-bar-mini-fill", style: "width: {progress.read()}%" } }
-                        button { class: "btn-abort-link", onclick: move |_| { cancel_flag.read().store(true, Ordering::SeqCst); is_loading.set(false); progress.set(0.0); }, "Cancelar" }
+                        button { class: "btn-abort-link", onclick: move |_| { cancel_flag.read().store(true, Ordering::SeqCst); is_loading.set(false); progress.set(0.0); pending_run.set(None); }, "Cancelar" }
                     }
                 }
             }
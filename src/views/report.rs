@@ -1,13 +1,18 @@
+use crate::components::plan_modal::PlanModal;
 use crate::components::status_modal::{StatusModal, StatusType};
-use crate::core::engine::{DataEngine, append_log};
+use crate::core::engine::DataEngine;
+use crate::core::export::{write_csv, write_xlsx};
+use crate::core::query_plan::{PlanNode, parse_plan};
+use crate::core::run_history::{self, ReportRunEntry};
+use crate::core::telemetry::{self, TelemetryEvent};
 use dioxus::prelude::*;
-use rust_xlsxwriter::*;
 
 #[component]
 pub fn ViewReport(
     on_back: EventHandler<MouseEvent>,
     engine: Signal<DataEngine>,
     query_sql: String,
+    mut pending_run: Signal<Option<ReportRunEntry>>,
 ) -> Element {
     use_drop(move || {
         engine.write().clear_memory();
@@ -24,7 +29,13 @@ pub fn ViewReport(
     let mut total_rows_count = use_signal(|| 0usize);
     let mut current_offset = use_signal(|| 0usize);
 
+    let mut show_plan = use_signal(|| false);
+    let mut plan_nodes = use_signal(|| Vec::<PlanNode>::new());
+    let mut plan_loading = use_signal(|| false);
+    let mut plan_error = use_signal(|| String::new());
+
     let sql_to_query = query_sql.clone();
+    let sql_to_explain = query_sql.clone();
 
     let report_task = use_resource(move || {
         let engine_handle = engine;
@@ -32,17 +43,22 @@ pub fn ViewReport(
 
         async move {
             let start_time = std::time::Instant::now();
-            let res = engine_handle
-                .read()
-                .execute_user_sql(&sql, "Tela de Visualização");
+            let report_name = engine_handle.read().report_name.clone();
+            let res = engine_handle.read().execute_user_sql(&sql, &report_name);
 
             match &res {
                 Ok((cols, total)) => {
                     headers.set(cols.clone());
                     total_rows_count.set(*total);
+                    let display_start = std::time::Instant::now();
                     let first_chunk = engine_handle.read().get_rows_slice(0, 200);
                     visible_rows.set(first_chunk);
                     current_offset.set(200);
+                    telemetry::record(&TelemetryEvent::new(
+                        &report_name,
+                        telemetry::STAGE_DISPLAY,
+                        display_start.elapsed().as_millis(),
+                    ));
                 }
                 Err(e) => {
                     status_msg.set(e.clone());
@@ -52,11 +68,20 @@ pub fn ViewReport(
                 }
             }
 
-            append_log(
-                "Visualização",
-                "Carga Inicial 200",
-                start_time.elapsed().as_millis(),
-            );
+            let elapsed_ms = start_time.elapsed().as_millis();
+
+            let finished_run = pending_run.write().take();
+            if let Some(mut run) = finished_run {
+                run.duracao_ms += elapsed_ms;
+                match &res {
+                    Ok((_, total)) => run.linhas = *total,
+                    Err(e) => {
+                        run.sucesso = false;
+                        run.erro = e.clone();
+                    }
+                }
+                run_history::record_run(&run);
+            }
             res
         }
     });
@@ -86,18 +111,7 @@ pub fn ViewReport(
 
             spawn(async move {
                 let export_result = tokio::task::spawn_blocking(move || {
-                    let all_data = engine_instance.get_rows_slice(0, total);
-                    let mut file_content = String::with_capacity(total * 100);
-
-                    file_content.push_str(&cols.join(";"));
-                    file_content.push('\n');
-
-                    for row in all_data {
-                        file_content.push_str(&row.join(";"));
-                        file_content.push('\n');
-                    }
-
-                    std::fs::write(&path, file_content)
+                    write_csv(&engine_instance, &cols, total, &path)
                 })
                 .await;
 
@@ -133,40 +147,7 @@ pub fn ViewReport(
 
             spawn(async move {
                 let export_result = tokio::task::spawn_blocking(move || {
-                    let mut workbook = Workbook::new();
-                    let worksheet = workbook.add_worksheet();
-                    let header_format = Format::new().set_bold();
-
-                    for (col_idx, header_text) in cols.iter().enumerate() {
-                        let _ = worksheet.write_with_format(
-                            0,
-                            col_idx as u16,
-                            header_text,
-                            &header_format,
-                        );
-                    }
-
-                    let all_data = engine_instance.get_rows_slice(0, total);
-
-                    for (row_idx, row_data) in all_data.iter().enumerate() {
-                        for (col_idx, cell_value) in row_data.iter().enumerate() {
-                            if let Ok(num) = cell_value.parse::<f64>() {
-                                let _ = worksheet.write_number(
-                                    (row_idx + 1) as u32,
-                                    col_idx as u16,
-                                    num,
-                                );
-                            } else {
-                                let _ = worksheet.write(
-                                    (row_idx + 1) as u32,
-                                    col_idx as u16,
-                                    cell_value,
-                                );
-                            }
-                        }
-                    }
-
-                    workbook.save(&path)
+                    write_xlsx(&engine_instance, &cols, total, &path)
                 })
                 .await;
 
@@ -197,6 +178,29 @@ pub fn ViewReport(
         )
     };
 
+    let show_query_plan = move |_| {
+        if plan_loading() {
+            return;
+        }
+        let sql = sql_to_explain.clone();
+        let engine_clone = engine.read().clone();
+        plan_error.set(String::new());
+        plan_loading.set(true);
+        show_plan.set(true);
+
+        spawn(async move {
+            let result =
+                tokio::task::spawn_blocking(move || engine_clone.explain_analyze(&sql, false))
+                    .await
+                    .unwrap_or(Err("Erro crítico ao gerar o plano".to_string()));
+            match result {
+                Ok(text) => plan_nodes.set(parse_plan(&text)),
+                Err(e) => plan_error.set(e),
+            }
+            plan_loading.set(false);
+        });
+    };
+
     rsx! {
         div { class: "app-container",
             StatusModal {
@@ -206,12 +210,20 @@ pub fn ViewReport(
                 sql_content: modal_sql_content(),
                 on_close: move |_| show_status_modal.set(false)
             }
+            PlanModal {
+                show: show_plan,
+                nodes: plan_nodes(),
+                loading: plan_loading(),
+                error: plan_error(),
+                on_close: move |_| show_plan.set(false)
+            }
 
             div { class: "middle-section",
                 div { class: "sidebar",
                     button { class: "btn-classic", onclick: move |evt| { engine.write().clear_memory(); on_back.call(evt)}, "🏠 Voltar" }
                     button { class: "btn-classic", onclick: export_csv, "💾 Exportar CSV" }
                     button { class: "btn-classic", onclick: export_xlsx, "📊 Exportar Excel" }
+                    button { class: "btn-classic", onclick: show_query_plan, "🔍 Plano de Execução" }
                 }
 
                 div { class: "main-view report-view-container",